@@ -25,6 +25,20 @@ from fluxreel_core import (
     ColorAdjustEffect,
     ChromaKeyEffect,
     NoiseEffect,
+    DisplacementEffect,
+    LensDistortionEffect,
+    RippleEffect,
+    WaveEffect,
+    TwirlEffect,
+    BulgeEffect,
+    CornerPinEffect,
+    PosterizeEffect,
+    ThresholdEffect,
+    HalftoneEffect,
+    PixelateEffect,
+    EdgeDetectEffect,
+    EmbossEffect,
+    VignetteEffect,
     Rectangle,
     Circle,
     Polygon,
@@ -43,11 +57,20 @@ from fluxreel_core import (
     ColorGrading,
     ColorCurves,
     LUT,
+    ColorConfig,
+    convert_color,
+    scope_histogram,
+    scope_waveform,
+    scope_rgb_parade,
+    scope_vectorscope,
     Mask,
     RectangleMask,
     EllipseMask,
+    apply_track_matte,
     MotionTracker,
+    PlanarTracker,
     Stabilizer,
+    OpticalFlow,
     Equalizer,
     Compressor,
     Reverb,
@@ -150,6 +173,20 @@ __all__ = [
     "ColorAdjustEffect",
     "ChromaKeyEffect",
     "NoiseEffect",
+    "DisplacementEffect",
+    "LensDistortionEffect",
+    "RippleEffect",
+    "WaveEffect",
+    "TwirlEffect",
+    "BulgeEffect",
+    "CornerPinEffect",
+    "PosterizeEffect",
+    "ThresholdEffect",
+    "HalftoneEffect",
+    "PixelateEffect",
+    "EdgeDetectEffect",
+    "EmbossEffect",
+    "VignetteEffect",
     # Geometry
     "Rectangle",
     "Circle",
@@ -161,13 +198,25 @@ __all__ = [
     "ColorGrading",
     "ColorCurves",
     "LUT",
+    # Color management
+    "ColorConfig",
+    "convert_color",
+    # Scopes
+    "scope_histogram",
+    "scope_waveform",
+    "scope_rgb_parade",
+    "scope_vectorscope",
     # Masking
     "Mask",
     "RectangleMask",
     "EllipseMask",
+    "apply_track_matte",
     # Motion Tracking
     "MotionTracker",
+    "PlanarTracker",
     "Stabilizer",
+    # Optical Flow
+    "OpticalFlow",
     # Professional Audio
     "Equalizer",
     "Compressor",
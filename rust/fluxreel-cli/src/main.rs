@@ -1,5 +1,7 @@
 use anyhow::{bail, Result};
 use clap::{Parser, Subcommand};
+use fluxreel_core::scopes;
+use fluxreel_core::{BitDepth, Frame};
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -99,6 +101,20 @@ enum Commands {
         /// Script file to validate
         input: PathBuf,
     },
+    /// Compute scopes for a rendered frame
+    Scopes {
+        /// Rendered frame (PNG, TIFF, EXR, ...)
+        input: PathBuf,
+        /// Scope to compute: all, histogram, waveform, vectorscope, parade
+        #[arg(short, long, default_value = "all")]
+        scope: String,
+        /// Directory for the scope images (defaults to the frame's directory)
+        #[arg(short, long)]
+        output_dir: Option<PathBuf>,
+        /// Waveform height and vectorscope size in pixels
+        #[arg(long, default_value_t = 256)]
+        size: u32,
+    },
 }
 
 fn main() -> Result<()> {
@@ -154,6 +170,14 @@ fn main() -> Result<()> {
         Commands::Validate { input } => {
             validate_script(&input)?;
         }
+        Commands::Scopes {
+            input,
+            scope,
+            output_dir,
+            size,
+        } => {
+            run_scopes(&input, &scope, output_dir.as_deref(), size)?;
+        }
     }
 
     Ok(())
@@ -341,6 +365,80 @@ fn validate_script(input: &Path) -> Result<()> {
     Ok(())
 }
 
+fn run_scopes(
+    input: &Path,
+    scope: &str,
+    output_dir: Option<&Path>,
+    size: u32,
+) -> Result<Vec<PathBuf>> {
+    if !SCOPES.contains(&scope) {
+        bail!("Unknown scope '{scope}'. Use: {}", SCOPES.join(", "));
+    }
+    let frame = Frame::open(input)?;
+    let (width, height) = (frame.width, frame.height);
+    let dir = output_dir
+        .map(Path::to_path_buf)
+        .or_else(|| input.parent().map(Path::to_path_buf))
+        .unwrap_or_default();
+    let stem = input
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("frame");
+    let wants = |name: &str| scope == "all" || scope == name;
+
+    println!("Scopes for {} ({}x{})", input.display(), width, height);
+
+    if wants("histogram") {
+        let histogram = scopes::histogram(&frame.data, 256);
+        let total = (width * height).max(1) as f32;
+        let mean = histogram
+            .luma
+            .iter()
+            .enumerate()
+            .map(|(i, c)| i as f32 / 255.0 * *c as f32)
+            .sum::<f32>()
+            / total;
+        println!("  Mean luma: {:.3}", mean);
+        println!(
+            "  Crushed blacks: {:.2}%",
+            histogram.luma[0] as f32 / total * 100.0
+        );
+        println!(
+            "  Clipped whites: {:.2}%",
+            histogram.luma[255] as f32 / total * 100.0
+        );
+    }
+
+    let mut written = Vec::new();
+    let mut save = |name: &str, image: Frame<u8>| -> Result<()> {
+        let path = dir.join(format!("{stem}_{name}.png"));
+        image.to_f32().save(&path, BitDepth::Eight)?;
+        println!("  Wrote {}", path.display());
+        written.push(path);
+        Ok(())
+    };
+    if wants("waveform") {
+        save(
+            "waveform",
+            scopes::waveform(&frame.data, width, height, size).render([0.4, 1.0, 0.4]),
+        )?;
+    }
+    if wants("parade") {
+        save(
+            "parade",
+            scopes::rgb_parade(&frame.data, width, height, size).render(),
+        )?;
+    }
+    if wants("vectorscope") {
+        save(
+            "vectorscope",
+            scopes::vectorscope(&frame.data, width, height, size).render([1.0, 1.0, 1.0]),
+        )?;
+    }
+    Ok(written)
+}
+
+const SCOPES: &[&str] = &["all", "histogram", "waveform", "vectorscope", "parade"];
 const RESOLUTION_PRESETS: &[&str] = &[
     "480p (854x480)",
     "720p (1280x720)",
@@ -358,6 +456,8 @@ const FEATURE_MODULES: &[&str] = &[
     "transitions",
     "effects",
     "color_grading",
+    "color_management",
+    "scopes",
     "masking",
     "motion_tracking",
     "audio_pro",
@@ -468,4 +568,23 @@ mod tests {
 
         fs::remove_file(temp_path).unwrap();
     }
+
+    #[test]
+    fn scopes_are_written_for_a_frame() {
+        let dir =
+            std::env::temp_dir().join(format!("fluxreel_cli_scopes_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("shot.png");
+        let data = (0..16 * 8 * 4).map(|i| (i % 7) as f32 / 6.0).collect();
+        Frame::from_raw(16, 8, data)
+            .unwrap()
+            .save(&input, BitDepth::Eight)
+            .unwrap();
+
+        let written = run_scopes(&input, "all", None, 32).unwrap();
+        assert_eq!(written.len(), 3);
+        assert!(written.iter().all(|p| p.exists()));
+        assert!(run_scopes(&input, "spectrum", None, 32).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -1,5 +1,6 @@
 use pyo3::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Keyframe {
@@ -88,6 +89,45 @@ impl AnimationSequence {
     }
 }
 
+/// Keyframe tracks for the animatable numeric properties of an effect
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PropertyTracks {
+    pub tracks: HashMap<String, Vec<Keyframe>>,
+}
+
+impl PropertyTracks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a keyframe to the track of `property`, keeping it sorted by time
+    pub fn add_keyframe(&mut self, property: &str, time: f32, value: f32, easing: &str) {
+        let track = self.tracks.entry(property.to_string()).or_default();
+        track.push(Keyframe {
+            time,
+            value,
+            easing: easing.to_string(),
+        });
+        track.sort_by(|a, b| a.time.total_cmp(&b.time));
+    }
+
+    /// Value of `property` at `time`, or `fallback` if it has no keyframes
+    pub fn value_at(&self, property: &str, time: f32, fallback: f32) -> f32 {
+        match self.tracks.get(property) {
+            Some(track) if !track.is_empty() => interpolate_keyframes(track, time),
+            _ => fallback,
+        }
+    }
+
+    pub fn is_animated(&self, property: &str) -> bool {
+        self.tracks.get(property).is_some_and(|t| !t.is_empty())
+    }
+
+    pub fn clear(&mut self, property: &str) {
+        self.tracks.remove(property);
+    }
+}
+
 /// Interpolate value at given time using keyframes
 pub fn interpolate_keyframes(keyframes: &[Keyframe], time: f32) -> f32 {
     if keyframes.is_empty() {
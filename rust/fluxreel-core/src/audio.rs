@@ -1,6 +1,242 @@
+use crate::animation::{Animation, Keyframe};
+use crate::speed_ramping::TimeRemap;
 use pyo3::prelude::*;
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::f64::consts::PI;
+use std::fs::File;
+use std::path::Path;
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+use thiserror::Error;
+
+/// Rate tracks are resampled to unless another is asked for; 48 kHz is the
+/// usual rate for video deliverables
+pub const PROJECT_SAMPLE_RATE: u32 = 48000;
+/// Zero crossings of the sinc kernel on each side when resampling
+const RESAMPLE_ZERO_CROSSINGS: f64 = 16.0;
+
+#[derive(Debug, Error)]
+pub enum AudioError {
+    #[error("{path}: {source}")]
+    Io {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("{path}: {message}")]
+    Decode { path: String, message: String },
+    #[error("{0}: no decodable audio track")]
+    NoTrack(String),
+}
+
+impl From<AudioError> for PyErr {
+    fn from(err: AudioError) -> PyErr {
+        match err {
+            AudioError::Io { .. } => PyErr::new::<pyo3::exceptions::PyIOError, _>(err.to_string()),
+            _ => PyErr::new::<pyo3::exceptions::PyValueError, _>(err.to_string()),
+        }
+    }
+}
+
+/// Interleaved f32 samples as decoded from a file
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedAudio {
+    pub samples: Vec<f32>,
+    pub sample_rate: u32,
+    pub channels: u32,
+}
+
+/// Decode a WAV, FLAC, MP3 or Ogg Vorbis file. Corrupt packets are skipped
+/// rather than failing the whole file.
+pub fn decode_file(path: &Path) -> Result<DecodedAudio, AudioError> {
+    let name = path.display().to_string();
+    let decode_err = |err: SymphoniaError| AudioError::Decode {
+        path: name.clone(),
+        message: err.to_string(),
+    };
+    let file = File::open(path).map_err(|source| AudioError::Io {
+        path: name.clone(),
+        source,
+    })?;
+    let stream = MediaSourceStream::new(Box::new(file), Default::default());
+    let mut hint = Hint::new();
+    if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+        hint.with_extension(ext);
+    }
+    let probed = symphonia::default::get_probe()
+        .format(
+            &hint,
+            stream,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .map_err(decode_err)?;
+    let mut format = probed.format;
+    let track = format
+        .tracks()
+        .iter()
+        .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or_else(|| AudioError::NoTrack(name.clone()))?;
+    let track_id = track.id;
+    let mut sample_rate = track.codec_params.sample_rate.unwrap_or(0);
+    let mut channels = track.codec_params.channels.map_or(0, |c| c.count() as u32);
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .map_err(decode_err)?;
+
+    let mut samples = Vec::new();
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(SymphoniaError::IoError(err))
+                if err.kind() == std::io::ErrorKind::UnexpectedEof =>
+            {
+                break
+            }
+            Err(SymphoniaError::ResetRequired) => break,
+            Err(err) => return Err(decode_err(err)),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+        match decoder.decode(&packet) {
+            Ok(buffer) => {
+                let spec = *buffer.spec();
+                sample_rate = spec.rate;
+                channels = spec.channels.count() as u32;
+                let mut interleaved = SampleBuffer::<f32>::new(buffer.capacity() as u64, spec);
+                interleaved.copy_interleaved_ref(buffer);
+                samples.extend_from_slice(interleaved.samples());
+            }
+            Err(SymphoniaError::DecodeError(_)) => continue,
+            Err(err) => return Err(decode_err(err)),
+        }
+    }
+    if sample_rate == 0 || channels == 0 {
+        return Err(AudioError::NoTrack(name));
+    }
+    Ok(DecodedAudio {
+        samples,
+        sample_rate,
+        channels,
+    })
+}
+
+/// Band-limited resampling of interleaved audio with a Hann-windowed sinc.
+/// Downsampling lowers the cutoff to the new Nyquist so nothing aliases.
+pub fn resample(samples: &[f32], channels: usize, from: u32, to: u32) -> Vec<f32> {
+    if from == to || samples.is_empty() || channels == 0 {
+        return samples.to_vec();
+    }
+    let ratio = to as f64 / from as f64;
+    let frames_in = (samples.len() / channels) as isize;
+    let frames_out = (frames_in as f64 * ratio).round() as usize;
+    let cutoff = ratio.min(1.0);
+    let half = (RESAMPLE_ZERO_CROSSINGS / cutoff).ceil() as isize;
+
+    let mut out = vec![0.0; frames_out * channels];
+    out.par_chunks_mut(channels)
+        .enumerate()
+        .for_each(|(j, frame)| {
+            let position = j as f64 / ratio;
+            let centre = position.floor() as isize;
+            let mut total = 0.0;
+            for i in (centre - half + 1)..=(centre + half) {
+                if !(0..frames_in).contains(&i) {
+                    continue;
+                }
+                let x = position - i as f64;
+                let window = 0.5 + 0.5 * (PI * x / half as f64).cos();
+                let arg = PI * x * cutoff;
+                let sinc = if arg.abs() < 1e-9 {
+                    1.0
+                } else {
+                    arg.sin() / arg
+                };
+                let weight = sinc * window;
+                total += weight;
+                for (c, o) in frame.iter_mut().enumerate() {
+                    *o += samples[i as usize * channels + c] * weight as f32;
+                }
+            }
+            if total.abs() > 1e-9 {
+                frame.iter_mut().for_each(|o| *o /= total as f32);
+            }
+        });
+    out
+}
+
+/// Default RMS window in seconds, about one video frame
+pub const DEFAULT_RMS_WINDOW: f32 = 0.05;
+
+/// Sample range and loudness of one slice of a track
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WaveformBucket {
+    pub min: f32,
+    pub max: f32,
+    pub rms: f32,
+}
+
+/// Split interleaved audio into `buckets` equal slices of frames and
+/// measure each over all channels
+pub fn waveform_buckets(samples: &[f32], channels: usize, buckets: usize) -> Vec<WaveformBucket> {
+    let frames = samples.len() / channels.max(1);
+    (0..buckets)
+        .into_par_iter()
+        .map(|b| {
+            let start = b * frames / buckets;
+            let end = ((b + 1) * frames / buckets).max(start + 1).min(frames);
+            let slice = samples.get(start * channels..end * channels).unwrap_or(&[]);
+            if slice.is_empty() {
+                return WaveformBucket {
+                    min: 0.0,
+                    max: 0.0,
+                    rms: 0.0,
+                };
+            }
+            let (min, max, squares) = slice
+                .iter()
+                .fold((f32::MAX, f32::MIN, 0.0), |(lo, hi, sq), s| {
+                    (lo.min(*s), hi.max(*s), sq + s * s)
+                });
+            WaveformBucket {
+                min,
+                max,
+                rms: (squares / slice.len() as f32).sqrt(),
+            }
+        })
+        .collect()
+}
+
+/// RMS over all channels of the `window` seconds centred on `time`;
+/// samples outside the track count as silence
+pub fn windowed_rms(
+    samples: &[f32],
+    channels: usize,
+    sample_rate: u32,
+    time: f32,
+    window: f32,
+) -> f32 {
+    let channels = channels.max(1);
+    let frames = (samples.len() / channels) as isize;
+    let half = ((window * sample_rate as f32) / 2.0).round().max(1.0) as isize;
+    let centre = (time * sample_rate as f32).round() as isize;
+    let (start, end) = (
+        (centre - half).clamp(0, frames),
+        (centre + half).clamp(0, frames),
+    );
+    let squares: f32 = samples[start as usize * channels..end as usize * channels]
+        .iter()
+        .map(|s| s * s)
+        .sum();
+    (squares / (2 * half as usize * channels) as f32).sqrt()
+}
 
 #[pyclass]
 #[derive(Clone)]
@@ -9,21 +245,78 @@ pub struct AudioTrack {
     pub path: String,
     #[pyo3(get)]
     pub duration: f32,
-    pub sample_rate: u32,
+    #[pyo3(get)]
+    pub sample_rate: u32, // rate of `samples` after resampling
+    #[pyo3(get)]
     pub channels: u32,
+    #[pyo3(get)]
+    pub source_sample_rate: u32, // rate stored in the file
+    #[pyo3(get)]
+    pub loaded: bool,
+    pub samples: Vec<f32>, // interleaved
 }
 
 #[pymethods]
 impl AudioTrack {
+    /// Decode `path` and resample it to `sample_rate`; a missing or
+    /// undecodable file raises. With `lazy=True` nothing is read yet (e.g.
+    /// a voiceover still being generated) and the track stays empty until
+    /// `load()` is called.
     #[new]
-    fn new(path: String) -> PyResult<Self> {
-        // Placeholder - would load actual audio file to get duration
-        Ok(Self {
+    #[pyo3(signature = (path, sample_rate=PROJECT_SAMPLE_RATE, lazy=false))]
+    fn new(path: String, sample_rate: u32, lazy: bool) -> PyResult<Self> {
+        if sample_rate == 0 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "Audio sample rate must be positive",
+            ));
+        }
+        let mut track = Self {
             path,
             duration: 0.0,
-            sample_rate: 44100,
+            sample_rate,
             channels: 2,
-        })
+            source_sample_rate: sample_rate,
+            loaded: false,
+            samples: Vec::new(),
+        };
+        if !lazy {
+            track.load()?;
+        }
+        Ok(track)
+    }
+
+    /// (Re)decode the file at `path`
+    fn load(&mut self) -> PyResult<()> {
+        let decoded = decode_file(Path::new(&self.path))?;
+        self.source_sample_rate = decoded.sample_rate;
+        self.channels = decoded.channels;
+        self.samples = resample(
+            &decoded.samples,
+            decoded.channels as usize,
+            decoded.sample_rate,
+            self.sample_rate,
+        );
+        self.loaded = true;
+        self.update_duration();
+        Ok(())
+    }
+
+    /// Convert the decoded samples to another rate
+    #[pyo3(name = "resample")]
+    fn py_resample(&mut self, sample_rate: u32) {
+        self.samples = resample(
+            &self.samples,
+            self.channels as usize,
+            self.sample_rate,
+            sample_rate,
+        );
+        self.sample_rate = sample_rate;
+        self.update_duration();
+    }
+
+    /// Interleaved f32 samples
+    fn get_samples(&self) -> Vec<f32> {
+        self.samples.clone()
     }
 
     fn play(&self) {
@@ -36,14 +329,81 @@ impl AudioTrack {
         Ok(Vec::new())
     }
 
+    /// Peak level (largest absolute sample) of each of `samples` equal
+    /// slices of the track
     fn get_waveform(&self, samples: usize) -> PyResult<Vec<f32>> {
-        // Placeholder for waveform generation
-        Ok(vec![0.0; samples])
+        Ok(self
+            .waveform(samples)
+            .iter()
+            .map(|b| b.max.abs().max(b.min.abs()))
+            .collect())
     }
 
-    fn get_amplitude_at(&self, _time: f32) -> PyResult<f32> {
-        // Placeholder for amplitude at specific time
-        Ok(0.0)
+    /// (min, max, rms) of each of `buckets` equal slices of the track
+    fn get_waveform_buckets(&self, buckets: usize) -> Vec<(f32, f32, f32)> {
+        self.waveform(buckets)
+            .iter()
+            .map(|b| (b.min, b.max, b.rms))
+            .collect()
+    }
+
+    /// RMS level over `window` seconds centred on `time`. `smoothing`
+    /// (0 to 1) blends in the levels of earlier windows with exponentially
+    /// falling weight, like a meter's release.
+    #[pyo3(signature = (time, window=DEFAULT_RMS_WINDOW, smoothing=0.0))]
+    fn get_amplitude_at(&self, time: f32, window: f32, smoothing: f32) -> PyResult<f32> {
+        Ok(self.amplitude_at(time, window, smoothing))
+    }
+
+    /// Animation of `property` following the track's loudness, one keyframe
+    /// per frame. The loudest moment maps to `max_value` and silence to
+    /// `min_value`.
+    #[pyo3(signature = (property, fps, min_value=0.0, max_value=1.0, window=DEFAULT_RMS_WINDOW, smoothing=0.5))]
+    fn amplitude_driver(
+        &self,
+        property: String,
+        fps: f32,
+        min_value: f32,
+        max_value: f32,
+        window: f32,
+        smoothing: f32,
+    ) -> PyResult<Animation> {
+        if fps <= 0.0 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "fps must be positive",
+            ));
+        }
+        let frames = (self.duration * fps).ceil() as usize;
+        let levels: Vec<f32> = (0..=frames)
+            .into_par_iter()
+            .map(|f| self.amplitude_at(f as f32 / fps, window, smoothing))
+            .collect();
+        let loudest = levels.iter().fold(0.0f32, |a, b| a.max(*b));
+        let keyframes = levels
+            .iter()
+            .enumerate()
+            .map(|(f, level)| {
+                let t = if loudest > 0.0 { level / loudest } else { 0.0 };
+                Keyframe {
+                    time: f as f32 / fps,
+                    value: min_value + (max_value - min_value) * t,
+                    easing: "linear".to_string(),
+                }
+            })
+            .collect();
+        Ok(Animation {
+            property,
+            keyframes,
+            duration: self.duration,
+            loop_count: 1,
+        })
+    }
+
+    /// Copy of the track played along `remap`, keeping its pitch when the
+    /// remap's `maintain_pitch` is set
+    #[pyo3(name = "retimed")]
+    fn py_retimed(&self, remap: TimeRemap) -> PyResult<Self> {
+        self.retimed(&remap)
     }
 
     fn normalize(&mut self) -> PyResult<()> {
@@ -62,6 +422,54 @@ impl AudioTrack {
     }
 }
 
+impl AudioTrack {
+    pub fn retimed(&self, remap: &TimeRemap) -> PyResult<Self> {
+        let mut track = self.clone();
+        track.samples =
+            remap.retime_audio(&self.samples, self.channels as usize, self.sample_rate)?;
+        track.update_duration();
+        Ok(track)
+    }
+
+    pub fn waveform(&self, buckets: usize) -> Vec<WaveformBucket> {
+        waveform_buckets(&self.samples, self.channels as usize, buckets)
+    }
+
+    pub fn amplitude_at(&self, time: f32, window: f32, smoothing: f32) -> f32 {
+        let rms = |t: f32| {
+            windowed_rms(
+                &self.samples,
+                self.channels as usize,
+                self.sample_rate,
+                t,
+                window,
+            )
+        };
+        let decay = smoothing.clamp(0.0, 0.99);
+        if decay <= 0.0 {
+            return rms(time);
+        }
+        let (mut total, mut weights, mut weight) = (0.0, 0.0, 1.0);
+        let mut k = 0;
+        while weight > 0.01 && time - k as f32 * window >= 0.0 {
+            total += rms(time - k as f32 * window) * weight;
+            weights += weight;
+            weight *= decay;
+            k += 1;
+        }
+        if weights > 0.0 {
+            total / weights
+        } else {
+            rms(time)
+        }
+    }
+
+    fn update_duration(&mut self) {
+        let frames = self.samples.len() / self.channels.max(1) as usize;
+        self.duration = frames as f32 / self.sample_rate.max(1) as f32;
+    }
+}
+
 #[pyclass]
 pub struct AudioMixer {
     pub tracks: Vec<AudioTrack>,
@@ -130,3 +538,192 @@ pub fn calculate_bpm(beat_times: Vec<f32>) -> f32 {
         0.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// 16-bit PCM WAV bytes
+    fn wav(samples: &[i16], channels: u16, rate: u32) -> Vec<u8> {
+        let data_len = (samples.len() * 2) as u32;
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+        bytes.extend_from_slice(b"WAVEfmt ");
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&channels.to_le_bytes());
+        bytes.extend_from_slice(&rate.to_le_bytes());
+        bytes.extend_from_slice(&(rate * channels as u32 * 2).to_le_bytes());
+        bytes.extend_from_slice(&(channels * 2).to_le_bytes());
+        bytes.extend_from_slice(&16u16.to_le_bytes());
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&data_len.to_le_bytes());
+        samples
+            .iter()
+            .for_each(|s| bytes.extend_from_slice(&s.to_le_bytes()));
+        bytes
+    }
+
+    /// Frequency from rising zero crossings of one channel
+    fn frequency(samples: &[f32], channels: usize, rate: u32) -> f32 {
+        let mono: Vec<f32> = samples.iter().step_by(channels).copied().collect();
+        let crossings = mono
+            .windows(2)
+            .filter(|w| w[0] < 0.0 && w[1] >= 0.0)
+            .count();
+        crossings as f32 * rate as f32 / mono.len() as f32
+    }
+
+    /// Empty directory for one test; the caller removes it when done
+    fn scratch_dir(test: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("fluxreel_{}_{}", test, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn wav_decodes_with_true_format_and_resamples() {
+        let dir = scratch_dir("audio_decode");
+        let path = dir.join("tone.wav");
+        // 1.5 s of 441 Hz in the left channel, silence in the right
+        let pcm: Vec<i16> = (0..66150)
+            .flat_map(|i| {
+                let s = (2.0 * std::f32::consts::PI * 441.0 * i as f32 / 44100.0).sin();
+                [(s * 16000.0) as i16, 0]
+            })
+            .collect();
+        std::fs::write(&path, wav(&pcm, 2, 44100)).unwrap();
+
+        let decoded = decode_file(&path).unwrap();
+        assert_eq!((decoded.sample_rate, decoded.channels), (44100, 2));
+        assert_eq!(decoded.samples.len(), pcm.len());
+
+        let track =
+            AudioTrack::new(path.display().to_string(), PROJECT_SAMPLE_RATE, false).unwrap();
+        assert!(track.loaded);
+        assert_eq!(
+            (track.sample_rate, track.source_sample_rate, track.channels),
+            (48000, 44100, 2)
+        );
+        assert!((track.duration - 1.5).abs() < 1e-3);
+        assert_eq!(track.samples.len(), 72000 * 2);
+        assert!((frequency(&track.samples, 2, 48000) - 441.0).abs() < 2.0);
+        let peak = track.samples[20000..40000]
+            .iter()
+            .step_by(2)
+            .fold(0.0f32, |a, s| a.max(s.abs()));
+        assert!((peak - 16000.0 / 32768.0).abs() < 0.01, "{}", peak);
+        assert!(track
+            .samples
+            .iter()
+            .skip(1)
+            .step_by(2)
+            .all(|s| s.abs() < 1e-6));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn missing_and_corrupt_files() {
+        assert!(AudioTrack::new("assets/not_rendered_yet.mp3".to_string(), 48000, false).is_err());
+        assert!(AudioTrack::new("assets/not_rendered_yet.mp3".to_string(), 0, true).is_err());
+        let track =
+            AudioTrack::new("assets/not_rendered_yet.mp3".to_string(), 48000, true).unwrap();
+        assert!(!track.loaded);
+        assert_eq!(track.duration, 0.0);
+        assert!(matches!(
+            decode_file(Path::new("assets/not_rendered_yet.mp3")),
+            Err(AudioError::Io { .. })
+        ));
+
+        let dir = scratch_dir("audio_corrupt");
+        let path = dir.join("corrupt.wav");
+        std::fs::write(&path, b"definitely not audio").unwrap();
+        assert!(matches!(decode_file(&path), Err(AudioError::Decode { .. })));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn track(samples: Vec<f32>, channels: u32, sample_rate: u32) -> AudioTrack {
+        let mut track = AudioTrack::new("in_memory.wav".to_string(), sample_rate, true).unwrap();
+        track.samples = samples;
+        track.channels = channels;
+        track.update_duration();
+        track
+    }
+
+    #[test]
+    fn waveform_and_amplitude_follow_the_signal() {
+        // One second at 1 kHz: a 0.5 amplitude square wave, then silence
+        let samples: Vec<f32> = (0..1000)
+            .map(|i| {
+                if i < 500 {
+                    if i % 2 == 0 {
+                        0.5
+                    } else {
+                        -0.5
+                    }
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+        let track = track(samples, 1, 1000);
+        let buckets = track.waveform(4);
+        assert_eq!(
+            buckets[0],
+            WaveformBucket {
+                min: -0.5,
+                max: 0.5,
+                rms: 0.5
+            }
+        );
+        assert_eq!(buckets[3].rms, 0.0);
+        assert_eq!(track.get_waveform(2).unwrap(), vec![0.5, 0.0]);
+
+        assert!((track.amplitude_at(0.25, 0.1, 0.0) - 0.5).abs() < 1e-4);
+        assert!((track.amplitude_at(0.5, 0.1, 0.0) - 0.5 / 2f32.sqrt()).abs() < 1e-3);
+        assert_eq!(track.amplitude_at(0.8, 0.1, 0.0), 0.0);
+        // Smoothing lets the level fall away gradually after the sound stops
+        let released = track.amplitude_at(0.65, 0.05, 0.8);
+        assert!(released > 0.05 && released < 0.5, "{}", released);
+    }
+
+    #[test]
+    fn retimed_track_follows_the_remap() {
+        let tone: Vec<f32> = (0..2000)
+            .map(|i| (2.0 * PI * 50.0 * (i / 2) as f64 / 1000.0).sin() as f32)
+            .collect();
+        let source = track(tone, 2, 1000);
+        let mut remap = TimeRemap::default();
+        remap.speed_ramp.keyframes[0].speed = 0.5;
+        remap.maintain_pitch = true;
+        let slow = source.retimed(&remap).unwrap();
+        assert!((slow.duration - 2.0).abs() < 1e-2, "{}", slow.duration);
+        assert_eq!((slow.channels, slow.sample_rate), (2, 1000));
+        assert!((frequency(&slow.samples, 2, 1000) - 50.0).abs() < 2.0);
+        assert_eq!(source.duration, 1.0);
+    }
+
+    #[test]
+    fn amplitude_driver_keys_a_property() {
+        let samples: Vec<f32> = (0..1000)
+            .map(|i| if i < 500 { 0.25 } else { 0.0 })
+            .collect();
+        let track = track(samples, 1, 1000);
+        let animation = track
+            .amplitude_driver("scale".to_string(), 10.0, 1.0, 2.0, 0.05, 0.0)
+            .unwrap();
+        assert_eq!(animation.property, "scale");
+        assert_eq!(animation.keyframes.len(), 11);
+        assert!((animation.keyframes[2].value - 2.0).abs() < 1e-4);
+        assert!((animation.keyframes[8].value - 1.0).abs() < 1e-4);
+        assert!(
+            (crate::animation::interpolate_keyframes(&animation.keyframes, 0.25) - 2.0).abs()
+                < 1e-4
+        );
+        assert!(track
+            .amplitude_driver("scale".to_string(), 0.0, 0.0, 1.0, 0.05, 0.0)
+            .is_err());
+    }
+}
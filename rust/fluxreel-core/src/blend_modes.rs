@@ -1,4 +1,7 @@
+use crate::color_management::ColorSpace;
+use crate::frame::{process_f32, to_f32_vec, Pixel};
 use pyo3::prelude::*;
+use rayon::prelude::*;
 
 #[derive(Debug, Clone)]
 pub enum BlendMode {
@@ -43,6 +46,65 @@ impl BlendModeEffect {
     }
 }
 
+impl BlendModeEffect {
+    /// Composite `layer` over `base` (both encoded in `space`)
+    pub fn apply<P: Pixel>(
+        &self,
+        base: &mut [P],
+        layer: &[P],
+        width: u32,
+        height: u32,
+        space: ColorSpace,
+    ) {
+        blend_frames(base, layer, width, height, &self.mode, self.opacity, space);
+    }
+}
+
+/// Blend two RGBA frames in linear light.
+///
+/// Both frames are decoded with the transfer function of `space`, blended and
+/// composited with the layer's alpha, then re-encoded into `base`. Mixing
+/// encoded values instead darkens soft edges and cross-fades.
+pub fn blend_frames<P: Pixel>(
+    base: &mut [P],
+    layer: &[P],
+    width: u32,
+    height: u32,
+    mode: &str,
+    opacity: f32,
+    space: ColorSpace,
+) {
+    debug_assert_eq!(base.len(), (width * height * 4) as usize);
+    debug_assert_eq!(base.len(), layer.len());
+    let transfer = space.transfer();
+    let layer = to_f32_vec(layer);
+    process_f32(base, |base| {
+        base.par_chunks_mut(4)
+            .zip(layer.par_chunks(4))
+            .for_each(|(b, l)| {
+                let alpha = l[3] * opacity;
+                if alpha <= 0.0 {
+                    return;
+                }
+                let bc = (
+                    transfer.decode(b[0]),
+                    transfer.decode(b[1]),
+                    transfer.decode(b[2]),
+                );
+                let lc = (
+                    transfer.decode(l[0]),
+                    transfer.decode(l[1]),
+                    transfer.decode(l[2]),
+                );
+                let out = apply_blend_mode(bc, lc, mode, alpha);
+                b[0] = transfer.encode(out.0);
+                b[1] = transfer.encode(out.1);
+                b[2] = transfer.encode(out.2);
+                b[3] = alpha + b[3] * (1.0 - alpha);
+            });
+    });
+}
+
 /// Apply blend mode to two colors
 pub fn apply_blend_mode(
     base: (f32, f32, f32),
@@ -218,3 +280,17 @@ fn subtract(base: (f32, f32, f32), blend: (f32, f32, f32)) -> (f32, f32, f32) {
         (base.2 - blend.2).max(0.0),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn half_opacity_normal_blend_mixes_in_linear_light() {
+        let mut base = vec![0u8, 0, 0, 255];
+        let layer = vec![255, 255, 255, 255];
+        blend_frames(&mut base, &layer, 1, 1, "normal", 0.5, ColorSpace::Srgb);
+        // 50% linear grey is sRGB 188, not the 128 of a gamma-space mix
+        assert_eq!(&base[..3], &[188, 188, 188]);
+    }
+}
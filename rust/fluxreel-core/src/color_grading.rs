@@ -1,11 +1,59 @@
+use crate::frame::{process_f32, Frame, Pixel};
+use crate::utils::{
+    hsl_to_rgb_f32, linear_to_srgb, normalize_angle, rgb_to_hsl_f32, srgb_to_linear,
+};
 use pyo3::prelude::*;
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 
+/// Rec.709 / sRGB luma weights, as used by the ASC CDL saturation operator
+const LUMA_WEIGHTS: [f32; 3] = [0.2126, 0.7152, 0.0722];
+
+/// Mid grey in linear light, the pivot for contrast
+const CONTRAST_PIVOT: f32 = 0.18;
+
 #[derive(Debug, Clone)]
 pub struct ColorWheel {
-    pub lift: (f32, f32, f32),  // RGB lift (shadows)
-    pub gamma: (f32, f32, f32), // RGB gamma (midtones)
-    pub gain: (f32, f32, f32),  // RGB gain (highlights)
+    pub lift: (f32, f32, f32),  // RGB lift (shadows), -1.0 to 1.0
+    pub gamma: (f32, f32, f32), // RGB gamma (midtones), -1.0 to 1.0
+    pub gain: (f32, f32, f32),  // RGB gain (highlights), -1.0 to 1.0
+}
+
+/// Python-facing CDL: ((slope), (offset), (power), saturation)
+type CdlValues = ((f32, f32, f32), (f32, f32, f32), (f32, f32, f32), f32);
+
+/// ASC CDL slope/offset/power per channel
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cdl {
+    pub slope: [f32; 3],
+    pub offset: [f32; 3],
+    pub power: [f32; 3],
+}
+
+impl ColorWheel {
+    /// CDL equivalent of the wheels: gain maps to slope (`1 + gain`), lift to
+    /// offset and gamma to power (`2^-gamma`, so positive gamma brightens)
+    pub fn to_cdl(&self) -> Cdl {
+        let (lift, gamma, gain) = (self.lift, self.gamma, self.gain);
+        Cdl {
+            slope: [1.0 + gain.0, 1.0 + gain.1, 1.0 + gain.2],
+            offset: [lift.0, lift.1, lift.2],
+            power: [(-gamma.0).exp2(), (-gamma.1).exp2(), (-gamma.2).exp2()],
+        }
+    }
+
+    pub fn from_cdl(cdl: &Cdl) -> Self {
+        let gamma = |p: f32| -p.max(1e-6).log2();
+        Self {
+            lift: (cdl.offset[0], cdl.offset[1], cdl.offset[2]),
+            gamma: (
+                gamma(cdl.power[0]),
+                gamma(cdl.power[1]),
+                gamma(cdl.power[2]),
+            ),
+            gain: (cdl.slope[0] - 1.0, cdl.slope[1] - 1.0, cdl.slope[2] - 1.0),
+        }
+    }
 }
 
 #[pyclass]
@@ -64,8 +112,423 @@ impl ColorGrading {
         self.contrast = 0.0;
         self.saturation = 100.0;
     }
+
+    /// Set the grade from ASC CDL values as delivered by a colorist
+    fn set_cdl(
+        &mut self,
+        slope: (f32, f32, f32),
+        offset: (f32, f32, f32),
+        power: (f32, f32, f32),
+        saturation: f32,
+    ) {
+        self.wheels = ColorWheel::from_cdl(&Cdl {
+            slope: [slope.0, slope.1, slope.2],
+            offset: [offset.0, offset.1, offset.2],
+            power: [power.0, power.1, power.2],
+        });
+        self.saturation = saturation * 100.0;
+    }
+
+    /// Current grade as ((slope), (offset), (power), saturation)
+    fn get_cdl(&self) -> CdlValues {
+        let cdl = self.wheels.to_cdl();
+        let t = |v: [f32; 3]| (v[0], v[1], v[2]);
+        (
+            t(cdl.slope),
+            t(cdl.offset),
+            t(cdl.power),
+            self.saturation / 100.0,
+        )
+    }
+
+    /// Load slope/offset/power/saturation from a `.cc`, `.ccc` or `.cdl` file
+    fn load_cdl(&mut self, path: &str) -> PyResult<()> {
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
+                "Failed to read CDL '{}': {}",
+                path, e
+            ))
+        })?;
+        let triple = |tag: &str| -> PyResult<(f32, f32, f32)> {
+            let v = xml_numbers(&text, tag).ok_or_else(|| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "CDL '{}' has no <{}> element",
+                    path, tag
+                ))
+            })?;
+            match v.as_slice() {
+                [r, g, b] => Ok((*r, *g, *b)),
+                _ => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "CDL '{}': <{}> needs three values",
+                    path, tag
+                ))),
+            }
+        };
+        let slope = triple("Slope")?;
+        let offset = triple("Offset")?;
+        let power = triple("Power")?;
+        let saturation = xml_numbers(&text, "Saturation")
+            .and_then(|v| v.first().copied())
+            .unwrap_or(1.0);
+        self.set_cdl(slope, offset, power, saturation);
+        Ok(())
+    }
+
+    /// Grade a single sRGB-encoded colour (0-1 channels)
+    fn grade_color(&self, r: f32, g: f32, b: f32) -> (f32, f32, f32) {
+        let out = self.grade_linear([srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b)]);
+        (
+            linear_to_srgb(out[0]).clamp(0.0, 1.0),
+            linear_to_srgb(out[1]).clamp(0.0, 1.0),
+            linear_to_srgb(out[2]).clamp(0.0, 1.0),
+        )
+    }
+
+    /// Suggested (temperature, tint, (gain)) that neutralise an RGBA8 frame.
+    /// The gain is an alternative to temperature/tint, in gain wheel units.
+    fn estimate_white_balance(
+        &self,
+        pixels: Vec<u8>,
+        width: u32,
+        height: u32,
+    ) -> PyResult<(f32, f32, (f32, f32, f32))> {
+        let frame = Frame::from_raw(width, height, pixels)?;
+        let estimate = estimate_white_balance(&frame.data);
+        let g = estimate.gain;
+        Ok((estimate.temperature, estimate.tint, (g[0], g[1], g[2])))
+    }
+
+    /// Set temperature and tint from `estimate_white_balance`
+    fn auto_white_balance(&mut self, pixels: Vec<u8>, width: u32, height: u32) -> PyResult<()> {
+        let frame = Frame::from_raw(width, height, pixels)?;
+        let estimate = estimate_white_balance(&frame.data);
+        self.temperature = estimate.temperature;
+        self.tint = estimate.tint;
+        Ok(())
+    }
+
+    /// Fit lift/gamma/gain and saturation so a frame of this shot matches
+    /// the colour statistics of a reference frame (both RGBA8)
+    fn match_to_reference(
+        &mut self,
+        pixels: Vec<u8>,
+        width: u32,
+        height: u32,
+        reference: Vec<u8>,
+        reference_width: u32,
+        reference_height: u32,
+    ) -> PyResult<()> {
+        let frame = Frame::from_raw(width, height, pixels)?;
+        let reference = Frame::from_raw(reference_width, reference_height, reference)?;
+        self.match_frame(&frame.data, &reference.data);
+        Ok(())
+    }
+}
+
+impl ColorGrading {
+    /// Per-channel white balance multipliers for `temperature` and `tint`.
+    ///
+    /// Positive temperature warms (more red, less blue), positive tint shifts
+    /// towards magenta (less green). The gains are normalised so a neutral
+    /// grey keeps its luminance.
+    pub fn white_balance_gains(&self) -> [f32; 3] {
+        let t = self.temperature / 100.0;
+        let tint = self.tint / 100.0;
+        let gains = [1.0 + 0.3 * t, 1.0 - 0.3 * tint, 1.0 - 0.3 * t].map(|g| g.max(0.0));
+        let luma: f32 = gains.iter().zip(LUMA_WEIGHTS).map(|(g, w)| g * w).sum();
+        gains.map(|g| g / luma.max(1e-6))
+    }
+
+    /// Steps 1-3 of `grade_linear`: exposure, white balance and contrast
+    fn balance_linear(&self, rgb: [f32; 3]) -> [f32; 3] {
+        let exposure = self.exposure.exp2();
+        let wb = self.white_balance_gains();
+        let contrast = (1.0 + self.contrast / 100.0).max(0.0);
+        let mut out = [0.0; 3];
+        for c in 0..3 {
+            let mut v = rgb[c] * exposure * wb[c];
+            if contrast != 1.0 && v > 0.0 {
+                v = CONTRAST_PIVOT * (v / CONTRAST_PIVOT).powf(contrast);
+            }
+            out[c] = v;
+        }
+        out
+    }
+
+    /// Grade one linear-light RGB value.
+    ///
+    /// Order of operations:
+    /// 1. exposure, in stops (`* 2^exposure`)
+    /// 2. white balance gains from temperature and tint
+    /// 3. contrast around 18% grey (`pivot * (x / pivot)^(1 + contrast/100)`)
+    /// 4. ASC CDL slope/offset/power from the lift/gamma/gain wheels, with
+    ///    negative values clamped to zero before the power as the spec requires
+    /// 5. ASC CDL saturation (`saturation / 100`) using Rec.709 luma weights
+    ///
+    /// With exposure, white balance and contrast at zero the result is a pure
+    /// ASC CDL evaluated in linear light.
+    pub fn grade_linear(&self, rgb: [f32; 3]) -> [f32; 3] {
+        let cdl = self.wheels.to_cdl();
+        let balanced = self.balance_linear(rgb);
+
+        let mut out = [0.0; 3];
+        for c in 0..3 {
+            let v = (balanced[c] * cdl.slope[c] + cdl.offset[c]).max(0.0);
+            out[c] = v.powf(cdl.power[c]);
+        }
+
+        let sat = self.saturation / 100.0;
+        if sat != 1.0 {
+            let luma: f32 = out.iter().zip(LUMA_WEIGHTS).map(|(v, w)| v * w).sum();
+            for v in out.iter_mut() {
+                *v = luma + (*v - luma) * sat;
+            }
+        }
+        out
+    }
+
+    /// Grade an sRGB-encoded RGBA frame in place, working in linear light
+    pub fn apply<P: Pixel>(&self, frame: &mut [P], width: u32, height: u32) {
+        debug_assert_eq!(frame.len(), (width * height * 4) as usize);
+        process_f32(frame, |frame| {
+            frame.par_chunks_mut(4).for_each(|px| {
+                let graded = self.grade_linear([
+                    srgb_to_linear(px[0]),
+                    srgb_to_linear(px[1]),
+                    srgb_to_linear(px[2]),
+                ]);
+                for c in 0..3 {
+                    px[c] = linear_to_srgb(graded[c].max(0.0));
+                }
+            });
+        });
+    }
+}
+
+impl Default for ColorGrading {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ColorGrading {
+    /// Fit the CDL wheels and saturation so `frame` (sRGB-encoded) matches
+    /// `reference` after grading.
+    ///
+    /// Exposure, white balance and contrast are kept; per channel, the 5th,
+    /// 50th and 95th percentiles of the balanced source are mapped onto the
+    /// reference's with slope/offset/power, then saturation is scaled so the
+    /// average chroma agrees.
+    pub fn match_frame<P: Pixel>(&mut self, frame: &[P], reference: &[P]) {
+        let source: Vec<[f32; 3]> = linear_pixels(frame)
+            .into_iter()
+            .map(|rgb| self.balance_linear(rgb))
+            .collect();
+        let target = linear_pixels(reference);
+        if source.is_empty() || target.is_empty() {
+            return;
+        }
+        let src_stats = channel_percentiles(&source, &MATCH_PERCENTILES);
+        let dst_stats = channel_percentiles(&target, &MATCH_PERCENTILES);
+
+        let mut cdl = Cdl {
+            slope: [1.0; 3],
+            offset: [0.0; 3],
+            power: [1.0; 3],
+        };
+        for c in 0..3 {
+            let (slope, offset, power) = fit_cdl_channel(src_stats[c], dst_stats[c]);
+            cdl.slope[c] = slope;
+            cdl.offset[c] = offset;
+            cdl.power[c] = power;
+        }
+        self.wheels = ColorWheel::from_cdl(&cdl);
+        self.saturation = 100.0;
+
+        let graded: Vec<[f32; 3]> = source
+            .iter()
+            .map(|rgb| {
+                let mut out = [0.0; 3];
+                for c in 0..3 {
+                    out[c] = (rgb[c] * cdl.slope[c] + cdl.offset[c])
+                        .max(0.0)
+                        .powf(cdl.power[c]);
+                }
+                out
+            })
+            .collect();
+        let (src_chroma, dst_chroma) = (mean_chroma(&graded), mean_chroma(&target));
+        if src_chroma > 1e-5 {
+            self.saturation = (dst_chroma / src_chroma * 100.0).clamp(0.0, 200.0);
+        }
+    }
+}
+
+/// White balance suggestion for a frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WhiteBalanceEstimate {
+    /// Estimated colour of a neutral surface, linear RGB
+    pub neutral: [f32; 3],
+    /// `ColorGrading::temperature` that neutralises the frame
+    pub temperature: f32,
+    /// `ColorGrading::tint` that neutralises the frame
+    pub tint: f32,
+    /// Alternative correction as gain wheel values
+    pub gain: [f32; 3],
+}
+
+/// Percentiles used for shot matching: shadows, midtones, highlights
+const MATCH_PERCENTILES: [f32; 3] = [0.05, 0.5, 0.95];
+
+/// Opaque pixels of an sRGB-encoded frame in linear light
+fn linear_pixels<P: Pixel>(frame: &[P]) -> Vec<[f32; 3]> {
+    frame
+        .par_chunks(4)
+        .filter(|px| px[3].to_f32() > 0.0)
+        .map(|px| {
+            [
+                srgb_to_linear(px[0].to_f32()),
+                srgb_to_linear(px[1].to_f32()),
+                srgb_to_linear(px[2].to_f32()),
+            ]
+        })
+        .collect()
+}
+
+fn luma_of(rgb: [f32; 3]) -> f32 {
+    rgb.iter().zip(LUMA_WEIGHTS).map(|(v, w)| v * w).sum()
+}
+
+fn mean_chroma(pixels: &[[f32; 3]]) -> f32 {
+    let total: f32 = pixels
+        .par_iter()
+        .map(|rgb| {
+            let l = luma_of(*rgb);
+            rgb.iter().map(|v| (v - l).abs()).sum::<f32>()
+        })
+        .sum();
+    total / pixels.len().max(1) as f32
+}
+
+/// Per-channel percentiles of linear pixels
+fn channel_percentiles(pixels: &[[f32; 3]], percentiles: &[f32; 3]) -> [[f32; 3]; 3] {
+    let mut out = [[0.0; 3]; 3];
+    for (c, stats) in out.iter_mut().enumerate() {
+        let mut values: Vec<f32> = pixels.iter().map(|p| p[c]).collect();
+        values.par_sort_unstable_by(|a, b| a.total_cmp(b));
+        for (stat, p) in stats.iter_mut().zip(percentiles) {
+            let i = (p * (values.len() - 1) as f32).round() as usize;
+            *stat = values[i];
+        }
+    }
+    out
+}
+
+/// Slope, offset and power mapping three source percentiles onto three
+/// target percentiles.
+///
+/// For each candidate power the target is linearised and the slope/offset
+/// pass through the outer percentiles; the power that best lands the median
+/// wins (searched on a log scale from 1/4 to 4).
+fn fit_cdl_channel(src: [f32; 3], dst: [f32; 3]) -> (f32, f32, f32) {
+    let affine = |power: f32| {
+        let t = dst.map(|v| v.max(1e-6).powf(1.0 / power));
+        let span = src[2] - src[0];
+        if span.abs() < 1e-4 {
+            (1.0, t[1] - src[1])
+        } else {
+            let slope = (t[2] - t[0]) / span;
+            (slope, t[0] - slope * src[0])
+        }
+    };
+    let mut best = (1.0, 0.0, 1.0);
+    let mut best_err = f32::INFINITY;
+    for step in -200..=200 {
+        let power = (step as f32 / 100.0).exp2();
+        let (slope, offset) = affine(power);
+        if slope <= 0.0 {
+            continue;
+        }
+        let mid = (src[1] * slope + offset).max(0.0).powf(power);
+        let err = (mid - dst[1]).abs();
+        if err < best_err {
+            best_err = err;
+            best = (slope, offset, power);
+        }
+    }
+    best
+}
+
+/// Gray-world white balance estimate for an sRGB-encoded frame.
+///
+/// Works in linear light on well-exposed pixels (luma 2%-90%). When enough
+/// of them are already close to neutral only those are averaged, which keeps
+/// large saturated objects from dragging the estimate. The result inverts
+/// `ColorGrading::white_balance_gains`.
+pub fn estimate_white_balance<P: Pixel>(frame: &[P]) -> WhiteBalanceEstimate {
+    let exposed: Vec<[f32; 3]> = linear_pixels(frame)
+        .into_iter()
+        .filter(|rgb| (0.02..=0.9).contains(&luma_of(*rgb)))
+        .collect();
+    let near_neutral: Vec<[f32; 3]> = exposed
+        .iter()
+        .copied()
+        .filter(|rgb| {
+            let l = luma_of(*rgb);
+            rgb.iter().all(|v| (v - l).abs() < 0.3 * l)
+        })
+        .collect();
+    let sample = if near_neutral.len() * 20 >= exposed.len() && !near_neutral.is_empty() {
+        &near_neutral
+    } else {
+        &exposed
+    };
+
+    let mut neutral = [0.0f32; 3];
+    for rgb in sample.iter() {
+        for c in 0..3 {
+            neutral[c] += rgb[c];
+        }
+    }
+    if sample.is_empty() || neutral.iter().any(|v| *v <= 0.0) {
+        return WhiteBalanceEstimate {
+            neutral: [1.0; 3],
+            temperature: 0.0,
+            tint: 0.0,
+            gain: [0.0; 3],
+        };
+    }
+    let neutral = neutral.map(|v| v / sample.len() as f32);
+
+    // gains r:b = (1 + 0.3t):(1 - 0.3t) must equal neutral b:r
+    let k = neutral[2] / neutral[0];
+    let t = ((k - 1.0) / (0.3 * (k + 1.0))).clamp(-1.0, 1.0);
+    // gains g:r = (1 - 0.3 tint):(1 + 0.3t) must equal neutral r:g
+    let tint = ((1.0 - (1.0 + 0.3 * t) * neutral[0] / neutral[1]) / 0.3).clamp(-1.0, 1.0);
+
+    let l = luma_of(neutral);
+    WhiteBalanceEstimate {
+        neutral,
+        temperature: t * 100.0,
+        tint: tint * 100.0,
+        gain: neutral.map(|v| l / v - 1.0),
+    }
+}
+
+/// Whitespace-separated numbers inside the first `<tag>...</tag>` element
+fn xml_numbers(text: &str, tag: &str) -> Option<Vec<f32>> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let start = text.find(&open)? + open.len();
+    let end = start + text[start..].find(&close)?;
+    text[start..end]
+        .split_whitespace()
+        .map(|v| v.parse().ok())
+        .collect()
 }
 
+/// Control point of a colour curve. Evaluation passes through the anchors
+/// with a monotone cubic; the handles are kept for editors that draw them.
 #[derive(Debug, Clone)]
 pub struct CurvePoint {
     pub x: f32,
@@ -74,6 +537,210 @@ pub struct CurvePoint {
     pub handle_out: (f32, f32),
 }
 
+impl CurvePoint {
+    fn new(x: f32, y: f32) -> Self {
+        Self {
+            x,
+            y,
+            handle_in: (x - 0.1, y),
+            handle_out: (x + 0.1, y),
+        }
+    }
+}
+
+/// Number of entries each curve is baked into
+pub const CURVE_LUT_SIZE: usize = 1024;
+
+/// Monotone cubic (Fritsch-Carlson) interpolation through sorted points.
+/// Never overshoots, so a curve between increasing points stays increasing.
+fn monotone_cubic(xs: &[f32], ys: &[f32], x: f32) -> f32 {
+    let n = xs.len();
+    if x <= xs[0] {
+        return ys[0];
+    }
+    if x >= xs[n - 1] {
+        return ys[n - 1];
+    }
+
+    let secants: Vec<f32> = (0..n - 1)
+        .map(|i| (ys[i + 1] - ys[i]) / (xs[i + 1] - xs[i]).max(1e-6))
+        .collect();
+    let mut tangents = vec![0.0; n];
+    tangents[0] = secants[0];
+    tangents[n - 1] = secants[n - 2];
+    for i in 1..n - 1 {
+        tangents[i] = if secants[i - 1] * secants[i] <= 0.0 {
+            0.0
+        } else {
+            (secants[i - 1] + secants[i]) / 2.0
+        };
+    }
+    for i in 0..n - 1 {
+        if secants[i] == 0.0 {
+            tangents[i] = 0.0;
+            tangents[i + 1] = 0.0;
+            continue;
+        }
+        let a = tangents[i] / secants[i];
+        let b = tangents[i + 1] / secants[i];
+        let h = a * a + b * b;
+        if h > 9.0 {
+            let t = 3.0 / h.sqrt();
+            tangents[i] = t * a * secants[i];
+            tangents[i + 1] = t * b * secants[i];
+        }
+    }
+
+    let i = xs.windows(2).position(|w| x < w[1]).unwrap_or(n - 2);
+    let h = xs[i + 1] - xs[i];
+    let t = (x - xs[i]) / h.max(1e-6);
+    let t2 = t * t;
+    let t3 = t2 * t;
+    (2.0 * t3 - 3.0 * t2 + 1.0) * ys[i]
+        + (t3 - 2.0 * t2 + t) * h * tangents[i]
+        + (-2.0 * t3 + 3.0 * t2) * ys[i + 1]
+        + (t3 - t2) * h * tangents[i + 1]
+}
+
+/// One of the curves of a `ColorCurves` set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CurveChannel {
+    Luma,
+    Red,
+    Green,
+    Blue,
+    HueVsSat,
+    HueVsHue,
+    LumaVsSat,
+}
+
+impl CurveChannel {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "luma" => Some(CurveChannel::Luma),
+            "red" => Some(CurveChannel::Red),
+            "green" => Some(CurveChannel::Green),
+            "blue" => Some(CurveChannel::Blue),
+            "hue_vs_sat" => Some(CurveChannel::HueVsSat),
+            "hue_vs_hue" => Some(CurveChannel::HueVsHue),
+            "luma_vs_sat" => Some(CurveChannel::LumaVsSat),
+            _ => None,
+        }
+    }
+
+    /// Output of an empty curve: the input for the tone curves, 0.5 for
+    /// the secondary curves
+    fn neutral(self) -> Option<f32> {
+        match self {
+            CurveChannel::Luma | CurveChannel::Red | CurveChannel::Green | CurveChannel::Blue => {
+                None
+            }
+            _ => Some(0.5),
+        }
+    }
+
+    /// Hue curves wrap around at 0/1
+    fn periodic(self) -> bool {
+        matches!(self, CurveChannel::HueVsSat | CurveChannel::HueVsHue)
+    }
+}
+
+fn unknown_channel(channel: &str) -> PyErr {
+    PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Unknown curve channel '{}'", channel))
+}
+
+/// Apply a set of curves, read through `curve`, to one gamma-encoded RGB
+/// value (0-1).
+///
+/// The red/green/blue curves run first, then the luma curve as a master
+/// curve on all three channels. The secondary curves (y = 0.5 is neutral)
+/// then work in HSL: hue vs hue shifts hue by up to ±180°, hue vs sat and
+/// luma vs sat scale saturation by up to 2x.
+fn grade_rgb(rgb: [f32; 3], curve: impl Fn(CurveChannel, f32) -> f32) -> [f32; 3] {
+    let channels = [CurveChannel::Red, CurveChannel::Green, CurveChannel::Blue];
+    let mut out = [0.0; 3];
+    for c in 0..3 {
+        out[c] = curve(CurveChannel::Luma, curve(channels[c], rgb[c]));
+    }
+
+    let (h, s, l) = rgb_to_hsl_f32(out[0], out[1], out[2]);
+    if s <= 0.0 {
+        return out;
+    }
+    let hue = h / 360.0;
+    let shift = (curve(CurveChannel::HueVsHue, hue) - 0.5) * 360.0;
+    let sat_gain =
+        2.0 * curve(CurveChannel::HueVsSat, hue) * 2.0 * curve(CurveChannel::LumaVsSat, l);
+    if shift == 0.0 && sat_gain == 1.0 {
+        return out;
+    }
+    let (r, g, b) = hsl_to_rgb_f32(
+        normalize_angle(h + shift),
+        (s * sat_gain).clamp(0.0, 1.0),
+        l,
+    );
+    [r, g, b]
+}
+
+/// Evaluate a curve at `x`. An empty curve returns `neutral` (or `x` itself
+/// when `neutral` is `None`); a single point gives a flat line. Periodic
+/// curves wrap around at 0/1, as hue does.
+fn evaluate_points(points: &[CurvePoint], x: f32, neutral: Option<f32>, periodic: bool) -> f32 {
+    match points.len() {
+        0 => return neutral.unwrap_or(x),
+        1 => return points[0].y,
+        _ => {}
+    }
+    let mut sorted: Vec<(f32, f32)> = points.iter().map(|p| (p.x, p.y)).collect();
+    sorted.sort_by(|a, b| a.0.total_cmp(&b.0));
+    if periodic {
+        let (first, last) = (sorted[0], sorted[sorted.len() - 1]);
+        sorted.insert(0, (last.0 - 1.0, last.1));
+        sorted.push((first.0 + 1.0, first.1));
+    }
+    let xs: Vec<f32> = sorted.iter().map(|p| p.0).collect();
+    let ys: Vec<f32> = sorted.iter().map(|p| p.1).collect();
+    monotone_cubic(&xs, &ys, x)
+}
+
+/// Curves sampled into fixed-size tables for fast per-pixel lookup
+#[derive(Debug, Clone)]
+pub struct BakedCurves {
+    pub luma: Vec<f32>,
+    pub red: Vec<f32>,
+    pub green: Vec<f32>,
+    pub blue: Vec<f32>,
+    pub hue_vs_sat: Vec<f32>,
+    pub hue_vs_hue: Vec<f32>,
+    pub luma_vs_sat: Vec<f32>,
+}
+
+fn lookup(table: &[f32], x: f32) -> f32 {
+    let t = x.clamp(0.0, 1.0) * (table.len() - 1) as f32;
+    let i = (t as usize).min(table.len() - 2);
+    let f = t - i as f32;
+    table[i] + (table[i + 1] - table[i]) * f
+}
+
+impl BakedCurves {
+    pub fn table(&self, channel: CurveChannel) -> &[f32] {
+        match channel {
+            CurveChannel::Luma => &self.luma,
+            CurveChannel::Red => &self.red,
+            CurveChannel::Green => &self.green,
+            CurveChannel::Blue => &self.blue,
+            CurveChannel::HueVsSat => &self.hue_vs_sat,
+            CurveChannel::HueVsHue => &self.hue_vs_hue,
+            CurveChannel::LumaVsSat => &self.luma_vs_sat,
+        }
+    }
+
+    /// Apply to one gamma-encoded RGB value (0-1); see `grade_rgb`
+    pub fn apply_rgb(&self, rgb: [f32; 3]) -> [f32; 3] {
+        grade_rgb(rgb, |channel, x| lookup(self.table(channel), x))
+    }
+}
+
 #[pyclass]
 #[derive(Clone)]
 pub struct ColorCurves {
@@ -81,6 +748,13 @@ pub struct ColorCurves {
     pub red: Vec<CurvePoint>,
     pub green: Vec<CurvePoint>,
     pub blue: Vec<CurvePoint>,
+    pub hue_vs_sat: Vec<CurvePoint>,
+    pub hue_vs_hue: Vec<CurvePoint>,
+    pub luma_vs_sat: Vec<CurvePoint>,
+}
+
+fn identity_curve() -> Vec<CurvePoint> {
+    vec![CurvePoint::new(0.0, 0.0), CurvePoint::new(1.0, 1.0)]
 }
 
 #[pymethods]
@@ -88,31 +762,496 @@ impl ColorCurves {
     #[new]
     fn new() -> Self {
         Self {
-            luma: vec![CurvePoint {
-                x: 0.0,
-                y: 0.0,
-                handle_in: (0.0, 0.0),
-                handle_out: (1.0, 1.0),
-            }],
-            red: Vec::new(),
-            green: Vec::new(),
-            blue: Vec::new(),
+            luma: identity_curve(),
+            red: identity_curve(),
+            green: identity_curve(),
+            blue: identity_curve(),
+            hue_vs_sat: Vec::new(),
+            hue_vs_hue: Vec::new(),
+            luma_vs_sat: Vec::new(),
+        }
+    }
+
+    /// Add a point, replacing any existing point at the same x
+    fn add_point(&mut self, channel: &str, x: f32, y: f32) -> PyResult<()> {
+        if !x.is_finite() || !y.is_finite() {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Curve point ({}, {}) must be finite",
+                x, y
+            )));
+        }
+        let channel = CurveChannel::from_name(channel).ok_or_else(|| unknown_channel(channel))?;
+        let points = self.points_mut(channel);
+        let x = x.clamp(0.0, 1.0);
+        points.retain(|p| (p.x - x).abs() > 1e-4);
+        points.push(CurvePoint::new(x, y));
+        points.sort_by(|a, b| a.x.total_cmp(&b.x));
+        Ok(())
+    }
+
+    /// Reset a channel to its neutral shape
+    fn reset_channel(&mut self, channel: &str) {
+        if let Some(channel) = CurveChannel::from_name(channel) {
+            *self.points_mut(channel) = match channel.neutral() {
+                None => identity_curve(),
+                Some(_) => Vec::new(),
+            };
         }
     }
 
-    fn add_point(&mut self, channel: &str, x: f32, y: f32) {
-        let point = CurvePoint {
+    fn evaluate(&self, channel: &str, x: f32) -> PyResult<f32> {
+        let channel = CurveChannel::from_name(channel).ok_or_else(|| unknown_channel(channel))?;
+        Ok(self.evaluate_channel(channel, x))
+    }
+
+    /// Apply all curves to a single 0-1 RGB colour. The curves are evaluated
+    /// directly; frames go through `bake` instead.
+    fn apply_color(&self, r: f32, g: f32, b: f32) -> (f32, f32, f32) {
+        let out = grade_rgb([r, g, b], |channel, x| self.evaluate_channel(channel, x));
+        (out[0], out[1], out[2])
+    }
+}
+
+impl ColorCurves {
+    pub fn points(&self, channel: CurveChannel) -> &[CurvePoint] {
+        match channel {
+            CurveChannel::Luma => &self.luma,
+            CurveChannel::Red => &self.red,
+            CurveChannel::Green => &self.green,
+            CurveChannel::Blue => &self.blue,
+            CurveChannel::HueVsSat => &self.hue_vs_sat,
+            CurveChannel::HueVsHue => &self.hue_vs_hue,
+            CurveChannel::LumaVsSat => &self.luma_vs_sat,
+        }
+    }
+
+    fn points_mut(&mut self, channel: CurveChannel) -> &mut Vec<CurvePoint> {
+        match channel {
+            CurveChannel::Luma => &mut self.luma,
+            CurveChannel::Red => &mut self.red,
+            CurveChannel::Green => &mut self.green,
+            CurveChannel::Blue => &mut self.blue,
+            CurveChannel::HueVsSat => &mut self.hue_vs_sat,
+            CurveChannel::HueVsHue => &mut self.hue_vs_hue,
+            CurveChannel::LumaVsSat => &mut self.luma_vs_sat,
+        }
+    }
+
+    pub fn evaluate_channel(&self, channel: CurveChannel, x: f32) -> f32 {
+        evaluate_points(
+            self.points(channel),
             x,
-            y,
-            handle_in: (x - 0.1, y),
-            handle_out: (x + 0.1, y),
+            channel.neutral(),
+            channel.periodic(),
+        )
+    }
+
+    /// Sample every curve into a `CURVE_LUT_SIZE` table
+    pub fn bake(&self) -> BakedCurves {
+        let bake = |channel: CurveChannel| -> Vec<f32> {
+            (0..CURVE_LUT_SIZE)
+                .map(|i| self.evaluate_channel(channel, i as f32 / (CURVE_LUT_SIZE - 1) as f32))
+                .collect()
         };
-        match channel {
-            "luma" => self.luma.push(point),
-            "red" => self.red.push(point),
-            "green" => self.green.push(point),
-            "blue" => self.blue.push(point),
-            _ => {}
+        BakedCurves {
+            luma: bake(CurveChannel::Luma),
+            red: bake(CurveChannel::Red),
+            green: bake(CurveChannel::Green),
+            blue: bake(CurveChannel::Blue),
+            hue_vs_sat: bake(CurveChannel::HueVsSat),
+            hue_vs_hue: bake(CurveChannel::HueVsHue),
+            luma_vs_sat: bake(CurveChannel::LumaVsSat),
+        }
+    }
+
+    /// Apply the curves to an RGBA frame in place
+    pub fn apply<P: Pixel>(&self, frame: &mut [P], width: u32, height: u32) {
+        debug_assert_eq!(frame.len(), (width * height * 4) as usize);
+        let baked = self.bake();
+        process_f32(frame, |frame| {
+            frame.par_chunks_mut(4).for_each(|px| {
+                let out = baked.apply_rgb([px[0], px[1], px[2]]);
+                px[..3].copy_from_slice(&out);
+            });
+        });
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum LutError {
+    #[error("failed to read LUT '{path}': {source}")]
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("{path}: line {line}: {message}")]
+    Parse {
+        path: String,
+        line: usize,
+        message: String,
+    },
+    #[error("unsupported LUT format '{0}' (expected .cube or .3dl)")]
+    UnsupportedFormat(String),
+}
+
+impl From<LutError> for PyErr {
+    fn from(err: LutError) -> PyErr {
+        match err {
+            LutError::Io { .. } => PyErr::new::<pyo3::exceptions::PyIOError, _>(err.to_string()),
+            _ => PyErr::new::<pyo3::exceptions::PyValueError, _>(err.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LutInterpolation {
+    Trilinear,
+    Tetrahedral,
+}
+
+/// Parsed LUT table. 3D entries are stored with red changing fastest, as in
+/// `.cube` files; values are normalised to 0-1 output.
+#[derive(Debug, Clone)]
+pub enum LutData {
+    OneD {
+        domain_min: [f32; 3],
+        domain_max: [f32; 3],
+        table: Vec<[f32; 3]>,
+    },
+    ThreeD {
+        size: usize,
+        domain_min: [f32; 3],
+        domain_max: [f32; 3],
+        table: Vec<[f32; 3]>,
+    },
+}
+
+fn lut_parse_error(path: &str, line: usize, message: impl Into<String>) -> LutError {
+    LutError::Parse {
+        path: path.to_string(),
+        line,
+        message: message.into(),
+    }
+}
+
+fn parse_floats<const N: usize>(
+    path: &str,
+    line: usize,
+    values: &[&str],
+) -> Result<[f32; N], LutError> {
+    if values.len() != N {
+        return Err(lut_parse_error(
+            path,
+            line,
+            format!("expected {} values, found {}", N, values.len()),
+        ));
+    }
+    let mut out = [0.0; N];
+    for (o, v) in out.iter_mut().zip(values) {
+        *o = v
+            .parse()
+            .map_err(|_| lut_parse_error(path, line, format!("invalid number '{}'", v)))?;
+    }
+    Ok(out)
+}
+
+impl LutData {
+    /// Parse an Adobe/Resolve `.cube` file (1D or 3D, with optional domain)
+    pub fn parse_cube(path: &str, text: &str) -> Result<Self, LutError> {
+        let mut size_1d = None;
+        let mut size_3d = None;
+        let mut domain_min = [0.0; 3];
+        let mut domain_max = [1.0; 3];
+        let mut table = Vec::new();
+        let mut last_line = 0;
+
+        for (index, raw) in text.lines().enumerate() {
+            let line = index + 1;
+            let content = raw.split('#').next().unwrap_or("").trim();
+            if content.is_empty() {
+                continue;
+            }
+            last_line = line;
+            let mut parts = content.split_whitespace();
+            let keyword = parts.next().unwrap_or("");
+            let values: Vec<&str> = parts.collect();
+            let starts_numeric = keyword
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_digit() || c == '-' || c == '+' || c == '.');
+
+            if starts_numeric {
+                let mut row = vec![keyword];
+                row.extend(values);
+                table.push(parse_floats::<3>(path, line, &row)?);
+                continue;
+            }
+            if !table.is_empty() {
+                return Err(lut_parse_error(
+                    path,
+                    line,
+                    format!("keyword '{}' after table data", keyword),
+                ));
+            }
+            let parse_size = |max: usize| -> Result<usize, LutError> {
+                let [n] = parse_floats::<1>(path, line, &values)?;
+                if n.fract() != 0.0 || n < 2.0 || n > max as f32 {
+                    return Err(lut_parse_error(
+                        path,
+                        line,
+                        format!("{} must be an integer from 2 to {}", keyword, max),
+                    ));
+                }
+                Ok(n as usize)
+            };
+            match keyword {
+                "TITLE" => {}
+                "LUT_1D_SIZE" => size_1d = Some(parse_size(65536)?),
+                "LUT_3D_SIZE" => size_3d = Some(parse_size(256)?),
+                "DOMAIN_MIN" => domain_min = parse_floats::<3>(path, line, &values)?,
+                "DOMAIN_MAX" => domain_max = parse_floats::<3>(path, line, &values)?,
+                "LUT_1D_INPUT_RANGE" | "LUT_3D_INPUT_RANGE" => {
+                    let [lo, hi] = parse_floats::<2>(path, line, &values)?;
+                    domain_min = [lo; 3];
+                    domain_max = [hi; 3];
+                }
+                // Vendor keywords such as LUT_IN_VIDEO_RANGE are ignored
+                _ => {}
+            }
+        }
+
+        if (0..3).any(|c| domain_max[c] <= domain_min[c]) {
+            return Err(lut_parse_error(
+                path,
+                last_line,
+                "DOMAIN_MAX must be greater than DOMAIN_MIN",
+            ));
+        }
+        let (expected, data) = match (size_1d, size_3d) {
+            (Some(_), Some(_)) => {
+                return Err(lut_parse_error(
+                    path,
+                    last_line,
+                    "file declares both LUT_1D_SIZE and LUT_3D_SIZE",
+                ))
+            }
+            (Some(n), None) => (
+                n,
+                LutData::OneD {
+                    domain_min,
+                    domain_max,
+                    table,
+                },
+            ),
+            (None, Some(n)) => (
+                n * n * n,
+                LutData::ThreeD {
+                    size: n,
+                    domain_min,
+                    domain_max,
+                    table,
+                },
+            ),
+            (None, None) => {
+                return Err(lut_parse_error(
+                    path,
+                    last_line,
+                    "missing LUT_1D_SIZE or LUT_3D_SIZE",
+                ))
+            }
+        };
+        if data.len() != expected {
+            return Err(lut_parse_error(
+                path,
+                last_line,
+                format!("expected {} table entries, found {}", expected, data.len()),
+            ));
+        }
+        Ok(data)
+    }
+
+    /// Parse an Autodesk/Lustre `.3dl` file.
+    ///
+    /// The first numeric row is the input shaper, whose length gives the mesh
+    /// size. Table rows are integers with blue changing fastest; the output bit
+    /// depth comes from a `Mesh` header or is inferred from the largest value.
+    pub fn parse_3dl(path: &str, text: &str) -> Result<Self, LutError> {
+        let mut size = None;
+        let mut output_bits = None;
+        let mut rows = Vec::new();
+        let mut last_line = 0;
+
+        for (index, raw) in text.lines().enumerate() {
+            let line = index + 1;
+            let content = raw.split('#').next().unwrap_or("").trim();
+            if content.is_empty() {
+                continue;
+            }
+            last_line = line;
+            let values: Vec<&str> = content.split_whitespace().collect();
+            match values[0] {
+                "3DMESH" => continue,
+                "Mesh" => {
+                    let [_, out_bits] = parse_floats::<2>(path, line, &values[1..])?;
+                    if out_bits.fract() != 0.0 || !(1.0..=32.0).contains(&out_bits) {
+                        return Err(lut_parse_error(
+                            path,
+                            line,
+                            format!("output depth must be 1 to 32 bits, found {}", values[2]),
+                        ));
+                    }
+                    output_bits = Some(out_bits as u32);
+                    continue;
+                }
+                _ => {}
+            }
+            if size.is_none() {
+                if values.len() < 2 {
+                    return Err(lut_parse_error(
+                        path,
+                        line,
+                        "expected an input shaper row before the table",
+                    ));
+                }
+                for v in &values {
+                    v.parse::<f32>().map_err(|_| {
+                        lut_parse_error(path, line, format!("invalid number '{}'", v))
+                    })?;
+                }
+                size = Some(values.len());
+                continue;
+            }
+            rows.push(parse_floats::<3>(path, line, &values)?);
+        }
+
+        let size = size.ok_or_else(|| lut_parse_error(path, last_line, "no LUT data"))?;
+        if rows.len() != size * size * size {
+            return Err(lut_parse_error(
+                path,
+                last_line,
+                format!(
+                    "expected {} table entries for a {}-point mesh, found {}",
+                    size * size * size,
+                    size,
+                    rows.len()
+                ),
+            ));
+        }
+
+        let max_value = rows.iter().flatten().fold(0.0f32, |m, v| m.max(*v));
+        let scale = match output_bits {
+            Some(bits) => ((1u64 << bits) - 1) as f32,
+            None if max_value <= 1023.0 => 1023.0,
+            None if max_value <= 4095.0 => 4095.0,
+            None => 65535.0,
+        };
+
+        // Reorder from blue-fastest to red-fastest
+        let mut table = vec![[0.0; 3]; rows.len()];
+        for r in 0..size {
+            for g in 0..size {
+                for b in 0..size {
+                    let src = rows[(r * size + g) * size + b];
+                    table[(b * size + g) * size + r] = src.map(|v| v / scale);
+                }
+            }
+        }
+        Ok(LutData::ThreeD {
+            size,
+            domain_min: [0.0; 3],
+            domain_max: [1.0; 3],
+            table,
+        })
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            LutData::OneD { table, .. } | LutData::ThreeD { table, .. } => table.len(),
+        }
+    }
+
+    /// Look up one RGB value (0-1 within the LUT domain)
+    pub fn sample(&self, rgb: [f32; 3], interpolation: LutInterpolation) -> [f32; 3] {
+        match self {
+            LutData::OneD {
+                domain_min,
+                domain_max,
+                table,
+            } => {
+                let n = table.len();
+                let mut out = [0.0; 3];
+                for c in 0..3 {
+                    let t = ((rgb[c] - domain_min[c]) / (domain_max[c] - domain_min[c]))
+                        .clamp(0.0, 1.0)
+                        * (n - 1) as f32;
+                    let i = (t.floor() as usize).min(n - 2);
+                    let f = t - i as f32;
+                    out[c] = table[i][c] + (table[i + 1][c] - table[i][c]) * f;
+                }
+                out
+            }
+            LutData::ThreeD {
+                size,
+                domain_min,
+                domain_max,
+                table,
+            } => {
+                let n = *size;
+                let mut base = [0usize; 3];
+                let mut frac = [0.0f32; 3];
+                for c in 0..3 {
+                    let t = ((rgb[c] - domain_min[c]) / (domain_max[c] - domain_min[c]))
+                        .clamp(0.0, 1.0)
+                        * (n - 1) as f32;
+                    base[c] = (t.floor() as usize).min(n - 2);
+                    frac[c] = t - base[c] as f32;
+                }
+                let at = |dr: usize, dg: usize, db: usize| {
+                    table[((base[2] + db) * n + base[1] + dg) * n + base[0] + dr]
+                };
+                let (fr, fg, fb) = (frac[0], frac[1], frac[2]);
+                let mut out = [0.0; 3];
+                match interpolation {
+                    LutInterpolation::Trilinear => {
+                        let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+                        for (c, o) in out.iter_mut().enumerate() {
+                            let c00 = lerp(at(0, 0, 0)[c], at(1, 0, 0)[c], fr);
+                            let c10 = lerp(at(0, 1, 0)[c], at(1, 1, 0)[c], fr);
+                            let c01 = lerp(at(0, 0, 1)[c], at(1, 0, 1)[c], fr);
+                            let c11 = lerp(at(0, 1, 1)[c], at(1, 1, 1)[c], fr);
+                            *o = lerp(lerp(c00, c10, fg), lerp(c01, c11, fg), fb);
+                        }
+                    }
+                    LutInterpolation::Tetrahedral => {
+                        let c000 = at(0, 0, 0);
+                        let c111 = at(1, 1, 1);
+                        // Walk the tetrahedron containing the point, ordered by
+                        // the largest fractional component first
+                        let (first, second, w) = if fr > fg {
+                            if fg > fb {
+                                (at(1, 0, 0), at(1, 1, 0), [fr, fg, fb])
+                            } else if fr > fb {
+                                (at(1, 0, 0), at(1, 0, 1), [fr, fb, fg])
+                            } else {
+                                (at(0, 0, 1), at(1, 0, 1), [fb, fr, fg])
+                            }
+                        } else if fb > fg {
+                            (at(0, 0, 1), at(0, 1, 1), [fb, fg, fr])
+                        } else if fb > fr {
+                            (at(0, 1, 0), at(0, 1, 1), [fg, fb, fr])
+                        } else {
+                            (at(0, 1, 0), at(1, 1, 0), [fg, fr, fb])
+                        };
+                        for c in 0..3 {
+                            out[c] = c000[c]
+                                + w[0] * (first[c] - c000[c])
+                                + w[1] * (second[c] - first[c])
+                                + w[2] * (c111[c] - second[c]);
+                        }
+                    }
+                }
+                out
+            }
         }
     }
 }
@@ -124,6 +1263,9 @@ pub struct LUT {
     pub path: String,
     #[pyo3(get, set)]
     pub intensity: f32, // 0.0 to 1.0
+    #[pyo3(get, set)]
+    pub interpolation: String, // "tetrahedral" or "trilinear"
+    pub data: Option<LutData>,
 }
 
 #[pymethods]
@@ -133,12 +1275,297 @@ impl LUT {
         Self {
             path,
             intensity: 1.0,
+            interpolation: "tetrahedral".to_string(),
+            data: None,
         }
     }
 
     fn load_cube(&mut self, path: &str) -> PyResult<()> {
+        let text = read_lut(path)?;
+        self.data = Some(LutData::parse_cube(path, &text)?);
+        self.path = path.to_string();
+        Ok(())
+    }
+
+    fn load_3dl(&mut self, path: &str) -> PyResult<()> {
+        let text = read_lut(path)?;
+        self.data = Some(LutData::parse_3dl(path, &text)?);
         self.path = path.to_string();
-        // LUT loading would be implemented here
         Ok(())
     }
+
+    /// Load `path`, choosing the parser from its extension
+    fn reload(&mut self) -> PyResult<()> {
+        let path = self.path.clone();
+        match std::path::Path::new(&path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase())
+            .as_deref()
+        {
+            Some("cube") => self.load_cube(&path),
+            Some("3dl") => self.load_3dl(&path),
+            _ => Err(LutError::UnsupportedFormat(path).into()),
+        }
+    }
+
+    fn is_loaded(&self) -> bool {
+        self.data.is_some()
+    }
+
+    fn size(&self) -> usize {
+        self.data.as_ref().map_or(0, |d| d.len())
+    }
+
+    /// Apply the LUT (mixed by `intensity`) to a single 0-1 RGB colour
+    fn apply_color(&self, r: f32, g: f32, b: f32) -> (f32, f32, f32) {
+        let out = self.apply_rgb([r, g, b]);
+        (out[0], out[1], out[2])
+    }
+}
+
+fn read_lut(path: &str) -> Result<String, LutError> {
+    std::fs::read_to_string(path).map_err(|source| LutError::Io {
+        path: path.to_string(),
+        source,
+    })
+}
+
+impl LUT {
+    fn lut_interpolation(&self) -> LutInterpolation {
+        match self.interpolation.as_str() {
+            "trilinear" => LutInterpolation::Trilinear,
+            _ => LutInterpolation::Tetrahedral,
+        }
+    }
+
+    /// Apply to one colour; an unloaded LUT passes values through
+    pub fn apply_rgb(&self, rgb: [f32; 3]) -> [f32; 3] {
+        let Some(data) = &self.data else {
+            return rgb;
+        };
+        let mix = self.intensity.clamp(0.0, 1.0);
+        let looked_up = data.sample(rgb, self.lut_interpolation());
+        [0, 1, 2].map(|c| rgb[c] + (looked_up[c] - rgb[c]) * mix)
+    }
+
+    /// Apply to an RGBA frame in place. LUTs are applied to the encoded
+    /// values, as delivered; alpha is untouched.
+    pub fn apply<P: Pixel>(&self, frame: &mut [P], width: u32, height: u32) {
+        debug_assert_eq!(frame.len(), (width * height * 4) as usize);
+        if self.data.is_none() || self.intensity <= 0.0 {
+            return;
+        }
+        process_f32(frame, |frame| {
+            frame.par_chunks_mut(4).for_each(|px| {
+                let out = self.apply_rgb([px[0], px[1], px[2]]);
+                px[..3].copy_from_slice(&out);
+            });
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn neutral_grade_is_identity() {
+        let grading = ColorGrading::new();
+        let out = grading.grade_linear([0.05, 0.18, 0.9]);
+        for (a, b) in out.iter().zip([0.05, 0.18, 0.9]) {
+            assert!((a - b).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn exposure_is_measured_in_stops() {
+        let mut grading = ColorGrading::new();
+        grading.exposure = 1.0;
+        assert!((grading.grade_linear([0.2, 0.2, 0.2])[1] - 0.4).abs() < 1e-6);
+    }
+
+    fn encoded_frame(pixels: &[[f32; 3]]) -> Vec<f32> {
+        pixels
+            .iter()
+            .flat_map(|p| {
+                [
+                    linear_to_srgb(p[0]),
+                    linear_to_srgb(p[1]),
+                    linear_to_srgb(p[2]),
+                    1.0,
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn white_balance_estimate_inverts_the_gains() {
+        let mut warm = ColorGrading::new();
+        warm.temperature = 30.0;
+        warm.tint = -10.0;
+        let pixels: Vec<[f32; 3]> = (1..20)
+            .map(|i| warm.grade_linear([i as f32 * 0.03; 3]))
+            .collect();
+        let estimate = estimate_white_balance(&encoded_frame(&pixels));
+        assert!((estimate.temperature + 30.0).abs() < 0.5, "{:?}", estimate);
+        // Tint is not symmetric in the model, so only check its direction
+        assert!(estimate.tint > 0.0, "{:?}", estimate);
+
+        let mut fixed = ColorGrading::new();
+        fixed.temperature = estimate.temperature;
+        fixed.tint = estimate.tint;
+        let out = fixed.grade_linear(pixels[5]);
+        assert!((out[0] - out[2]).abs() < 1e-3 && (out[0] - out[1]).abs() < 1e-3);
+    }
+
+    #[test]
+    fn matching_recovers_a_known_grade() {
+        let mut camera_b = ColorGrading::new();
+        camera_b.set_cdl((1.3, 1.0, 0.8), (0.01, 0.0, 0.02), (0.9, 1.0, 1.1), 1.0);
+        let source: Vec<[f32; 3]> = (0..100)
+            .map(|i| {
+                let v = i as f32 / 120.0;
+                [v, v * 0.9 + 0.02, v * 0.8 + 0.05]
+            })
+            .collect();
+        let reference: Vec<[f32; 3]> = source.iter().map(|p| camera_b.grade_linear(*p)).collect();
+
+        let mut grading = ColorGrading::new();
+        grading.match_frame(&encoded_frame(&source), &encoded_frame(&reference));
+        for (p, r) in source.iter().zip(&reference).step_by(10) {
+            let out = grading.grade_linear(*p);
+            for c in 0..3 {
+                assert!((out[c] - r[c]).abs() < 0.02, "{:?} vs {:?}", out, r);
+            }
+        }
+    }
+
+    #[test]
+    fn cdl_values_round_trip_and_match_the_asc_formula() {
+        let mut grading = ColorGrading::new();
+        grading.set_cdl((1.2, 1.0, 0.8), (0.01, 0.0, -0.02), (0.9, 1.0, 1.1), 1.0);
+        let (slope, offset, power, sat) = grading.get_cdl();
+        assert!((slope.0 - 1.2).abs() < 1e-5 && (offset.2 + 0.02).abs() < 1e-6);
+        assert!((power.2 - 1.1).abs() < 1e-5 && (sat - 1.0).abs() < 1e-6);
+
+        let out = grading.grade_linear([0.5, 0.5, 0.5]);
+        assert!((out[0] - (0.5f32 * 1.2 + 0.01).powf(0.9)).abs() < 1e-5);
+        assert!((out[2] - (0.5f32 * 0.8 - 0.02).powf(1.1)).abs() < 1e-5);
+    }
+
+    const IDENTITY_CUBE: &str = "TITLE \"identity\"\n# comment\nLUT_3D_SIZE 2\n\n\
+        0 0 0\n1 0 0\n0 1 0\n1 1 0\n0 0 1\n1 0 1\n0 1 1\n1 1 1\n";
+
+    #[test]
+    fn identity_cube_is_transparent_with_both_interpolations() {
+        let data = LutData::parse_cube("identity.cube", IDENTITY_CUBE).unwrap();
+        for interpolation in [LutInterpolation::Trilinear, LutInterpolation::Tetrahedral] {
+            let out = data.sample([0.2, 0.7, 0.9], interpolation);
+            for (a, b) in out.iter().zip([0.2, 0.7, 0.9]) {
+                assert!((a - b).abs() < 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn cube_errors_report_line_numbers() {
+        let text = "LUT_3D_SIZE 2\n0 0 0\n1 0\n";
+        let err = LutData::parse_cube("bad.cube", text).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "bad.cube: line 3: expected 3 values, found 2"
+        );
+
+        let err = LutData::parse_cube("short.cube", "LUT_1D_SIZE 4\n0 0 0\n1 1 1\n").unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("line 3: expected 4 table entries, found 2"));
+
+        for depth in ["64", "0", "-2", "10.5"] {
+            let text = format!("3DMESH\nMesh 4 {}\n0 1023\n", depth);
+            let err = LutData::parse_3dl("mesh.3dl", &text).unwrap_err();
+            assert!(matches!(err, LutError::Parse { line: 2, .. }), "{}", err);
+        }
+    }
+
+    #[test]
+    fn one_d_cube_honours_domain() {
+        let text = "LUT_1D_SIZE 2\nDOMAIN_MIN 0 0 0\nDOMAIN_MAX 2 2 2\n0 0 0\n1 1 1\n";
+        let data = LutData::parse_cube("domain.cube", text).unwrap();
+        let out = data.sample([1.0, 0.5, 2.0], LutInterpolation::Trilinear);
+        assert_eq!(out, [0.5, 0.25, 1.0]);
+    }
+
+    #[test]
+    fn three_dl_is_reordered_and_scaled() {
+        // Blue-fastest ordering; only the red output of pure red is 1023
+        let mut text = String::from("0 1023\n");
+        for r in 0..2 {
+            for g in 0..2 {
+                for b in 0..2 {
+                    text.push_str(&format!("{} {} {}\n", r * 1023, g * 1023, b * 1023));
+                }
+            }
+        }
+        let data = LutData::parse_3dl("mesh.3dl", &text).unwrap();
+        let out = data.sample([1.0, 0.0, 0.0], LutInterpolation::Tetrahedral);
+        assert_eq!(out, [1.0, 0.0, 0.0]);
+        let out = data.sample([0.0, 0.0, 1.0], LutInterpolation::Tetrahedral);
+        assert_eq!(out, [0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn intensity_mixes_with_the_original() {
+        let text = "LUT_1D_SIZE 2\n1 1 1\n1 1 1\n";
+        let mut lut = LUT::new("white.cube".to_string());
+        lut.data = Some(LutData::parse_cube("white.cube", text).unwrap());
+        lut.intensity = 0.5;
+        assert_eq!(lut.apply_rgb([0.0, 0.5, 1.0]), [0.5, 0.75, 1.0]);
+    }
+
+    #[test]
+    fn default_curves_are_identity() {
+        let curves = ColorCurves::new();
+        let mut frame = vec![0u8, 64, 200, 255, 255, 17, 90, 128];
+        let original = frame.clone();
+        curves.apply(&mut frame, 2, 1);
+        assert_eq!(frame, original);
+    }
+
+    #[test]
+    fn monotone_curve_does_not_overshoot() {
+        let mut curves = ColorCurves::new();
+        curves.add_point("luma", 0.25, 0.1).unwrap();
+        curves.add_point("luma", 0.5, 0.9).unwrap();
+        assert!(curves.add_point("luma", f32::NAN, 0.5).is_err());
+        assert!(curves.add_point("luma", 0.75, f32::INFINITY).is_err());
+        assert!(curves.add_point("lumma", 0.75, 0.5).is_err());
+        let mut previous = 0.0;
+        for i in 0..=100 {
+            let v = curves.evaluate("luma", i as f32 / 100.0).unwrap();
+            assert!(v >= previous && v <= 1.0);
+            previous = v;
+        }
+        assert!((curves.evaluate("luma", 0.5).unwrap() - 0.9).abs() < 1e-6);
+    }
+
+    #[test]
+    fn hue_vs_sat_desaturates_the_selected_hue() {
+        let mut curves = ColorCurves::new();
+        curves.add_point("hue_vs_sat", 0.0, 0.0).unwrap();
+        curves.add_point("hue_vs_sat", 0.5, 0.5).unwrap();
+        let red = curves.bake().apply_rgb([1.0, 0.0, 0.0]);
+        assert!((red[0] - red[1]).abs() < 1e-3 && (red[1] - red[2]).abs() < 1e-3);
+        let cyan = curves.bake().apply_rgb([0.0, 1.0, 1.0]);
+        assert!((cyan[0]).abs() < 1e-3 && (cyan[1] - 1.0).abs() < 1e-3);
+        // The single-colour path evaluates the curves without baking
+        for rgb in [[1.0, 0.0, 0.0], [0.2, 0.7, 0.4], [0.9, 0.8, 0.1]] {
+            let baked = curves.bake().apply_rgb(rgb);
+            let (r, g, b) = curves.apply_color(rgb[0], rgb[1], rgb[2]);
+            for (a, e) in [r, g, b].iter().zip(baked) {
+                assert!((a - e).abs() < 1e-3, "{:?}", rgb);
+            }
+        }
+    }
 }
@@ -0,0 +1,483 @@
+use crate::frame::{process_f32, Pixel};
+use nalgebra::{Matrix3, Vector3};
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Encoding curve of a colour space. Decoding yields linear light where 1.0
+/// is reference (diffuse) white, so SDR and HDR sources can be mixed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TransferFunction {
+    Linear,
+    Srgb,
+    /// BT.709/BT.2020 camera OETF
+    Rec709,
+    /// SMPTE ST 2084, with 203 nits (BT.2408) as reference white
+    Pq,
+    /// ARIB STD-B67 hybrid log-gamma, with 75% signal as reference white
+    Hlg,
+}
+
+const PQ_M1: f32 = 2610.0 / 16384.0;
+const PQ_M2: f32 = 2523.0 / 4096.0 * 128.0;
+const PQ_C1: f32 = 3424.0 / 4096.0;
+const PQ_C2: f32 = 2413.0 / 4096.0 * 32.0;
+const PQ_C3: f32 = 2392.0 / 4096.0 * 32.0;
+const PQ_REFERENCE_WHITE: f32 = 203.0 / 10000.0;
+
+const HLG_A: f32 = 0.17883277;
+const HLG_B: f32 = 0.28466892;
+const HLG_C: f32 = 0.559_910_7;
+/// Scene-linear value of HLG reference white (75% signal)
+const HLG_REFERENCE_WHITE: f32 = 0.26496256;
+
+impl TransferFunction {
+    pub fn decode(&self, v: f32) -> f32 {
+        match self {
+            TransferFunction::Linear => v,
+            TransferFunction::Srgb => crate::utils::srgb_to_linear(v),
+            TransferFunction::Rec709 => {
+                if v < 0.081 {
+                    v / 4.5
+                } else {
+                    ((v + 0.099) / 1.099).powf(1.0 / 0.45)
+                }
+            }
+            TransferFunction::Pq => {
+                let p = v.max(0.0).powf(1.0 / PQ_M2);
+                let y = ((p - PQ_C1).max(0.0) / (PQ_C2 - PQ_C3 * p)).powf(1.0 / PQ_M1);
+                y / PQ_REFERENCE_WHITE
+            }
+            TransferFunction::Hlg => {
+                let e = if v <= 0.5 {
+                    v * v / 3.0
+                } else {
+                    (((v - HLG_C) / HLG_A).exp() + HLG_B) / 12.0
+                };
+                e / HLG_REFERENCE_WHITE
+            }
+        }
+    }
+
+    pub fn encode(&self, v: f32) -> f32 {
+        match self {
+            TransferFunction::Linear => v,
+            TransferFunction::Srgb => crate::utils::linear_to_srgb(v),
+            TransferFunction::Rec709 => {
+                if v < 0.018 {
+                    v * 4.5
+                } else {
+                    1.099 * v.powf(0.45) - 0.099
+                }
+            }
+            TransferFunction::Pq => {
+                let y = (v * PQ_REFERENCE_WHITE).clamp(0.0, 1.0).powf(PQ_M1);
+                ((PQ_C1 + PQ_C2 * y) / (1.0 + PQ_C3 * y)).powf(PQ_M2)
+            }
+            TransferFunction::Hlg => {
+                let e = (v * HLG_REFERENCE_WHITE).max(0.0);
+                if e <= 1.0 / 12.0 {
+                    (3.0 * e).sqrt()
+                } else {
+                    HLG_A * (12.0 * e - HLG_B).ln() + HLG_C
+                }
+            }
+        }
+    }
+}
+
+/// Supported colour spaces: primaries, white point and encoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColorSpace {
+    LinearSrgb,
+    Srgb,
+    Rec709,
+    Rec2020,
+    Rec2100Pq,
+    Rec2100Hlg,
+    DisplayP3,
+    AcesCg,
+}
+
+pub const COLOR_SPACE_NAMES: &[&str] = &[
+    "linear_srgb",
+    "srgb",
+    "rec709",
+    "rec2020",
+    "rec2100_pq",
+    "rec2100_hlg",
+    "display_p3",
+    "acescg",
+];
+
+const D65: (f32, f32) = (0.3127, 0.3290);
+const ACES_WHITE: (f32, f32) = (0.32168, 0.33767);
+
+impl ColorSpace {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "linear_srgb" | "linear" | "linear_rec709" => Some(ColorSpace::LinearSrgb),
+            "srgb" => Some(ColorSpace::Srgb),
+            "rec709" | "bt709" => Some(ColorSpace::Rec709),
+            "rec2020" | "bt2020" => Some(ColorSpace::Rec2020),
+            "rec2100_pq" | "pq" | "hdr10" => Some(ColorSpace::Rec2100Pq),
+            "rec2100_hlg" | "hlg" => Some(ColorSpace::Rec2100Hlg),
+            "display_p3" | "p3" => Some(ColorSpace::DisplayP3),
+            "acescg" | "aces_cg" => Some(ColorSpace::AcesCg),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ColorSpace::LinearSrgb => "linear_srgb",
+            ColorSpace::Srgb => "srgb",
+            ColorSpace::Rec709 => "rec709",
+            ColorSpace::Rec2020 => "rec2020",
+            ColorSpace::Rec2100Pq => "rec2100_pq",
+            ColorSpace::Rec2100Hlg => "rec2100_hlg",
+            ColorSpace::DisplayP3 => "display_p3",
+            ColorSpace::AcesCg => "acescg",
+        }
+    }
+
+    pub fn transfer(&self) -> TransferFunction {
+        match self {
+            ColorSpace::LinearSrgb | ColorSpace::AcesCg => TransferFunction::Linear,
+            ColorSpace::Srgb | ColorSpace::DisplayP3 => TransferFunction::Srgb,
+            ColorSpace::Rec709 | ColorSpace::Rec2020 => TransferFunction::Rec709,
+            ColorSpace::Rec2100Pq => TransferFunction::Pq,
+            ColorSpace::Rec2100Hlg => TransferFunction::Hlg,
+        }
+    }
+
+    pub fn is_hdr(&self) -> bool {
+        matches!(self, ColorSpace::Rec2100Pq | ColorSpace::Rec2100Hlg)
+    }
+
+    /// Red, green and blue primaries plus white point as xy chromaticities
+    fn chromaticities(&self) -> [(f32, f32); 4] {
+        match self {
+            ColorSpace::LinearSrgb | ColorSpace::Srgb | ColorSpace::Rec709 => {
+                [(0.64, 0.33), (0.30, 0.60), (0.15, 0.06), D65]
+            }
+            ColorSpace::Rec2020 | ColorSpace::Rec2100Pq | ColorSpace::Rec2100Hlg => {
+                [(0.708, 0.292), (0.170, 0.797), (0.131, 0.046), D65]
+            }
+            ColorSpace::DisplayP3 => [(0.680, 0.320), (0.265, 0.690), (0.150, 0.060), D65],
+            ColorSpace::AcesCg => [(0.713, 0.293), (0.165, 0.830), (0.128, 0.044), ACES_WHITE],
+        }
+    }
+
+    fn white_xyz(&self) -> Vector3<f32> {
+        xy_to_xyz(self.chromaticities()[3])
+    }
+
+    /// Linear RGB to CIE XYZ
+    pub fn to_xyz_matrix(&self) -> Matrix3<f32> {
+        let [r, g, b, _] = self.chromaticities();
+        let (r, g, b) = (xy_to_xyz(r), xy_to_xyz(g), xy_to_xyz(b));
+        let primaries = Matrix3::from_columns(&[r, g, b]);
+        let scale = primaries
+            .try_inverse()
+            .expect("colour primaries are linearly independent")
+            * self.white_xyz();
+        primaries * Matrix3::from_diagonal(&scale)
+    }
+}
+
+fn xy_to_xyz((x, y): (f32, f32)) -> Vector3<f32> {
+    Vector3::new(x / y, 1.0, (1.0 - x - y) / y)
+}
+
+/// Bradford chromatic adaptation between two white points (XYZ)
+fn bradford(src_white: Vector3<f32>, dst_white: Vector3<f32>) -> Matrix3<f32> {
+    let m = Matrix3::new(
+        0.8951, 0.2664, -0.1614, -0.7502, 1.7135, 0.0367, 0.0389, -0.0685, 1.0296,
+    );
+    let m_inv = m.try_inverse().expect("Bradford matrix is invertible");
+    let src = m * src_white;
+    let dst = m * dst_white;
+    let gain = Matrix3::from_diagonal(&Vector3::new(dst.x / src.x, dst.y / src.y, dst.z / src.z));
+    m_inv * gain * m
+}
+
+/// How out-of-range linear values are brought into an SDR output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ToneMapping {
+    /// Values above 1.0 are clipped
+    Clip,
+    /// Extended Reinhard on luminance, rolling highlights off up to `HDR_PEAK`
+    Reinhard,
+}
+
+impl ToneMapping {
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "reinhard" => ToneMapping::Reinhard,
+            _ => ToneMapping::Clip,
+        }
+    }
+}
+
+/// Brightest linear value (relative to reference white) mapped to SDR white
+const HDR_PEAK: f32 = 4.9;
+
+fn tone_map(rgb: [f32; 3], mode: ToneMapping) -> [f32; 3] {
+    match mode {
+        ToneMapping::Clip => rgb,
+        ToneMapping::Reinhard => {
+            let l = 0.2126 * rgb[0] + 0.7152 * rgb[1] + 0.0722 * rgb[2];
+            if l <= 0.0 {
+                return rgb;
+            }
+            let mapped = l * (1.0 + l / (HDR_PEAK * HDR_PEAK)) / (1.0 + l);
+            rgb.map(|v| v * mapped / l)
+        }
+    }
+}
+
+/// Conversion between two colour spaces: decode, matrix (with white point
+/// adaptation), optional tone mapping, encode
+#[derive(Debug, Clone)]
+pub struct ColorTransform {
+    pub src: ColorSpace,
+    pub dst: ColorSpace,
+    pub matrix: Matrix3<f32>,
+    pub tone_mapping: ToneMapping,
+}
+
+impl ColorTransform {
+    pub fn new(src: ColorSpace, dst: ColorSpace) -> Self {
+        let to_xyz = src.to_xyz_matrix();
+        let from_xyz = dst
+            .to_xyz_matrix()
+            .try_inverse()
+            .expect("colour space matrix is invertible");
+        let adapt = bradford(src.white_xyz(), dst.white_xyz());
+        Self {
+            src,
+            dst,
+            matrix: from_xyz * adapt * to_xyz,
+            tone_mapping: ToneMapping::Clip,
+        }
+    }
+
+    pub fn with_tone_mapping(mut self, tone_mapping: ToneMapping) -> Self {
+        self.tone_mapping = tone_mapping;
+        self
+    }
+
+    /// Linear `src` RGB to linear `dst` RGB
+    pub fn convert_linear(&self, rgb: [f32; 3]) -> [f32; 3] {
+        let v = self.matrix * Vector3::new(rgb[0], rgb[1], rgb[2]);
+        let out = [v.x, v.y, v.z];
+        if self.dst.is_hdr() {
+            out
+        } else {
+            tone_map(out, self.tone_mapping)
+        }
+    }
+
+    /// Encoded `src` values to encoded `dst` values
+    pub fn convert(&self, rgb: [f32; 3]) -> [f32; 3] {
+        let decode = self.src.transfer();
+        let encode = self.dst.transfer();
+        self.convert_linear(rgb.map(|v| decode.decode(v)))
+            .map(|v| encode.encode(v))
+    }
+
+    /// Convert an RGBA frame in place; alpha is untouched
+    pub fn apply_frame<P: Pixel>(&self, frame: &mut [P], width: u32, height: u32) {
+        debug_assert_eq!(frame.len(), (width * height * 4) as usize);
+        process_f32(frame, |frame| {
+            frame.par_chunks_mut(4).for_each(|px| {
+                let out = self.convert([px[0], px[1], px[2]]);
+                px[..3].copy_from_slice(&out);
+            });
+        });
+    }
+}
+
+pub(crate) fn parse_space(name: &str) -> PyResult<ColorSpace> {
+    ColorSpace::from_name(name).ok_or_else(|| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Unknown color space '{}'. Use: {}",
+            name,
+            COLOR_SPACE_NAMES.join(", ")
+        ))
+    })
+}
+
+/// Project colour management: the space compositing happens in, the space a
+/// render is delivered in, and per-asset input spaces
+#[pyclass]
+#[derive(Clone)]
+pub struct ColorConfig {
+    #[pyo3(get)]
+    pub working_space: String,
+    #[pyo3(get)]
+    pub output_space: String,
+    #[pyo3(get, set)]
+    pub tone_mapping: String, // "clip", "reinhard" (HDR into SDR outputs)
+    pub input_spaces: HashMap<String, String>,
+}
+
+#[pymethods]
+impl ColorConfig {
+    #[new]
+    fn new() -> Self {
+        Self {
+            working_space: "linear_srgb".to_string(),
+            output_space: "srgb".to_string(),
+            tone_mapping: "reinhard".to_string(),
+            input_spaces: HashMap::new(),
+        }
+    }
+
+    fn set_working_space(&mut self, space: &str) -> PyResult<()> {
+        self.working_space = parse_space(space)?.name().to_string();
+        Ok(())
+    }
+
+    fn set_output_space(&mut self, space: &str) -> PyResult<()> {
+        self.output_space = parse_space(space)?.name().to_string();
+        Ok(())
+    }
+
+    /// Override the input space of an asset (by path)
+    fn set_input_space(&mut self, asset: String, space: &str) -> PyResult<()> {
+        let space = parse_space(space)?;
+        self.input_spaces.insert(asset, space.name().to_string());
+        Ok(())
+    }
+
+    /// Input space of an asset: an explicit override, else a guess from the
+    /// file extension (float formats are linear, everything else sRGB)
+    fn input_space_for(&self, asset: &str) -> String {
+        if let Some(space) = self.input_spaces.get(asset) {
+            return space.clone();
+        }
+        let ext = std::path::Path::new(asset)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        match ext.as_deref() {
+            Some("exr") | Some("hdr") => "linear_srgb",
+            Some("mp4") | Some("mov") | Some("mxf") => "rec709",
+            _ => "srgb",
+        }
+        .to_string()
+    }
+}
+
+impl Default for ColorConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ColorConfig {
+    pub fn working(&self) -> ColorSpace {
+        ColorSpace::from_name(&self.working_space).unwrap_or(ColorSpace::LinearSrgb)
+    }
+
+    pub fn output(&self) -> ColorSpace {
+        ColorSpace::from_name(&self.output_space).unwrap_or(ColorSpace::Srgb)
+    }
+
+    /// Transform from an asset's space into the working space. `declared`
+    /// is the space set on the node using the asset, which wins over the
+    /// config's own overrides and the extension guess.
+    pub fn input_transform(&self, asset: &str, declared: Option<&str>) -> ColorTransform {
+        let name = declared.map_or_else(|| self.input_space_for(asset), str::to_string);
+        let src = ColorSpace::from_name(&name).unwrap_or(ColorSpace::Srgb);
+        ColorTransform::new(src, self.working())
+    }
+
+    /// Transform from the working space into the delivery space
+    pub fn output_transform(&self) -> ColorTransform {
+        ColorTransform::new(self.working(), self.output())
+            .with_tone_mapping(ToneMapping::from_name(&self.tone_mapping))
+    }
+}
+
+/// Convert one colour between named spaces
+#[pyfunction]
+pub fn convert_color(
+    r: f32,
+    g: f32,
+    b: f32,
+    from_space: &str,
+    to_space: &str,
+) -> PyResult<(f32, f32, f32)> {
+    let transform = ColorTransform::new(parse_space(from_space)?, parse_space(to_space)?);
+    let out = transform.convert([r, g, b]);
+    Ok((out[0], out[1], out[2]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: [f32; 3], b: [f32; 3], eps: f32) {
+        for (x, y) in a.iter().zip(b) {
+            assert!((x - y).abs() < eps, "{:?} != {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn transfer_functions_round_trip() {
+        for transfer in [
+            TransferFunction::Srgb,
+            TransferFunction::Rec709,
+            TransferFunction::Pq,
+            TransferFunction::Hlg,
+        ] {
+            for v in [0.0, 0.02, 0.18, 0.5, 1.0, 2.5] {
+                let back = transfer.decode(transfer.encode(v));
+                assert!((back - v).abs() < 1e-3, "{:?} {} -> {}", transfer, v, back);
+            }
+        }
+    }
+
+    #[test]
+    fn white_stays_white_across_gamuts() {
+        for dst in [
+            ColorSpace::Rec2020,
+            ColorSpace::DisplayP3,
+            ColorSpace::AcesCg,
+        ] {
+            let out = ColorTransform::new(ColorSpace::LinearSrgb, dst).convert_linear([1.0; 3]);
+            assert_close(out, [1.0; 3], 1e-4);
+        }
+    }
+
+    #[test]
+    fn rec709_red_is_inside_rec2020() {
+        let out = ColorTransform::new(ColorSpace::LinearSrgb, ColorSpace::Rec2020)
+            .convert_linear([1.0, 0.0, 0.0]);
+        // Published BT.709 -> BT.2020 matrix, first column
+        assert_close(out, [0.6274, 0.0691, 0.0164], 1e-3);
+    }
+
+    #[test]
+    fn declared_space_wins_over_guess() {
+        let mut config = ColorConfig::new();
+        assert_eq!(
+            config.input_transform("plate.exr", None).src,
+            ColorSpace::LinearSrgb
+        );
+        config
+            .set_input_space("plate.exr".to_string(), "acescg")
+            .unwrap();
+        assert_eq!(
+            config.input_transform("plate.exr", None).src,
+            ColorSpace::AcesCg
+        );
+        let declared = config.input_transform("plate.exr", Some("srgb"));
+        assert_eq!(declared.src, ColorSpace::Srgb);
+        assert_eq!(declared.dst, config.working());
+    }
+}
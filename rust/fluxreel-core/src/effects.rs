@@ -1,4 +1,13 @@
+use crate::animation::PropertyTracks;
+use crate::color_management::ColorSpace;
+use crate::frame::{process_f32, to_f32_vec, Frame, Pixel};
+use crate::geometry::Homography;
+use crate::motion_tracking::PlanarTracker;
+use crate::sampling::{sample_rgba, warp_frame, EdgeMode, Interpolation};
+use crate::utils::{deg_to_rad, hsl_to_rgb_f32, normalize_angle, rgb_to_hsl_f32, smoothstep};
+use nalgebra::Point2;
 use pyo3::prelude::*;
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 
 #[pyclass]
@@ -16,6 +25,94 @@ impl BlurEffect {
     }
 }
 
+impl BlurEffect {
+    /// Gaussian blur in linear light on premultiplied colour, so highlights
+    /// keep their energy and transparent edges do not darken. `radius` covers
+    /// three standard deviations.
+    pub fn apply<P: Pixel>(&self, frame: &mut [P], width: u32, height: u32, space: ColorSpace) {
+        debug_assert_eq!(frame.len(), (width * height * 4) as usize);
+        if self.radius <= 0.0 || width == 0 || height == 0 {
+            return;
+        }
+        process_f32(frame, |frame| {
+            let kernel = gaussian_kernel(self.radius / 3.0);
+            let transfer = space.transfer();
+            let (w, h) = (width as usize, height as usize);
+
+            let mut linear: Vec<[f32; 4]> = frame
+                .chunks(4)
+                .map(|px| {
+                    let a = px[3];
+                    [
+                        transfer.decode(px[0]) * a,
+                        transfer.decode(px[1]) * a,
+                        transfer.decode(px[2]) * a,
+                        a,
+                    ]
+                })
+                .collect();
+
+            let mut horizontal = vec![[0.0f32; 4]; w * h];
+            horizontal
+                .par_chunks_mut(w)
+                .zip(linear.par_chunks(w))
+                .for_each(|(out, row)| {
+                    for (x, o) in out.iter_mut().enumerate() {
+                        *o = convolve(&kernel, |dx| {
+                            row[(x as i32 + dx).clamp(0, w as i32 - 1) as usize]
+                        });
+                    }
+                });
+            linear.par_chunks_mut(w).enumerate().for_each(|(y, out)| {
+                for (x, o) in out.iter_mut().enumerate() {
+                    *o = convolve(&kernel, |dy| {
+                        horizontal[(y as i32 + dy).clamp(0, h as i32 - 1) as usize * w + x]
+                    });
+                }
+            });
+
+            frame
+                .par_chunks_mut(4)
+                .zip(linear.par_iter())
+                .for_each(|(px, p)| {
+                    let a = p[3];
+                    if a <= 0.0 {
+                        px.fill(0.0);
+                        return;
+                    }
+                    for c in 0..3 {
+                        px[c] = transfer.encode(p[c] / a);
+                    }
+                    px[3] = a;
+                });
+        });
+    }
+}
+
+/// Normalised Gaussian weights for offsets `-r..=r`, `r = ceil(3 sigma)`
+fn gaussian_kernel(sigma: f32) -> Vec<f32> {
+    let sigma = sigma.max(0.01);
+    let r = (sigma * 3.0).ceil() as i32;
+    let mut kernel: Vec<f32> = (-r..=r)
+        .map(|i| (-(i * i) as f32 / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let sum: f32 = kernel.iter().sum();
+    kernel.iter_mut().for_each(|k| *k /= sum);
+    kernel
+}
+
+fn convolve<F: Fn(i32) -> [f32; 4]>(kernel: &[f32], fetch: F) -> [f32; 4] {
+    let r = (kernel.len() / 2) as i32;
+    let mut out = [0.0; 4];
+    for (i, k) in kernel.iter().enumerate() {
+        let p = fetch(i as i32 - r);
+        for c in 0..4 {
+            out[c] += p[c] * k;
+        }
+    }
+    out
+}
+
 #[pyclass]
 #[derive(Clone)]
 pub struct GlowEffect {
@@ -68,19 +165,145 @@ impl ShadowEffect {
     }
 }
 
+pub(crate) fn check_property(effect: &str, properties: &[&str], property: &str) -> PyResult<()> {
+    if properties.contains(&property) {
+        Ok(())
+    } else {
+        Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Unknown {} property '{}'",
+            effect, property
+        )))
+    }
+}
+
+/// Scalar effect parameter that can be keyframed
+trait AnimatedValue {
+    fn to_f32(&self) -> f32;
+    fn from_f32(value: f32) -> Self;
+}
+
+impl AnimatedValue for f32 {
+    fn to_f32(&self) -> f32 {
+        *self
+    }
+
+    fn from_f32(value: f32) -> Self {
+        value
+    }
+}
+
+impl AnimatedValue for u32 {
+    fn to_f32(&self) -> f32 {
+        *self as f32
+    }
+
+    fn from_f32(value: f32) -> Self {
+        value.round().max(0.0) as u32
+    }
+}
+
+/// Effect with keyframed parameters
+pub trait AnimatedEffect: Clone {
+    /// Effect name used in error messages
+    const NAME: &'static str;
+    /// Parameters that accept keyframes
+    const PROPERTIES: &'static [&'static str];
+
+    fn keyframes_mut(&mut self) -> &mut PropertyTracks;
+
+    /// Replace every animated parameter with its value at `time` and drop
+    /// the keyframes
+    fn bake(&mut self, time: f32);
+
+    fn add_keyframe(
+        &mut self,
+        property: &str,
+        time: f32,
+        value: f32,
+        easing: &str,
+    ) -> PyResult<()> {
+        check_property(Self::NAME, Self::PROPERTIES, property)?;
+        self.keyframes_mut()
+            .add_keyframe(property, time, value, easing);
+        Ok(())
+    }
+
+    /// Snapshot of the effect with every animated property evaluated at `time`
+    fn at_time(&self, time: f32) -> Self {
+        let mut effect = self.clone();
+        effect.bake(time);
+        effect
+    }
+}
+
+/// Implement [`AnimatedEffect`] for an effect whose listed fields are its
+/// animated parameters, keyed by field name, and give the Python class its
+/// `add_keyframe` and `at_time` methods
+macro_rules! animated_effect {
+    ($effect:ty, $name:literal, [$($field:ident),+ $(,)?]) => {
+        impl AnimatedEffect for $effect {
+            const NAME: &'static str = $name;
+            const PROPERTIES: &'static [&'static str] = &[$(stringify!($field)),+];
+
+            fn keyframes_mut(&mut self) -> &mut PropertyTracks {
+                &mut self.keyframes
+            }
+
+            fn bake(&mut self, time: f32) {
+                let keyframes = std::mem::take(&mut self.keyframes);
+                $(
+                    self.$field = AnimatedValue::from_f32(keyframes.value_at(
+                        stringify!($field),
+                        time,
+                        AnimatedValue::to_f32(&self.$field),
+                    ));
+                )+
+            }
+        }
+
+        #[pymethods]
+        impl $effect {
+            #[pyo3(name = "add_keyframe")]
+            fn py_add_keyframe(
+                &mut self,
+                property: &str,
+                time: f32,
+                value: f32,
+                easing: String,
+            ) -> PyResult<()> {
+                AnimatedEffect::add_keyframe(self, property, time, value, &easing)
+            }
+
+            /// Snapshot of the effect with every animated property
+            /// evaluated at `time`
+            #[pyo3(name = "at_time")]
+            fn py_at_time(&self, time: f32) -> Self {
+                AnimatedEffect::at_time(self, time)
+            }
+        }
+    };
+}
+
 #[pyclass]
 #[derive(Clone)]
 pub struct ColorAdjustEffect {
     #[pyo3(get, set)]
-    pub brightness: f32,
+    pub brightness: f32, // -1.0 to 1.0 (0.0 = unchanged)
     #[pyo3(get, set)]
-    pub contrast: f32,
+    pub contrast: f32, // 0.0 to 2.0 (1.0 = unchanged)
     #[pyo3(get, set)]
-    pub saturation: f32,
+    pub saturation: f32, // 0.0 to 2.0 (1.0 = unchanged, 0.0 = grayscale)
     #[pyo3(get, set)]
-    pub hue: f32,
+    pub hue: f32, // -180.0 to 180.0 degrees
+    pub keyframes: PropertyTracks,
 }
 
+animated_effect!(
+    ColorAdjustEffect,
+    "color adjust",
+    [brightness, contrast, saturation, hue]
+);
+
 #[pymethods]
 impl ColorAdjustEffect {
     #[new]
@@ -90,10 +313,43 @@ impl ColorAdjustEffect {
             contrast: 1.0,
             saturation: 1.0,
             hue: 0.0,
+            keyframes: PropertyTracks::new(),
         }
     }
 }
 
+impl ColorAdjustEffect {
+    /// Apply the adjustment in place to an RGBA frame at the given time.
+    ///
+    /// Brightness and contrast work on the gamma-encoded (perceptual) values,
+    /// hue and saturation are applied afterwards in HSL. Alpha is untouched.
+    pub fn apply<P: Pixel>(&self, frame: &mut [P], width: u32, height: u32, time: f32) {
+        debug_assert_eq!(frame.len(), (width * height * 4) as usize);
+        process_f32(frame, |frame| {
+            let params = self.at_time(time);
+            let adjust_hsl = params.hue != 0.0 || params.saturation != 1.0;
+
+            frame.par_chunks_mut(4).for_each(|px| {
+                let mut rgb = [0.0f32; 3];
+                for c in 0..3 {
+                    let v = px[c] + params.brightness;
+                    rgb[c] = ((v - 0.5) * params.contrast + 0.5).max(0.0);
+                }
+
+                if adjust_hsl {
+                    let (h, s, l) = rgb_to_hsl_f32(rgb[0], rgb[1], rgb[2]);
+                    let h = normalize_angle(h + params.hue);
+                    let s = (s * params.saturation).clamp(0.0, 1.0);
+                    let (r, g, b) = hsl_to_rgb_f32(h, s, l);
+                    rgb = [r, g, b];
+                }
+
+                px[..3].copy_from_slice(&rgb);
+            });
+        });
+    }
+}
+
 #[pyclass]
 #[derive(Clone)]
 pub struct ChromaKeyEffect {
@@ -133,3 +389,1225 @@ impl NoiseEffect {
         Self { amount, seed: 0 }
     }
 }
+
+/// Channel of a displacement map that drives one axis
+fn map_channel_value(px: [f32; 4], channel: &str) -> f32 {
+    match channel {
+        "red" => px[0],
+        "green" => px[1],
+        "blue" => px[2],
+        "alpha" => px[3],
+        _ => luma(px[0], px[1], px[2]),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DisplacementMap {
+    pub pixels: Vec<f32>,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Offsets pixels by the values of another layer or image.
+///
+/// A map value of 0.5 leaves a pixel in place; 0.0 and 1.0 shift it by the
+/// full negative or positive scale (in pixels).
+#[pyclass]
+#[derive(Clone)]
+pub struct DisplacementEffect {
+    #[pyo3(get, set)]
+    pub horizontal_scale: f32, // pixels
+    #[pyo3(get, set)]
+    pub vertical_scale: f32, // pixels
+    #[pyo3(get, set)]
+    pub horizontal_channel: String, // "red", "green", "blue", "alpha", "luma"
+    #[pyo3(get, set)]
+    pub vertical_channel: String,
+    #[pyo3(get, set)]
+    pub map_path: Option<String>,
+    #[pyo3(get, set)]
+    pub edge_mode: String, // "clamp", "wrap", "mirror", "transparent"
+    #[pyo3(get, set)]
+    pub interpolation: String, // "nearest", "bilinear", "bicubic"
+    pub map: Option<DisplacementMap>,
+    pub keyframes: PropertyTracks,
+}
+
+animated_effect!(
+    DisplacementEffect,
+    "displacement",
+    [horizontal_scale, vertical_scale]
+);
+
+#[pymethods]
+impl DisplacementEffect {
+    #[new]
+    fn new(horizontal_scale: f32, vertical_scale: f32) -> Self {
+        Self {
+            horizontal_scale,
+            vertical_scale,
+            horizontal_channel: "red".to_string(),
+            vertical_channel: "green".to_string(),
+            map_path: None,
+            edge_mode: "clamp".to_string(),
+            interpolation: "bilinear".to_string(),
+            map: None,
+            keyframes: PropertyTracks::new(),
+        }
+    }
+
+    fn load_map(&mut self, path: &str) -> PyResult<()> {
+        let image = image::open(path)
+            .map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
+                    "Failed to load displacement map '{}': {}",
+                    path, e
+                ))
+            })?
+            .to_rgba32f();
+        self.map = Some(DisplacementMap {
+            width: image.width(),
+            height: image.height(),
+            pixels: image.into_raw(),
+        });
+        self.map_path = Some(path.to_string());
+        Ok(())
+    }
+
+    /// Use the pixels of another layer (RGBA8) as the displacement map
+    fn set_map_layer(&mut self, pixels: Vec<u8>, width: u32, height: u32) -> PyResult<()> {
+        if pixels.len() != (width * height * 4) as usize {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "Map layer size does not match its dimensions",
+            ));
+        }
+        self.map = Some(DisplacementMap {
+            pixels: to_f32_vec(&pixels),
+            width,
+            height,
+        });
+        self.map_path = None;
+        Ok(())
+    }
+}
+
+impl DisplacementEffect {
+    /// Displace an RGBA frame using the loaded map; a no-op without one.
+    /// Maps of a different size are stretched over the frame.
+    pub fn apply<P: Pixel>(&self, frame: &mut [P], width: u32, height: u32, time: f32) {
+        process_f32(frame, |frame| {
+            let Some(map) = &self.map else {
+                return;
+            };
+            let params = self.at_time(time);
+            let edge = EdgeMode::from_name(&self.edge_mode);
+            let interpolation = Interpolation::from_name(&self.interpolation);
+            let sx = map.width as f32 / width as f32;
+            let sy = map.height as f32 / height as f32;
+
+            let out = warp_frame(frame, width, height, interpolation, edge, |x, y| {
+                let m = sample_rgba(
+                    &map.pixels,
+                    map.width,
+                    map.height,
+                    x * sx,
+                    y * sy,
+                    Interpolation::Bilinear,
+                    EdgeMode::Clamp,
+                );
+                let dx = (map_channel_value(m, &params.horizontal_channel) - 0.5) * 2.0;
+                let dy = (map_channel_value(m, &params.vertical_channel) - 0.5) * 2.0;
+                (
+                    x + dx * params.horizontal_scale,
+                    y + dy * params.vertical_scale,
+                )
+            });
+            frame.copy_from_slice(&out);
+        });
+    }
+}
+
+/// Radial barrel/pincushion distortion with optional chromatic aberration.
+///
+/// Radius is normalised so the frame corners sit at 1.0. Positive strength
+/// bows straight lines outwards (barrel), negative inwards (pincushion).
+#[pyclass]
+#[derive(Clone)]
+pub struct LensDistortionEffect {
+    #[pyo3(get, set)]
+    pub strength: f32, // -1.0 to 1.0
+    #[pyo3(get, set)]
+    pub chromatic_aberration: f32, // 0.0 to 1.0
+    #[pyo3(get, set)]
+    pub zoom: f32, // 1.0 = unchanged
+    #[pyo3(get, set)]
+    pub center_x: f32, // 0.0 to 1.0
+    #[pyo3(get, set)]
+    pub center_y: f32, // 0.0 to 1.0
+    #[pyo3(get, set)]
+    pub edge_mode: String,
+    #[pyo3(get, set)]
+    pub interpolation: String,
+    pub keyframes: PropertyTracks,
+}
+
+animated_effect!(
+    LensDistortionEffect,
+    "lens distortion",
+    [strength, chromatic_aberration, zoom, center_x, center_y]
+);
+
+#[pymethods]
+impl LensDistortionEffect {
+    #[new]
+    fn new(strength: f32) -> Self {
+        Self {
+            strength,
+            chromatic_aberration: 0.0,
+            zoom: 1.0,
+            center_x: 0.5,
+            center_y: 0.5,
+            edge_mode: "transparent".to_string(),
+            interpolation: "bilinear".to_string(),
+            keyframes: PropertyTracks::new(),
+        }
+    }
+}
+
+impl LensDistortionEffect {
+    pub fn apply<P: Pixel>(&self, frame: &mut [P], width: u32, height: u32, time: f32) {
+        debug_assert_eq!(frame.len(), (width * height * 4) as usize);
+        if width == 0 || height == 0 {
+            return;
+        }
+        process_f32(frame, |frame| {
+            let params = self.at_time(time);
+            let edge = EdgeMode::from_name(&self.edge_mode);
+            let interpolation = Interpolation::from_name(&self.interpolation);
+            let cx = params.center_x * width as f32;
+            let cy = params.center_y * height as f32;
+            let norm = (0.5 * (width as f32).hypot(height as f32)).max(1.0);
+            let zoom = params.zoom.max(0.01);
+            // Red and blue are pushed apart radially around the green channel
+            let fringe = params.chromatic_aberration * 0.1;
+            let ks = [
+                params.strength + fringe,
+                params.strength,
+                params.strength - fringe,
+            ];
+
+            let src = frame.to_vec();
+            frame
+                .par_chunks_mut((width * 4) as usize)
+                .enumerate()
+                .for_each(|(y, row)| {
+                    for x in 0..width as usize {
+                        let dx = (x as f32 + 0.5 - cx) / norm;
+                        let dy = (y as f32 + 0.5 - cy) / norm;
+                        let r2 = dx * dx + dy * dy;
+                        let mut px = [0.0f32; 4];
+                        for (c, k) in ks.iter().enumerate() {
+                            let f = (1.0 + k * r2) / zoom;
+                            let s = sample_rgba(
+                                &src,
+                                width,
+                                height,
+                                cx + dx * f * norm,
+                                cy + dy * f * norm,
+                                interpolation,
+                                edge,
+                            );
+                            px[c] = s[c];
+                            if c == 1 {
+                                px[3] = s[3];
+                            }
+                        }
+                        row[x * 4..x * 4 + 4].copy_from_slice(&px);
+                    }
+                });
+        });
+    }
+}
+
+/// Concentric sine ripple spreading from a centre point
+#[pyclass]
+#[derive(Clone)]
+pub struct RippleEffect {
+    #[pyo3(get, set)]
+    pub center_x: f32, // 0.0 to 1.0
+    #[pyo3(get, set)]
+    pub center_y: f32, // 0.0 to 1.0
+    #[pyo3(get, set)]
+    pub amplitude: f32, // pixels
+    #[pyo3(get, set)]
+    pub wavelength: f32, // pixels
+    #[pyo3(get, set)]
+    pub phase: f32, // degrees, animate to make the ripple travel
+    #[pyo3(get, set)]
+    pub decay: f32, // 0.0 = no falloff
+    #[pyo3(get, set)]
+    pub edge_mode: String,
+    #[pyo3(get, set)]
+    pub interpolation: String,
+    pub keyframes: PropertyTracks,
+}
+
+animated_effect!(
+    RippleEffect,
+    "ripple",
+    [center_x, center_y, amplitude, wavelength, phase, decay]
+);
+
+#[pymethods]
+impl RippleEffect {
+    #[new]
+    fn new(amplitude: f32, wavelength: f32) -> Self {
+        Self {
+            center_x: 0.5,
+            center_y: 0.5,
+            amplitude,
+            wavelength,
+            phase: 0.0,
+            decay: 0.0,
+            edge_mode: "mirror".to_string(),
+            interpolation: "bilinear".to_string(),
+            keyframes: PropertyTracks::new(),
+        }
+    }
+}
+
+impl RippleEffect {
+    pub fn apply<P: Pixel>(&self, frame: &mut [P], width: u32, height: u32, time: f32) {
+        process_f32(frame, |frame| {
+            let params = self.at_time(time);
+            let cx = params.center_x * width as f32;
+            let cy = params.center_y * height as f32;
+            let wavelength = params.wavelength.max(1.0);
+            let phase = deg_to_rad(params.phase);
+            let max_dim = width.max(height) as f32;
+
+            let out = warp_frame(
+                frame,
+                width,
+                height,
+                Interpolation::from_name(&self.interpolation),
+                EdgeMode::from_name(&self.edge_mode),
+                |x, y| {
+                    let dx = x - cx;
+                    let dy = y - cy;
+                    let r = (dx * dx + dy * dy).sqrt();
+                    if r < 1e-4 {
+                        return (x, y);
+                    }
+                    let falloff = (-params.decay * r / max_dim).exp();
+                    let d = params.amplitude
+                        * (std::f32::consts::TAU * r / wavelength - phase).sin()
+                        * falloff;
+                    (x + dx / r * d, y + dy / r * d)
+                },
+            );
+            frame.copy_from_slice(&out);
+        });
+    }
+}
+
+/// Parallel sine wave displacing pixels across its direction of travel
+#[pyclass]
+#[derive(Clone)]
+pub struct WaveEffect {
+    #[pyo3(get, set)]
+    pub amplitude: f32, // pixels
+    #[pyo3(get, set)]
+    pub wavelength: f32, // pixels
+    #[pyo3(get, set)]
+    pub angle: f32, // degrees, direction of travel (0 = left to right)
+    #[pyo3(get, set)]
+    pub phase: f32, // degrees
+    #[pyo3(get, set)]
+    pub edge_mode: String,
+    #[pyo3(get, set)]
+    pub interpolation: String,
+    pub keyframes: PropertyTracks,
+}
+
+animated_effect!(WaveEffect, "wave", [amplitude, wavelength, angle, phase]);
+
+#[pymethods]
+impl WaveEffect {
+    #[new]
+    fn new(amplitude: f32, wavelength: f32) -> Self {
+        Self {
+            amplitude,
+            wavelength,
+            angle: 0.0,
+            phase: 0.0,
+            edge_mode: "mirror".to_string(),
+            interpolation: "bilinear".to_string(),
+            keyframes: PropertyTracks::new(),
+        }
+    }
+}
+
+impl WaveEffect {
+    pub fn apply<P: Pixel>(&self, frame: &mut [P], width: u32, height: u32, time: f32) {
+        process_f32(frame, |frame| {
+            let params = self.at_time(time);
+            let (sin_a, cos_a) = deg_to_rad(params.angle).sin_cos();
+            let wavelength = params.wavelength.max(1.0);
+            let phase = deg_to_rad(params.phase);
+
+            let out = warp_frame(
+                frame,
+                width,
+                height,
+                Interpolation::from_name(&self.interpolation),
+                EdgeMode::from_name(&self.edge_mode),
+                |x, y| {
+                    let along = x * cos_a + y * sin_a;
+                    let d = params.amplitude
+                        * (std::f32::consts::TAU * along / wavelength + phase).sin();
+                    (x - sin_a * d, y + cos_a * d)
+                },
+            );
+            frame.copy_from_slice(&out);
+        });
+    }
+}
+
+/// Rotates pixels around a centre, strongest at the centre and fading to
+/// nothing at the radius
+#[pyclass]
+#[derive(Clone)]
+pub struct TwirlEffect {
+    #[pyo3(get, set)]
+    pub center_x: f32, // 0.0 to 1.0
+    #[pyo3(get, set)]
+    pub center_y: f32, // 0.0 to 1.0
+    #[pyo3(get, set)]
+    pub radius: f32, // fraction of the shorter frame side
+    #[pyo3(get, set)]
+    pub angle: f32, // degrees at the centre
+    #[pyo3(get, set)]
+    pub edge_mode: String,
+    #[pyo3(get, set)]
+    pub interpolation: String,
+    pub keyframes: PropertyTracks,
+}
+
+animated_effect!(TwirlEffect, "twirl", [center_x, center_y, radius, angle]);
+
+#[pymethods]
+impl TwirlEffect {
+    #[new]
+    fn new(angle: f32, radius: f32) -> Self {
+        Self {
+            center_x: 0.5,
+            center_y: 0.5,
+            radius,
+            angle,
+            edge_mode: "clamp".to_string(),
+            interpolation: "bilinear".to_string(),
+            keyframes: PropertyTracks::new(),
+        }
+    }
+}
+
+impl TwirlEffect {
+    pub fn apply<P: Pixel>(&self, frame: &mut [P], width: u32, height: u32, time: f32) {
+        process_f32(frame, |frame| {
+            let params = self.at_time(time);
+            let cx = params.center_x * width as f32;
+            let cy = params.center_y * height as f32;
+            let radius = (params.radius * width.min(height) as f32).max(1.0);
+            let angle = deg_to_rad(params.angle);
+
+            let out = warp_frame(
+                frame,
+                width,
+                height,
+                Interpolation::from_name(&self.interpolation),
+                EdgeMode::from_name(&self.edge_mode),
+                |x, y| {
+                    let dx = x - cx;
+                    let dy = y - cy;
+                    let r = (dx * dx + dy * dy).sqrt();
+                    if r >= radius {
+                        return (x, y);
+                    }
+                    let falloff = 1.0 - r / radius;
+                    let (sin_t, cos_t) = (-angle * falloff * falloff).sin_cos();
+                    (cx + dx * cos_t - dy * sin_t, cy + dx * sin_t + dy * cos_t)
+                },
+            );
+            frame.copy_from_slice(&out);
+        });
+    }
+}
+
+/// Magnifies (positive strength) or pinches (negative strength) a circular
+/// region
+#[pyclass]
+#[derive(Clone)]
+pub struct BulgeEffect {
+    #[pyo3(get, set)]
+    pub center_x: f32, // 0.0 to 1.0
+    #[pyo3(get, set)]
+    pub center_y: f32, // 0.0 to 1.0
+    #[pyo3(get, set)]
+    pub radius: f32, // fraction of the shorter frame side
+    #[pyo3(get, set)]
+    pub strength: f32, // -1.0 to 1.0
+    #[pyo3(get, set)]
+    pub edge_mode: String,
+    #[pyo3(get, set)]
+    pub interpolation: String,
+    pub keyframes: PropertyTracks,
+}
+
+animated_effect!(BulgeEffect, "bulge", [center_x, center_y, radius, strength]);
+
+#[pymethods]
+impl BulgeEffect {
+    #[new]
+    fn new(strength: f32, radius: f32) -> Self {
+        Self {
+            center_x: 0.5,
+            center_y: 0.5,
+            radius,
+            strength,
+            edge_mode: "clamp".to_string(),
+            interpolation: "bilinear".to_string(),
+            keyframes: PropertyTracks::new(),
+        }
+    }
+}
+
+impl BulgeEffect {
+    pub fn apply<P: Pixel>(&self, frame: &mut [P], width: u32, height: u32, time: f32) {
+        process_f32(frame, |frame| {
+            let params = self.at_time(time);
+            let cx = params.center_x * width as f32;
+            let cy = params.center_y * height as f32;
+            let radius = (params.radius * width.min(height) as f32).max(1.0);
+            let strength = params.strength.clamp(-1.0, 1.0);
+
+            let out = warp_frame(
+                frame,
+                width,
+                height,
+                Interpolation::from_name(&self.interpolation),
+                EdgeMode::from_name(&self.edge_mode),
+                |x, y| {
+                    let dx = x - cx;
+                    let dy = y - cy;
+                    let r = (dx * dx + dy * dy).sqrt();
+                    if r >= radius {
+                        return (x, y);
+                    }
+                    let t = 1.0 - r / radius;
+                    let scale = 1.0 - strength * t * t;
+                    (cx + dx * scale, cy + dy * scale)
+                },
+            );
+            frame.copy_from_slice(&out);
+        });
+    }
+}
+
+fn luma(r: f32, g: f32, b: f32) -> f32 {
+    0.2126 * r + 0.7152 * g + 0.0722 * b
+}
+
+/// Rec.709 luma of every pixel, normalised to 0-1
+fn luma_plane(frame: &[f32]) -> Vec<f32> {
+    frame
+        .par_chunks(4)
+        .map(|px| luma(px[0], px[1], px[2]))
+        .collect()
+}
+
+/// Replace each `block_size` square with its average colour.
+///
+/// Shared by `PixelateEffect` and the `pixelate` transition.
+pub fn pixelate_frame<P: Pixel>(frame: &mut [P], width: u32, height: u32, block_size: u32) {
+    debug_assert_eq!(frame.len(), (width * height * 4) as usize);
+    let block = block_size.max(1) as usize;
+    if block == 1 || width == 0 || height == 0 {
+        return;
+    }
+    let w = width as usize;
+    let stride = w * 4;
+
+    process_f32(frame, |frame| {
+        frame.par_chunks_mut(stride * block).for_each(|band| {
+            let rows = band.len() / stride;
+            for bx in (0..w).step_by(block) {
+                let cols = block.min(w - bx);
+                let mut sum = [0.0f32; 4];
+                for y in 0..rows {
+                    for x in bx..bx + cols {
+                        let i = y * stride + x * 4;
+                        for c in 0..4 {
+                            sum[c] += band[i + c];
+                        }
+                    }
+                }
+                let n = (rows * cols) as f32;
+                let avg = sum.map(|v| v / n);
+                for y in 0..rows {
+                    for x in bx..bx + cols {
+                        let i = y * stride + x * 4;
+                        band[i..i + 4].copy_from_slice(&avg);
+                    }
+                }
+            }
+        });
+    });
+}
+
+/// Projects a layer onto four output corners, e.g. to replace a phone
+/// screen or billboard. The corners can follow a `PlanarTracker`.
+#[pyclass]
+#[derive(Clone)]
+pub struct CornerPinEffect {
+    #[pyo3(get)]
+    pub corners: Vec<(f32, f32)>, // output px, clockwise from top-left
+    /// Corners per frame from a planar track; overrides `corners`
+    pub tracked: Vec<(u32, [Point2<f32>; 4])>,
+    #[pyo3(get, set)]
+    pub interpolation: String,
+}
+
+fn check_corners(corners: &[(f32, f32)]) -> PyResult<()> {
+    if corners.len() == 4 {
+        Ok(())
+    } else {
+        Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "A corner pin needs 4 corners, got {}",
+            corners.len()
+        )))
+    }
+}
+
+#[pymethods]
+impl CornerPinEffect {
+    #[new]
+    fn new(corners: Vec<(f32, f32)>) -> PyResult<Self> {
+        check_corners(&corners)?;
+        Ok(Self {
+            corners,
+            tracked: Vec::new(),
+            interpolation: "bilinear".to_string(),
+        })
+    }
+
+    fn set_corners(&mut self, corners: Vec<(f32, f32)>) -> PyResult<()> {
+        check_corners(&corners)?;
+        self.corners = corners;
+        Ok(())
+    }
+
+    /// Pin the layer to the tracked plane in every frame the tracker covers
+    fn follow_planar_track(&mut self, tracker: PlanarTracker) {
+        self.tracked = tracker
+            .tracks
+            .iter()
+            .filter_map(|t| tracker.corners_at(t.frame).map(|c| (t.frame, c)))
+            .collect();
+    }
+
+    fn clear_track(&mut self) {
+        self.tracked.clear();
+    }
+
+    fn corners_at_frame(&self, frame: u32) -> Vec<(f32, f32)> {
+        self.corners_at(frame).iter().map(|p| (p.x, p.y)).collect()
+    }
+
+    /// Pin an RGBA8 layer into a transparent `width` x `height` frame
+    #[pyo3(name = "render")]
+    fn py_render(
+        &self,
+        pixels: Vec<u8>,
+        layer_width: u32,
+        layer_height: u32,
+        width: u32,
+        height: u32,
+        frame: u32,
+    ) -> PyResult<Vec<u8>> {
+        let layer = Frame::from_raw(layer_width, layer_height, pixels)?;
+        Ok(self.render(&layer, width, height, frame).data)
+    }
+}
+
+impl CornerPinEffect {
+    /// Corners at `frame`; outside a planar track the nearest tracked frame
+    /// holds
+    pub fn corners_at(&self, frame: u32) -> [Point2<f32>; 4] {
+        if let Some((_, corners)) = self.tracked.iter().min_by_key(|(f, _)| f.abs_diff(frame)) {
+            return *corners;
+        }
+        let c = &self.corners;
+        [0, 1, 2, 3].map(|i| Point2::new(c[i].0, c[i].1))
+    }
+
+    /// Layer mapped onto the corners in a new transparent frame. A
+    /// degenerate quad or an empty layer renders nothing.
+    pub fn render<P: Pixel>(
+        &self,
+        layer: &Frame<P>,
+        width: u32,
+        height: u32,
+        frame: u32,
+    ) -> Frame<P> {
+        let mut out = Frame::new(width, height);
+        if width == 0 || height == 0 || layer.width == 0 || layer.height == 0 {
+            return out;
+        }
+        let inverse = Homography::from_rect(
+            layer.width as f32,
+            layer.height as f32,
+            &self.corners_at(frame),
+        )
+        .and_then(|h| h.inverse());
+        let Some(inverse) = inverse else {
+            return out;
+        };
+        let src = to_f32_vec(&layer.data);
+        let interpolation = Interpolation::from_name(&self.interpolation);
+        out.data
+            .par_chunks_mut((width * 4) as usize)
+            .enumerate()
+            .for_each(|(y, row)| {
+                for x in 0..width as usize {
+                    let Some(p) = inverse.apply(Point2::new(x as f32 + 0.5, y as f32 + 0.5)) else {
+                        continue;
+                    };
+                    let px = sample_rgba(
+                        &src,
+                        layer.width,
+                        layer.height,
+                        p.x,
+                        p.y,
+                        interpolation,
+                        EdgeMode::Transparent,
+                    );
+                    for (d, v) in row[x * 4..x * 4 + 4].iter_mut().zip(px) {
+                        *d = P::from_f32(v);
+                    }
+                }
+            });
+        out
+    }
+}
+
+/// Reduces each colour channel to a fixed number of levels
+#[pyclass]
+#[derive(Clone)]
+pub struct PosterizeEffect {
+    #[pyo3(get, set)]
+    pub levels: u32, // 2 to 256
+    pub keyframes: PropertyTracks,
+}
+
+animated_effect!(PosterizeEffect, "posterize", [levels]);
+
+#[pymethods]
+impl PosterizeEffect {
+    #[new]
+    fn new(levels: u32) -> Self {
+        Self {
+            levels,
+            keyframes: PropertyTracks::new(),
+        }
+    }
+}
+
+impl PosterizeEffect {
+    pub fn apply<P: Pixel>(&self, frame: &mut [P], width: u32, height: u32, time: f32) {
+        debug_assert_eq!(frame.len(), (width * height * 4) as usize);
+        process_f32(frame, |frame| {
+            let steps = (self.at_time(time).levels.clamp(2, 256) - 1) as f32;
+            frame.par_chunks_mut(4).for_each(|px| {
+                for v in px[..3].iter_mut() {
+                    *v = (v.clamp(0.0, 1.0) * steps).round() / steps;
+                }
+            });
+        });
+    }
+}
+
+/// Black and white split at a luma level, optionally with a soft ramp
+#[pyclass]
+#[derive(Clone)]
+pub struct ThresholdEffect {
+    #[pyo3(get, set)]
+    pub level: f32, // 0.0 to 1.0
+    #[pyo3(get, set)]
+    pub softness: f32, // 0.0 to 1.0, width of the ramp around level
+    pub keyframes: PropertyTracks,
+}
+
+animated_effect!(ThresholdEffect, "threshold", [level, softness]);
+
+#[pymethods]
+impl ThresholdEffect {
+    #[new]
+    fn new(level: f32) -> Self {
+        Self {
+            level,
+            softness: 0.0,
+            keyframes: PropertyTracks::new(),
+        }
+    }
+}
+
+impl ThresholdEffect {
+    pub fn apply<P: Pixel>(&self, frame: &mut [P], width: u32, height: u32, time: f32) {
+        debug_assert_eq!(frame.len(), (width * height * 4) as usize);
+        process_f32(frame, |frame| {
+            let params = self.at_time(time);
+            let half = params.softness.max(0.0) / 2.0;
+            frame.par_chunks_mut(4).for_each(|px| {
+                let y = luma(px[0], px[1], px[2]);
+                let v = if half > 0.0 {
+                    smoothstep(params.level - half, params.level + half, y)
+                } else if y >= params.level {
+                    1.0
+                } else {
+                    0.0
+                };
+                px[..3].fill(v);
+            });
+        });
+    }
+}
+
+/// Print-style halftone: black dots on white sized by the darkness of each
+/// cell of a rotated grid
+#[pyclass]
+#[derive(Clone)]
+pub struct HalftoneEffect {
+    #[pyo3(get, set)]
+    pub dot_size: f32, // grid cell size in pixels
+    #[pyo3(get, set)]
+    pub angle: f32, // degrees
+    pub keyframes: PropertyTracks,
+}
+
+animated_effect!(HalftoneEffect, "halftone", [dot_size, angle]);
+
+#[pymethods]
+impl HalftoneEffect {
+    #[new]
+    fn new(dot_size: f32) -> Self {
+        Self {
+            dot_size,
+            angle: 45.0,
+            keyframes: PropertyTracks::new(),
+        }
+    }
+}
+
+impl HalftoneEffect {
+    pub fn apply<P: Pixel>(&self, frame: &mut [P], width: u32, height: u32, time: f32) {
+        debug_assert_eq!(frame.len(), (width * height * 4) as usize);
+        if width == 0 || height == 0 {
+            return;
+        }
+        process_f32(frame, |frame| {
+            let params = self.at_time(time);
+            let cell = params.dot_size.max(2.0);
+            let (sin_a, cos_a) = deg_to_rad(params.angle).sin_cos();
+            let src = frame.to_vec();
+
+            frame
+                .par_chunks_mut((width * 4) as usize)
+                .enumerate()
+                .for_each(|(y, row)| {
+                    for x in 0..width as usize {
+                        let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+                        // Position in the rotated grid, then the centre of its cell
+                        let u = px * cos_a + py * sin_a;
+                        let v = -px * sin_a + py * cos_a;
+                        let cu = ((u / cell).floor() + 0.5) * cell;
+                        let cv = ((v / cell).floor() + 0.5) * cell;
+                        let cx = cu * cos_a - cv * sin_a;
+                        let cy = cu * sin_a + cv * cos_a;
+
+                        let s = sample_rgba(
+                            &src,
+                            width,
+                            height,
+                            cx,
+                            cy,
+                            Interpolation::Bilinear,
+                            EdgeMode::Clamp,
+                        );
+                        let darkness = 1.0 - luma(s[0], s[1], s[2]).min(1.0);
+                        // Full darkness covers the whole cell, corners included
+                        let radius = cell * std::f32::consts::FRAC_1_SQRT_2 * darkness.sqrt();
+                        let d = ((u - cu).powi(2) + (v - cv).powi(2)).sqrt();
+                        let ink = 1.0 - smoothstep(radius - 0.5, radius + 0.5, d);
+                        row[x * 4..x * 4 + 3].fill(1.0 - ink);
+                    }
+                });
+        });
+    }
+}
+
+/// Mosaic of flat colour blocks
+#[pyclass]
+#[derive(Clone)]
+pub struct PixelateEffect {
+    #[pyo3(get, set)]
+    pub block_size: f32, // pixels
+    pub keyframes: PropertyTracks,
+}
+
+animated_effect!(PixelateEffect, "pixelate", [block_size]);
+
+#[pymethods]
+impl PixelateEffect {
+    #[new]
+    fn new(block_size: f32) -> Self {
+        Self {
+            block_size,
+            keyframes: PropertyTracks::new(),
+        }
+    }
+}
+
+impl PixelateEffect {
+    pub fn apply<P: Pixel>(&self, frame: &mut [P], width: u32, height: u32, time: f32) {
+        let block = self.at_time(time).block_size.round().max(1.0) as u32;
+        pixelate_frame(frame, width, height, block);
+    }
+}
+
+/// Sobel edge detection: bright edges on black (or dark on white when
+/// inverted)
+#[pyclass]
+#[derive(Clone)]
+pub struct EdgeDetectEffect {
+    #[pyo3(get, set)]
+    pub strength: f32, // gain applied to the gradient magnitude
+    #[pyo3(get, set)]
+    pub invert: bool,
+    #[pyo3(get, set)]
+    pub mix: f32, // 0.0 = original, 1.0 = edges only
+    pub keyframes: PropertyTracks,
+}
+
+animated_effect!(EdgeDetectEffect, "edge detect", [strength, mix]);
+
+#[pymethods]
+impl EdgeDetectEffect {
+    #[new]
+    fn new(strength: f32) -> Self {
+        Self {
+            strength,
+            invert: false,
+            mix: 1.0,
+            keyframes: PropertyTracks::new(),
+        }
+    }
+}
+
+impl EdgeDetectEffect {
+    pub fn apply<P: Pixel>(&self, frame: &mut [P], width: u32, height: u32, time: f32) {
+        debug_assert_eq!(frame.len(), (width * height * 4) as usize);
+        if width == 0 || height == 0 {
+            return;
+        }
+        process_f32(frame, |frame| {
+            let params = self.at_time(time);
+            let mix = params.mix.clamp(0.0, 1.0);
+            let lum = luma_plane(frame);
+            let (w, h) = (width as i32, height as i32);
+            let at = |x: i32, y: i32| lum[(y.clamp(0, h - 1) * w + x.clamp(0, w - 1)) as usize];
+
+            frame
+                .par_chunks_mut((width * 4) as usize)
+                .enumerate()
+                .for_each(|(y, row)| {
+                    let y = y as i32;
+                    for x in 0..w {
+                        let gx = at(x + 1, y - 1) + 2.0 * at(x + 1, y) + at(x + 1, y + 1)
+                            - at(x - 1, y - 1)
+                            - 2.0 * at(x - 1, y)
+                            - at(x - 1, y + 1);
+                        let gy = at(x - 1, y + 1) + 2.0 * at(x, y + 1) + at(x + 1, y + 1)
+                            - at(x - 1, y - 1)
+                            - 2.0 * at(x, y - 1)
+                            - at(x + 1, y - 1);
+                        let mut edge =
+                            ((gx * gx + gy * gy).sqrt() * params.strength).clamp(0.0, 1.0);
+                        if params.invert {
+                            edge = 1.0 - edge;
+                        }
+                        let i = x as usize * 4;
+                        for v in row[i..i + 3].iter_mut() {
+                            *v += (edge - *v) * mix;
+                        }
+                    }
+                });
+        });
+    }
+}
+
+/// Relief look from the directional derivative of luma, lit from `angle`
+#[pyclass]
+#[derive(Clone)]
+pub struct EmbossEffect {
+    #[pyo3(get, set)]
+    pub strength: f32,
+    #[pyo3(get, set)]
+    pub angle: f32, // degrees, direction of the light
+    pub keyframes: PropertyTracks,
+}
+
+animated_effect!(EmbossEffect, "emboss", [strength, angle]);
+
+#[pymethods]
+impl EmbossEffect {
+    #[new]
+    fn new(strength: f32) -> Self {
+        Self {
+            strength,
+            angle: 135.0,
+            keyframes: PropertyTracks::new(),
+        }
+    }
+}
+
+impl EmbossEffect {
+    pub fn apply<P: Pixel>(&self, frame: &mut [P], width: u32, height: u32, time: f32) {
+        debug_assert_eq!(frame.len(), (width * height * 4) as usize);
+        if width == 0 || height == 0 {
+            return;
+        }
+        process_f32(frame, |frame| {
+            let params = self.at_time(time);
+            let (dy, dx) = deg_to_rad(params.angle).sin_cos();
+            let lum = luma_plane(frame);
+            let (w, h) = (width as i32, height as i32);
+            let at = |x: i32, y: i32| lum[(y.clamp(0, h - 1) * w + x.clamp(0, w - 1)) as usize];
+
+            frame
+                .par_chunks_mut((width * 4) as usize)
+                .enumerate()
+                .for_each(|(y, row)| {
+                    let y = y as i32;
+                    for x in 0..w {
+                        let gx = (at(x + 1, y) - at(x - 1, y)) / 2.0;
+                        let gy = (at(x, y + 1) - at(x, y - 1)) / 2.0;
+                        let relief = 0.5 + (gx * dx + gy * dy) * params.strength;
+                        let v = relief.max(0.0);
+                        row[x as usize * 4..x as usize * 4 + 3].fill(v);
+                    }
+                });
+        });
+    }
+}
+
+/// Darkens the frame towards its edges.
+///
+/// Distance is measured in frame-relative units so the falloff follows the
+/// frame's aspect ratio; 1.0 is a corner.
+#[pyclass]
+#[derive(Clone)]
+pub struct VignetteEffect {
+    #[pyo3(get, set)]
+    pub amount: f32, // 0.0 to 1.0
+    #[pyo3(get, set)]
+    pub radius: f32, // 0.0 to 1.0, where darkening starts
+    #[pyo3(get, set)]
+    pub softness: f32, // 0.0 to 1.0
+    pub keyframes: PropertyTracks,
+}
+
+animated_effect!(VignetteEffect, "vignette", [amount, radius, softness]);
+
+#[pymethods]
+impl VignetteEffect {
+    #[new]
+    fn new(amount: f32) -> Self {
+        Self {
+            amount,
+            radius: 0.5,
+            softness: 0.5,
+            keyframes: PropertyTracks::new(),
+        }
+    }
+}
+
+impl VignetteEffect {
+    pub fn apply<P: Pixel>(&self, frame: &mut [P], width: u32, height: u32, time: f32) {
+        debug_assert_eq!(frame.len(), (width * height * 4) as usize);
+        if width == 0 || height == 0 {
+            return;
+        }
+        process_f32(frame, |frame| {
+            let params = self.at_time(time);
+            let amount = params.amount.clamp(0.0, 1.0);
+            let outer = params.radius + params.softness.max(1e-3);
+
+            frame
+                .par_chunks_mut((width * 4) as usize)
+                .enumerate()
+                .for_each(|(y, row)| {
+                    let ny = ((y as f32 + 0.5) / height as f32 - 0.5) * 2.0;
+                    for x in 0..width as usize {
+                        let nx = ((x as f32 + 0.5) / width as f32 - 0.5) * 2.0;
+                        let d = ((nx * nx + ny * ny) / 2.0).sqrt();
+                        let k = 1.0 - amount * smoothstep(params.radius, outer, d);
+                        for v in row[x * 4..x * 4 + 3].iter_mut() {
+                            *v *= k;
+                        }
+                    }
+                });
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid_frame(rgba: [u8; 4], pixels: usize) -> Vec<u8> {
+        rgba.iter().copied().cycle().take(pixels * 4).collect()
+    }
+
+    #[test]
+    fn neutral_color_adjust_leaves_frame_unchanged() {
+        let mut frame = solid_frame([12, 128, 240, 200], 4);
+        let original = frame.clone();
+        ColorAdjustEffect::new().apply(&mut frame, 2, 2, 0.0);
+        assert_eq!(frame, original);
+    }
+
+    #[test]
+    fn corner_pin_places_layer_inside_quad() {
+        let layer = Frame::from_raw(4, 4, solid_frame([255, 0, 0, 255], 16)).unwrap();
+        let pin =
+            CornerPinEffect::new(vec![(2.0, 2.0), (6.0, 2.0), (7.0, 7.0), (2.0, 6.0)]).unwrap();
+        let out = pin.render(&layer, 8, 8, 0);
+        assert_eq!(out.pixel(4, 4), [1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(out.pixel(0, 0)[3], 0.0);
+        assert_eq!(out.pixel(7, 2)[3], 0.0);
+        assert!(CornerPinEffect::new(vec![(0.0, 0.0)]).is_err());
+        assert!(pin.render(&layer, 0, 8, 0).data.is_empty());
+        let empty: Frame<u8> = Frame::new(0, 4);
+        assert_eq!(pin.render(&empty, 8, 8, 0).pixel(4, 4)[3], 0.0);
+    }
+
+    #[test]
+    fn zero_saturation_produces_gray() {
+        let mut effect = ColorAdjustEffect::new();
+        effect.saturation = 0.0;
+        let mut frame = solid_frame([200, 40, 90, 255], 1);
+        effect.apply(&mut frame, 1, 1, 0.0);
+        assert_eq!(frame[0], frame[1]);
+        assert_eq!(frame[1], frame[2]);
+        assert_eq!(frame[3], 255);
+    }
+
+    #[test]
+    fn brightness_follows_keyframes() {
+        let mut effect = ColorAdjustEffect::new();
+        effect
+            .add_keyframe("brightness", 0.0, 0.0, "linear")
+            .unwrap();
+        effect
+            .add_keyframe("brightness", 1.0, 0.5, "linear")
+            .unwrap();
+        assert!((effect.at_time(0.5).brightness - 0.25).abs() < 1e-6);
+        assert!(effect.add_keyframe("gamma", 0.0, 1.0, "linear").is_err());
+        assert!(effect.at_time(0.5).keyframes.tracks.is_empty());
+
+        let mut frame = solid_frame([0, 0, 0, 255], 1);
+        effect.apply(&mut frame, 1, 1, 1.0);
+        assert_eq!(&frame[..3], &[128, 128, 128]);
+    }
+
+    #[test]
+    fn zero_strength_distortions_are_identity() {
+        let frame: Vec<u8> = (0..16 * 16 * 4).map(|i| (i * 7 % 256) as u8).collect();
+
+        let mut twirled = frame.clone();
+        TwirlEffect::new(0.0, 0.5).apply(&mut twirled, 16, 16, 0.0);
+        assert_eq!(twirled, frame);
+
+        let mut lensed = frame.clone();
+        LensDistortionEffect::new(0.0).apply(&mut lensed, 16, 16, 0.0);
+        assert_eq!(lensed, frame);
+    }
+
+    #[test]
+    fn distortions_accept_empty_frames() {
+        for (width, height) in [(0, 3), (3, 0)] {
+            let mut frame: Vec<u8> = Vec::new();
+            LensDistortionEffect::new(0.5).apply(&mut frame, width, height, 0.0);
+            TwirlEffect::new(90.0, 0.5).apply(&mut frame, width, height, 0.0);
+            BulgeEffect::new(0.5, 0.5).apply(&mut frame, width, height, 0.0);
+            WaveEffect::new(4.0, 16.0).apply(&mut frame, width, height, 0.0);
+            RippleEffect::new(4.0, 16.0).apply(&mut frame, width, height, 0.0);
+            assert!(frame.is_empty());
+        }
+    }
+
+    #[test]
+    fn edge_modes_resolve_out_of_bounds_samples() {
+        // 2x1 frame: red then blue
+        let frame = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0];
+        let sample = |x: f32, edge| sample_rgba(&frame, 2, 1, x, 0.5, Interpolation::Nearest, edge);
+        assert_eq!(sample(-0.5, EdgeMode::Clamp)[0], 1.0);
+        assert_eq!(sample(-0.5, EdgeMode::Wrap)[2], 1.0);
+        assert_eq!(sample(-0.5, EdgeMode::Mirror)[0], 1.0);
+        assert_eq!(sample(-0.5, EdgeMode::Transparent)[3], 0.0);
+    }
+
+    #[test]
+    fn pixelate_averages_blocks() {
+        // 2x1 frame averaged into a single block
+        let mut frame = vec![0u8, 0, 0, 255, 200, 100, 50, 255];
+        pixelate_frame(&mut frame, 2, 1, 2);
+        assert_eq!(frame, vec![100, 50, 25, 255, 100, 50, 25, 255]);
+    }
+
+    #[test]
+    fn stylize_effects_accept_empty_frames() {
+        for (width, height) in [(0, 3), (3, 0)] {
+            let mut frame: Vec<u8> = Vec::new();
+            HalftoneEffect::new(6.0).apply(&mut frame, width, height, 0.0);
+            EdgeDetectEffect::new(1.0).apply(&mut frame, width, height, 0.0);
+            EmbossEffect::new(1.0).apply(&mut frame, width, height, 0.0);
+            VignetteEffect::new(0.5).apply(&mut frame, width, height, 0.0);
+            PixelateEffect::new(4.0).apply(&mut frame, width, height, 0.0);
+            pixelate_frame(&mut frame, width, height, 4);
+            assert!(frame.is_empty());
+        }
+    }
+
+    #[test]
+    fn posterize_keyframes_snap_levels() {
+        let mut effect = PosterizeEffect::new(256);
+        effect.add_keyframe("levels", 1.0, 2.0, "linear").unwrap();
+        let mut frame = vec![100u8, 140, 250, 255];
+        effect.apply(&mut frame, 1, 1, 1.0);
+        assert_eq!(frame, vec![0, 255, 255, 255]);
+    }
+
+    #[test]
+    fn float_frames_keep_hdr_highlights() {
+        let mut frame = vec![4.0f32, 2.0, 0.5, 1.0];
+        VignetteEffect::new(0.0).apply(&mut frame, 1, 1, 0.0);
+        assert_eq!(frame, vec![4.0, 2.0, 0.5, 1.0]);
+
+        let mut effect = ColorAdjustEffect::new();
+        effect.brightness = 0.5;
+        effect.apply(&mut frame, 1, 1, 0.0);
+        assert_eq!(frame, vec![4.5, 2.5, 1.0, 1.0]);
+    }
+}
@@ -0,0 +1,375 @@
+use half::f16;
+use image::{ImageBuffer, Rgba};
+use rayon::prelude::*;
+use std::path::Path;
+use thiserror::Error;
+
+/// Channel storage type of a frame buffer.
+///
+/// Integer formats map their full range to 0.0-1.0; float formats store the
+/// value directly and may exceed 1.0 for HDR highlights.
+pub trait Pixel: Copy + Default + PartialEq + Send + Sync + std::fmt::Debug + 'static {
+    /// Bits per channel
+    const BITS: u32;
+
+    fn to_f32(self) -> f32;
+    fn from_f32(v: f32) -> Self;
+
+    /// Borrow the buffer as floats when no conversion is needed
+    fn as_f32_slice_mut(_data: &mut [Self]) -> Option<&mut [f32]> {
+        None
+    }
+}
+
+impl Pixel for u8 {
+    const BITS: u32 = 8;
+
+    fn to_f32(self) -> f32 {
+        self as f32 / 255.0
+    }
+
+    fn from_f32(v: f32) -> Self {
+        (v.clamp(0.0, 1.0) * 255.0).round() as u8
+    }
+}
+
+impl Pixel for u16 {
+    const BITS: u32 = 16;
+
+    fn to_f32(self) -> f32 {
+        self as f32 / 65535.0
+    }
+
+    fn from_f32(v: f32) -> Self {
+        (v.clamp(0.0, 1.0) * 65535.0).round() as u16
+    }
+}
+
+impl Pixel for f16 {
+    const BITS: u32 = 16;
+
+    fn to_f32(self) -> f32 {
+        f16::to_f32(self)
+    }
+
+    fn from_f32(v: f32) -> Self {
+        f16::from_f32(v)
+    }
+}
+
+impl Pixel for f32 {
+    const BITS: u32 = 32;
+
+    fn to_f32(self) -> f32 {
+        self
+    }
+
+    fn from_f32(v: f32) -> Self {
+        v
+    }
+
+    fn as_f32_slice_mut(data: &mut [Self]) -> Option<&mut [f32]> {
+        Some(data)
+    }
+}
+
+/// Convert a buffer of any pixel type to floats
+pub fn to_f32_vec<P: Pixel>(data: &[P]) -> Vec<f32> {
+    data.par_iter().map(|v| v.to_f32()).collect()
+}
+
+/// Convert floats back to a pixel type, clamping integer formats
+pub fn from_f32_vec<P: Pixel>(data: &[f32]) -> Vec<P> {
+    data.par_iter().map(|v| P::from_f32(*v)).collect()
+}
+
+/// Run a float kernel over a buffer of any pixel type in place.
+///
+/// Float buffers are handed over directly; other formats are converted to
+/// 0-1 floats and written back once the kernel is done.
+pub fn process_f32<P: Pixel, F: FnOnce(&mut [f32])>(data: &mut [P], kernel: F) {
+    if let Some(floats) = P::as_f32_slice_mut(data) {
+        kernel(floats);
+        return;
+    }
+    let mut floats = to_f32_vec(data);
+    kernel(&mut floats);
+    data.par_iter_mut()
+        .zip(floats.par_iter())
+        .for_each(|(d, v)| *d = P::from_f32(*v));
+}
+
+#[derive(Debug, Error)]
+pub enum FrameError {
+    #[error("{path}: {source}")]
+    Image {
+        path: String,
+        #[source]
+        source: image::ImageError,
+    },
+    #[error("{path}: {source}")]
+    Exr {
+        path: String,
+        #[source]
+        source: exr::error::Error,
+    },
+    #[error("frame data has {actual} values, expected {expected}")]
+    SizeMismatch { expected: usize, actual: usize },
+    #[error("{0}")]
+    Unsupported(String),
+}
+
+impl From<FrameError> for pyo3::PyErr {
+    fn from(err: FrameError) -> Self {
+        match err {
+            FrameError::Image { .. } | FrameError::Exr { .. } => {
+                pyo3::PyErr::new::<pyo3::exceptions::PyIOError, _>(err.to_string())
+            }
+            _ => pyo3::PyErr::new::<pyo3::exceptions::PyValueError, _>(err.to_string()),
+        }
+    }
+}
+
+/// Precision of a written image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitDepth {
+    Eight,
+    /// 10-bit code values stored in a 16-bit container
+    Ten,
+    Sixteen,
+    Float,
+}
+
+impl BitDepth {
+    pub fn from_bits(bits: u32) -> Option<Self> {
+        match bits {
+            8 => Some(BitDepth::Eight),
+            10 => Some(BitDepth::Ten),
+            16 => Some(BitDepth::Sixteen),
+            32 => Some(BitDepth::Float),
+            _ => None,
+        }
+    }
+
+    pub fn bits(&self) -> u32 {
+        match self {
+            BitDepth::Eight => 8,
+            BitDepth::Ten => 10,
+            BitDepth::Sixteen => 16,
+            BitDepth::Float => 32,
+        }
+    }
+}
+
+/// Interleaved RGBA frame with straight (non-premultiplied) alpha
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame<P: Pixel = f32> {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<P>,
+}
+
+impl<P: Pixel> Frame<P> {
+    /// Transparent black frame
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            data: vec![P::default(); (width * height * 4) as usize],
+        }
+    }
+
+    pub fn from_raw(width: u32, height: u32, data: Vec<P>) -> Result<Self, FrameError> {
+        let expected = (width * height * 4) as usize;
+        if data.len() != expected {
+            return Err(FrameError::SizeMismatch {
+                expected,
+                actual: data.len(),
+            });
+        }
+        Ok(Self {
+            width,
+            height,
+            data,
+        })
+    }
+
+    /// Same image in another pixel format
+    pub fn convert<Q: Pixel>(&self) -> Frame<Q> {
+        Frame {
+            width: self.width,
+            height: self.height,
+            data: self
+                .data
+                .par_iter()
+                .map(|v| Q::from_f32(v.to_f32()))
+                .collect(),
+        }
+    }
+
+    pub fn to_f32(&self) -> Frame<f32> {
+        self.convert()
+    }
+
+    /// Pixel at (x, y) as 0-1 floats
+    pub fn pixel(&self, x: u32, y: u32) -> [f32; 4] {
+        let i = ((y * self.width + x) * 4) as usize;
+        [
+            self.data[i].to_f32(),
+            self.data[i + 1].to_f32(),
+            self.data[i + 2].to_f32(),
+            self.data[i + 3].to_f32(),
+        ]
+    }
+
+    /// Run a float kernel over the frame in place
+    pub fn process<F: FnOnce(&mut [f32], u32, u32)>(&mut self, kernel: F) {
+        let (width, height) = (self.width, self.height);
+        process_f32(&mut self.data, |data| kernel(data, width, height));
+    }
+}
+
+impl Frame<f32> {
+    /// Load any image the `image` crate can decode. Integer formats are
+    /// normalised to 0-1; EXR keeps its float values.
+    pub fn open<Q: AsRef<Path>>(path: Q) -> Result<Self, FrameError> {
+        let path = path.as_ref();
+        let image = image::open(path).map_err(|source| FrameError::Image {
+            path: path.display().to_string(),
+            source,
+        })?;
+        let rgba = image.to_rgba32f();
+        Ok(Self {
+            width: rgba.width(),
+            height: rgba.height(),
+            data: rgba.into_raw(),
+        })
+    }
+
+    /// Write the frame, picking the container from the extension.
+    ///
+    /// PNG and TIFF take 8, 10 or 16 bits (10-bit values are quantised to
+    /// 1023 steps and stored in 16 bits); EXR takes 16-bit half or 32-bit
+    /// float.
+    pub fn save<Q: AsRef<Path>>(&self, path: Q, depth: BitDepth) -> Result<(), FrameError> {
+        let path = path.as_ref();
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase())
+            .unwrap_or_default();
+        let image_err = |source| FrameError::Image {
+            path: path.display().to_string(),
+            source,
+        };
+
+        match (ext.as_str(), depth) {
+            ("exr", BitDepth::Float) => {
+                ImageBuffer::<Rgba<f32>, _>::from_raw(self.width, self.height, self.data.clone())
+                    .expect("frame size checked on construction")
+                    .save(path)
+                    .map_err(image_err)
+            }
+            ("exr", BitDepth::Sixteen) => {
+                let width = self.width as usize;
+                exr::prelude::write_rgba_file(path, width, self.height as usize, |x, y| {
+                    let i = (y * width + x) * 4;
+                    let px = &self.data[i..i + 4];
+                    (
+                        f16::from_f32(px[0]),
+                        f16::from_f32(px[1]),
+                        f16::from_f32(px[2]),
+                        f16::from_f32(px[3]),
+                    )
+                })
+                .map_err(|source| FrameError::Exr {
+                    path: path.display().to_string(),
+                    source,
+                })
+            }
+            ("exr", _) => Err(FrameError::Unsupported(
+                "EXR output is 16-bit half or 32-bit float".to_string(),
+            )),
+            ("png" | "tif" | "tiff", BitDepth::Eight) => ImageBuffer::<Rgba<u8>, _>::from_raw(
+                self.width,
+                self.height,
+                from_f32_vec(&self.data),
+            )
+            .expect("frame size checked on construction")
+            .save(path)
+            .map_err(image_err),
+            ("png" | "tif" | "tiff", BitDepth::Ten | BitDepth::Sixteen) => {
+                let steps = ((1u32 << depth.bits()) - 1) as f32;
+                let data: Vec<u16> = self
+                    .data
+                    .par_iter()
+                    .map(|v| {
+                        let q = (v.clamp(0.0, 1.0) * steps).round() / steps;
+                        (q * 65535.0).round() as u16
+                    })
+                    .collect();
+                ImageBuffer::<Rgba<u16>, _>::from_raw(self.width, self.height, data)
+                    .expect("frame size checked on construction")
+                    .save(path)
+                    .map_err(image_err)
+            }
+            ("png" | "tif" | "tiff", BitDepth::Float) => Err(FrameError::Unsupported(format!(
+                "{} output supports 8, 10 or 16 bits; use .exr for float",
+                ext.to_uppercase()
+            ))),
+            _ => Err(FrameError::Unsupported(format!(
+                "Unsupported output format '{}'",
+                path.display()
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pixel_formats_round_trip_through_f32() {
+        assert_eq!(u8::from_f32(200u8.to_f32()), 200);
+        assert_eq!(u16::from_f32(51234u16.to_f32()), 51234);
+        assert_eq!(f16::from_f32(2.5).to_f32(), 2.5);
+        assert_eq!(u8::from_f32(4.0), 255);
+    }
+
+    #[test]
+    fn sixteen_bit_png_keeps_precision() {
+        let dir = std::env::temp_dir().join(format!("fluxreel_frame_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("gradient.png");
+        let data: Vec<f32> = (0..64 * 4).map(|i| (i / 4) as f32 / 1000.0).collect();
+        let frame = Frame::from_raw(64, 1, data).unwrap();
+        frame.save(&path, BitDepth::Sixteen).unwrap();
+
+        let loaded = Frame::open(&path).unwrap();
+        for (a, b) in frame.data.iter().zip(&loaded.data) {
+            assert!((a - b).abs() < 1.0 / 65535.0);
+        }
+        assert!(frame.save(dir.join("bad.png"), BitDepth::Float).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn half_float_exr_keeps_overbright_values() {
+        let dir =
+            std::env::temp_dir().join(format!("fluxreel_frame_half_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("hdr.exr");
+        let data: Vec<f32> = (0..8 * 2 * 4).map(|i| i as f32 * 0.25).collect();
+        let frame = Frame::from_raw(8, 2, data).unwrap();
+        frame.save(&path, BitDepth::Sixteen).unwrap();
+
+        let loaded = Frame::open(&path).unwrap();
+        assert_eq!((loaded.width, loaded.height), (8, 2));
+        for (a, b) in frame.data.iter().zip(&loaded.data) {
+            assert_eq!(f16::from_f32(*a).to_f32(), *b);
+        }
+        assert!(frame.save(dir.join("bad.exr"), BitDepth::Eight).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
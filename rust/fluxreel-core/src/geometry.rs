@@ -1,4 +1,4 @@
-use nalgebra::{Point2, Vector2};
+use nalgebra::{Matrix3, Point2, SMatrix, Vector2, Vector3};
 use pyo3::prelude::*;
 
 #[pyclass]
@@ -132,3 +132,134 @@ pub fn rotate_point(px: f32, py: f32, cx: f32, cy: f32, angle_degrees: f32) -> (
     let dy = py - cy;
     (cx + dx * cos_a - dy * sin_a, cy + dx * sin_a + dy * cos_a)
 }
+
+/// Projective mapping between two planes, as a 3x3 matrix acting on
+/// homogeneous coordinates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Homography(pub Matrix3<f64>);
+
+impl Homography {
+    pub fn identity() -> Self {
+        Homography(Matrix3::identity())
+    }
+
+    /// Least-squares fit mapping each `src` point onto the matching `dst`
+    /// point (direct linear transform on normalised coordinates). Needs at
+    /// least four correspondences, no three of them collinear.
+    pub fn from_points(src: &[Point2<f32>], dst: &[Point2<f32>]) -> Option<Self> {
+        if src.len() < 4 || src.len() != dst.len() {
+            return None;
+        }
+        let (ts, src) = normalise(src)?;
+        let (td, dst) = normalise(dst)?;
+
+        // Accumulate AᵀA for the 2n x 9 DLT system
+        let mut ata = SMatrix::<f64, 9, 9>::zeros();
+        for (p, q) in src.iter().zip(&dst) {
+            let rows = [
+                [-p.x, -p.y, -1.0, 0.0, 0.0, 0.0, q.x * p.x, q.x * p.y, q.x],
+                [0.0, 0.0, 0.0, -p.x, -p.y, -1.0, q.y * p.x, q.y * p.y, q.y],
+            ];
+            for row in rows {
+                let r = SMatrix::<f64, 9, 1>::from_row_slice(&row);
+                ata += r * r.transpose();
+            }
+        }
+        let eigen = ata.symmetric_eigen();
+        let (smallest, _) = eigen
+            .eigenvalues
+            .iter()
+            .enumerate()
+            .min_by(|a, b| a.1.total_cmp(b.1))?;
+        let h = eigen.eigenvectors.column(smallest);
+        let normalised = Matrix3::from_row_slice(h.as_slice());
+
+        let m = td.try_inverse()? * normalised * ts;
+        if m[(2, 2)].abs() < 1e-12 {
+            return None;
+        }
+        Some(Homography(m / m[(2, 2)]))
+    }
+
+    /// Map the rectangle `width` x `height` (origin top-left) onto a quad
+    /// given clockwise from its top-left corner
+    pub fn from_rect(width: f32, height: f32, quad: &[Point2<f32>; 4]) -> Option<Self> {
+        let rect = [
+            Point2::new(0.0, 0.0),
+            Point2::new(width, 0.0),
+            Point2::new(width, height),
+            Point2::new(0.0, height),
+        ];
+        Self::from_points(&rect, quad)
+    }
+
+    /// Mapped point, or `None` for points sent to infinity
+    pub fn apply(&self, p: Point2<f32>) -> Option<Point2<f32>> {
+        let v = self.0 * Vector3::new(p.x as f64, p.y as f64, 1.0);
+        if v.z.abs() < 1e-12 {
+            return None;
+        }
+        Some(Point2::new((v.x / v.z) as f32, (v.y / v.z) as f32))
+    }
+
+    pub fn inverse(&self) -> Option<Self> {
+        self.0.try_inverse().map(Homography)
+    }
+
+    /// Row-major matrix, normalised so the bottom-right entry is 1
+    pub fn to_row_major(&self) -> Vec<f32> {
+        let m = self.0 / self.0[(2, 2)];
+        m.transpose().iter().map(|v| *v as f32).collect()
+    }
+}
+
+/// Translate points to their centroid and scale them to a mean distance of
+/// √2, which keeps the DLT well conditioned. Returns the transform as well.
+fn normalise(points: &[Point2<f32>]) -> Option<(Matrix3<f64>, Vec<Point2<f64>>)> {
+    let n = points.len() as f64;
+    let (cx, cy) = points.iter().fold((0.0, 0.0), |(x, y), p| {
+        (x + p.x as f64 / n, y + p.y as f64 / n)
+    });
+    let mean_distance = points
+        .iter()
+        .map(|p| ((p.x as f64 - cx).powi(2) + (p.y as f64 - cy).powi(2)).sqrt())
+        .sum::<f64>()
+        / n;
+    if mean_distance < 1e-9 {
+        return None;
+    }
+    let s = std::f64::consts::SQRT_2 / mean_distance;
+    let t = Matrix3::new(s, 0.0, -s * cx, 0.0, s, -s * cy, 0.0, 0.0, 1.0);
+    let mapped = points
+        .iter()
+        .map(|p| Point2::new(s * (p.x as f64 - cx), s * (p.y as f64 - cy)))
+        .collect();
+    Some((t, mapped))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn homography_maps_rect_onto_quad() {
+        let quad = [
+            Point2::new(10.0, 12.0),
+            Point2::new(90.0, 5.0),
+            Point2::new(80.0, 70.0),
+            Point2::new(20.0, 60.0),
+        ];
+        let h = Homography::from_rect(100.0, 50.0, &quad).unwrap();
+        assert!((h.apply(Point2::new(100.0, 50.0)).unwrap() - quad[2]).norm() < 1e-3);
+        let back = h.inverse().unwrap().apply(quad[3]).unwrap();
+        assert!((back - Point2::new(0.0, 50.0)).norm() < 1e-3);
+        // Straight lines stay straight: the rect centre lands on the
+        // intersection of the quad's diagonals
+        let centre = h.apply(Point2::new(50.0, 25.0)).unwrap();
+        let d1 = quad[2] - quad[0];
+        let d2 = quad[3] - quad[1];
+        let cross = |a: Vector2<f32>, b: Vector2<f32>| a.x * b.y - a.y * b.x;
+        assert!(cross(centre - quad[0], d1).abs() < 1e-2 * d1.norm());
+        assert!(cross(centre - quad[1], d2).abs() < 1e-2 * d2.norm());
+    }
+}
@@ -7,17 +7,23 @@ pub mod audio;
 pub mod audio_pro;
 pub mod blend_modes;
 pub mod color_grading;
+pub mod color_management;
 pub mod effects;
+pub mod frame;
 pub mod geometry;
 pub mod masking;
 pub mod motion_tracking;
 pub mod multicam;
 pub mod node;
+pub mod optical_flow;
 pub mod renderer;
+pub mod sampling;
 pub mod scene;
+pub mod scopes;
 pub mod speed_ramping;
 pub mod streaming;
 pub mod time;
+pub mod time_stretch;
 pub mod transitions;
 pub mod utils;
 
@@ -27,17 +33,23 @@ pub use audio::*;
 pub use audio_pro::*;
 pub use blend_modes::*;
 pub use color_grading::*;
+pub use color_management::*;
 pub use effects::*;
+pub use frame::*;
 pub use geometry::*;
 pub use masking::*;
 pub use motion_tracking::*;
 pub use multicam::*;
 pub use node::*;
+pub use optical_flow::*;
 pub use renderer::*;
+pub use sampling::*;
 pub use scene::*;
+pub use scopes::*;
 pub use speed_ramping::*;
 pub use streaming::*;
 pub use time::*;
+pub use time_stretch::*;
 pub use transitions::*;
 pub use utils::*;
 
@@ -141,6 +153,20 @@ fn fluxreel_core(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<ColorAdjustEffect>()?;
     m.add_class::<ChromaKeyEffect>()?;
     m.add_class::<NoiseEffect>()?;
+    m.add_class::<DisplacementEffect>()?;
+    m.add_class::<LensDistortionEffect>()?;
+    m.add_class::<RippleEffect>()?;
+    m.add_class::<WaveEffect>()?;
+    m.add_class::<TwirlEffect>()?;
+    m.add_class::<BulgeEffect>()?;
+    m.add_class::<CornerPinEffect>()?;
+    m.add_class::<PosterizeEffect>()?;
+    m.add_class::<ThresholdEffect>()?;
+    m.add_class::<HalftoneEffect>()?;
+    m.add_class::<PixelateEffect>()?;
+    m.add_class::<EdgeDetectEffect>()?;
+    m.add_class::<EmbossEffect>()?;
+    m.add_class::<VignetteEffect>()?;
 
     // Geometry
     m.add_class::<Rectangle>()?;
@@ -159,15 +185,30 @@ fn fluxreel_core(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<ColorCurves>()?;
     m.add_class::<LUT>()?;
 
+    // Color management
+    m.add_class::<ColorConfig>()?;
+    m.add_function(wrap_pyfunction!(convert_color, m)?)?;
+
+    // Scopes
+    m.add_function(wrap_pyfunction!(scope_histogram, m)?)?;
+    m.add_function(wrap_pyfunction!(scope_waveform, m)?)?;
+    m.add_function(wrap_pyfunction!(scope_rgb_parade, m)?)?;
+    m.add_function(wrap_pyfunction!(scope_vectorscope, m)?)?;
+
     // Masking & Rotoscoping
     m.add_class::<Mask>()?;
     m.add_class::<RectangleMask>()?;
     m.add_class::<EllipseMask>()?;
+    m.add_function(wrap_pyfunction!(apply_track_matte, m)?)?;
 
     // Motion Tracking
     m.add_class::<MotionTracker>()?;
+    m.add_class::<PlanarTracker>()?;
     m.add_class::<Stabilizer>()?;
 
+    // Optical Flow
+    m.add_class::<OpticalFlow>()?;
+
     // Professional Audio
     m.add_class::<Equalizer>()?;
     m.add_class::<Compressor>()?;
@@ -1,26 +1,456 @@
-use nalgebra::Point2;
+use crate::animation::PropertyTracks;
+use crate::effects::check_property;
+use crate::frame::{Frame, Pixel};
+use crate::motion_tracking::MotionTracker;
+use crate::node::TrackBinding;
+use crate::utils::{ease_function, lerp, smoothstep};
+use nalgebra::{Point2, Rotation2, Vector2};
 use pyo3::prelude::*;
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 
+/// Maximum distance (px) between a flattened segment and its curve
+const FLATNESS: f32 = 0.1;
+/// Sub-scanlines per pixel row for anti-aliased fills
+const SUBSAMPLES: usize = 5;
+
 #[derive(Debug, Clone)]
 pub struct BezierPoint {
     pub anchor: Point2<f32>,
     pub handle_in: Point2<f32>,
     pub handle_out: Point2<f32>,
+    /// Feather width at this vertex; `None` uses the mask's feather
+    pub feather: Option<f32>,
+}
+
+impl BezierPoint {
+    fn translated(&self, offset: Vector2<f32>) -> Self {
+        Self {
+            anchor: self.anchor + offset,
+            handle_in: self.handle_in + offset,
+            handle_out: self.handle_out + offset,
+            feather: self.feather,
+        }
+    }
+
+    /// Anchor and handles passed through `f`
+    fn mapped<F: Fn(Point2<f32>) -> Point2<f32>>(&self, f: F) -> Self {
+        Self {
+            anchor: f(self.anchor),
+            handle_in: f(self.handle_in),
+            handle_out: f(self.handle_out),
+            feather: self.feather,
+        }
+    }
+
+    /// Blend towards `other`; a feather set on only one side blends with
+    /// the mask-wide `default_feather`
+    fn lerp(&self, other: &Self, t: f32, default_feather: f32) -> Self {
+        let mix = |a: Point2<f32>, b: Point2<f32>| a + (b - a) * t;
+        let feather = match (self.feather, other.feather) {
+            (None, None) => None,
+            (a, b) => Some(lerp(
+                a.unwrap_or(default_feather),
+                b.unwrap_or(default_feather),
+                t,
+            )),
+        };
+        Self {
+            anchor: mix(self.anchor, other.anchor),
+            handle_in: mix(self.handle_in, other.handle_in),
+            handle_out: mix(self.handle_out, other.handle_out),
+            feather,
+        }
+    }
+}
+
+/// Mask shape at one point in time
+#[derive(Debug, Clone)]
+pub struct PathKeyframe {
+    pub time: f32,
+    pub points: Vec<BezierPoint>,
+    pub easing: String,
+}
+
+/// Per-pixel coverage (0-1) of a mask, row-major
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlphaMatte {
+    pub width: u32,
+    pub height: u32,
+    pub alpha: Vec<f32>,
+}
+
+impl AlphaMatte {
+    pub fn new(width: u32, height: u32, value: f32) -> Self {
+        Self {
+            width,
+            height,
+            alpha: vec![value; (width * height) as usize],
+        }
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> f32 {
+        self.alpha[(y * self.width + x) as usize]
+    }
+
+    pub fn invert(&mut self) {
+        self.alpha.iter_mut().for_each(|a| *a = 1.0 - *a);
+    }
+
+    /// Multiply the alpha channel of an RGBA frame of the same size
+    pub fn apply<P: Pixel>(&self, frame: &mut [P]) {
+        debug_assert_eq!(frame.len(), self.alpha.len() * 4);
+        frame
+            .par_chunks_mut(4)
+            .zip(self.alpha.par_iter())
+            .for_each(|(px, a)| px[3] = P::from_f32(px[3].to_f32() * a));
+    }
+
+    /// Alpha as one byte per pixel
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.alpha.iter().map(|a| u8::from_f32(*a)).collect()
+    }
+}
+
+/// How a mask combines with the masks above it in a stack
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaskMode {
+    /// Disabled
+    None,
+    Add,
+    Subtract,
+    Intersect,
+    Difference,
+}
+
+impl MaskMode {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "none" => Some(MaskMode::None),
+            "add" => Some(MaskMode::Add),
+            "subtract" => Some(MaskMode::Subtract),
+            "intersect" => Some(MaskMode::Intersect),
+            "difference" => Some(MaskMode::Difference),
+            _ => None,
+        }
+    }
+
+    /// Combine the alpha accumulated so far with this mask's alpha
+    pub fn combine(&self, base: f32, mask: f32) -> f32 {
+        match self {
+            MaskMode::None => base,
+            MaskMode::Add => base + mask - base * mask,
+            MaskMode::Subtract => base * (1.0 - mask),
+            MaskMode::Intersect => base * mask,
+            MaskMode::Difference => base + mask - 2.0 * base * mask,
+        }
+    }
+}
+
+fn parse_mask_mode(mode: &str) -> PyResult<MaskMode> {
+    MaskMode::from_name(mode).ok_or_else(|| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Unknown mask mode '{}'; expected none, add, subtract, intersect or difference",
+            mode
+        ))
+    })
+}
+
+/// A shape that rasterizes to soft alpha
+pub trait MaskShape {
+    /// Coverage of the bare shape with feathering, before invert and opacity
+    fn coverage(&self, width: u32, height: u32) -> Vec<f32>;
+    fn inverted(&self) -> bool;
+    fn opacity(&self) -> f32;
+    fn mode(&self) -> MaskMode;
+
+    /// Coverage with invert and opacity applied
+    fn rasterize(&self, width: u32, height: u32) -> AlphaMatte {
+        let mut alpha = self.coverage(width, height);
+        let (inverted, opacity) = (self.inverted(), self.opacity().clamp(0.0, 1.0));
+        alpha.par_iter_mut().for_each(|a| {
+            let value = if inverted { 1.0 - *a } else { *a };
+            *a = value * opacity;
+        });
+        AlphaMatte {
+            width,
+            height,
+            alpha,
+        }
+    }
+}
+
+/// Alpha at a pixel `sdf` px outside a shape edge (negative inside). Feathers
+/// wider than a pixel are centred on the edge; narrower ones fall back to a
+/// one-pixel anti-aliasing ramp.
+fn edge_alpha(sdf: f32, feather: f32) -> f32 {
+    if feather > 1.0 {
+        smoothstep(-feather / 2.0, feather / 2.0, -sdf)
+    } else {
+        (0.5 - sdf).clamp(0.0, 1.0)
+    }
+}
+
+/// Evaluate a signed distance function at every pixel centre
+fn sdf_coverage<F: Fn(f32, f32) -> f32 + Sync>(
+    width: u32,
+    height: u32,
+    feather: f32,
+    sdf: F,
+) -> Vec<f32> {
+    let mut alpha = vec![0.0; (width * height) as usize];
+    if width == 0 {
+        return alpha;
+    }
+    alpha
+        .par_chunks_mut(width as usize)
+        .enumerate()
+        .for_each(|(y, row)| {
+            for (x, a) in row.iter_mut().enumerate() {
+                *a = edge_alpha(sdf(x as f32 + 0.5, y as f32 + 0.5), feather);
+            }
+        });
+    alpha
+}
+
+/// Closed polygon of a flattened mask path with a feather width per vertex
+#[derive(Debug, Clone, Default)]
+pub struct MaskPolygon {
+    pub points: Vec<Point2<f32>>,
+    pub feather: Vec<f32>,
+}
+
+impl MaskPolygon {
+    fn edges(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let n = self.points.len();
+        (0..n).map(move |i| (i, (i + 1) % n))
+    }
+
+    /// Shoelace area; positive when the path winds clockwise on screen
+    pub fn signed_area(&self) -> f32 {
+        self.edges()
+            .map(|(i, j)| {
+                let (a, b) = (self.points[i], self.points[j]);
+                a.x * b.y - b.x * a.y
+            })
+            .sum::<f32>()
+            / 2.0
+    }
+
+    /// Even-odd point-in-polygon test
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        let mut inside = false;
+        for (i, j) in self.edges() {
+            let (a, b) = (self.points[i], self.points[j]);
+            if (a.y <= y) != (b.y <= y) && x < a.x + (y - a.y) * (b.x - a.x) / (b.y - a.y) {
+                inside = !inside;
+            }
+        }
+        inside
+    }
+
+    /// Grow (positive) or shrink (negative) the outline by `distance` px,
+    /// moving each vertex along its mitred normal
+    pub fn offset(&self, distance: f32) -> Self {
+        let n = self.points.len();
+        if distance == 0.0 || n < 3 {
+            return self.clone();
+        }
+        let side = self.signed_area().signum();
+        let normal = |a: Point2<f32>, b: Point2<f32>| {
+            let d = b - a;
+            Vector2::new(d.y, -d.x).normalize() * side
+        };
+        let points = (0..n)
+            .map(|i| {
+                let prev = self.points[(i + n - 1) % n];
+                let (p, next) = (self.points[i], self.points[(i + 1) % n]);
+                let (n0, n1) = (normal(prev, p), normal(p, next));
+                let sum = n0 + n1;
+                if sum.norm() < 1e-6 {
+                    return p + n0 * distance;
+                }
+                let miter = sum.normalize();
+                // Limit spikes at very sharp corners
+                p + miter * (distance / miter.dot(&n0).max(0.25))
+            })
+            .collect();
+        Self {
+            points,
+            feather: self.feather.clone(),
+        }
+    }
+
+    /// Anti-aliased even-odd fill: coverage is exact along each sub-scanline
+    /// and averaged over `SUBSAMPLES` sub-scanlines per row
+    pub fn coverage(&self, width: u32, height: u32) -> Vec<f32> {
+        let mut alpha = vec![0.0; (width * height) as usize];
+        if self.points.len() < 3 || width == 0 {
+            return alpha;
+        }
+        let edges: Vec<(Point2<f32>, Point2<f32>)> = self
+            .edges()
+            .map(|(i, j)| (self.points[i], self.points[j]))
+            .collect();
+        alpha
+            .par_chunks_mut(width as usize)
+            .enumerate()
+            .for_each(|(y, row)| {
+                let y = y as f32;
+                let candidates: Vec<_> = edges
+                    .iter()
+                    .filter(|(a, b)| a.y.min(b.y) < y + 1.0 && a.y.max(b.y) > y)
+                    .collect();
+                if candidates.is_empty() {
+                    return;
+                }
+                let weight = 1.0 / SUBSAMPLES as f32;
+                let mut crossings = Vec::new();
+                for s in 0..SUBSAMPLES {
+                    let sy = y + (s as f32 + 0.5) * weight;
+                    crossings.clear();
+                    for (a, b) in &candidates {
+                        if (a.y <= sy) != (b.y <= sy) {
+                            crossings.push(a.x + (sy - a.y) * (b.x - a.x) / (b.y - a.y));
+                        }
+                    }
+                    crossings.sort_by(|a, b| a.total_cmp(b));
+                    for span in crossings.chunks_exact(2) {
+                        add_span(row, span[0], span[1], weight);
+                    }
+                }
+                row.iter_mut().for_each(|a| *a = a.min(1.0));
+            });
+        alpha
+    }
+
+    /// Soften the edges of a coverage buffer. Each pixel near the outline
+    /// takes a smoothstep of its signed distance to the nearest edge over
+    /// that edge's feather width (interpolated between its vertices), so the
+    /// feather is centred on the path.
+    pub fn feather_edges(&self, alpha: &mut [f32], width: u32) {
+        let reach = self.feather.iter().fold(0.0f32, |m, f| m.max(*f)) / 2.0;
+        if reach <= 0.0 || self.points.len() < 3 || width == 0 {
+            return;
+        }
+        let edges: Vec<usize> = (0..self.points.len()).collect();
+        alpha
+            .par_chunks_mut(width as usize)
+            .enumerate()
+            .for_each(|(y, row)| {
+                let cy = y as f32 + 0.5;
+                let candidates: Vec<(usize, usize)> = edges
+                    .iter()
+                    .map(|&i| (i, (i + 1) % self.points.len()))
+                    .filter(|&(i, j)| {
+                        let (a, b) = (self.points[i], self.points[j]);
+                        a.y.min(b.y) - reach <= cy && a.y.max(b.y) + reach >= cy
+                    })
+                    .collect();
+                if candidates.is_empty() {
+                    return;
+                }
+                for (x, a) in row.iter_mut().enumerate() {
+                    let p = Point2::new(x as f32 + 0.5, cy);
+                    let (distance, feather) = candidates
+                        .iter()
+                        .map(|&(i, j)| self.edge_distance(p, i, j))
+                        .fold(
+                            (f32::MAX, 0.0),
+                            |best, d| if d.0 < best.0 { d } else { best },
+                        );
+                    // Below a pixel the anti-aliased fill is already right
+                    if feather <= 1.0 || distance > feather / 2.0 {
+                        continue;
+                    }
+                    let signed = if *a >= 0.5 { distance } else { -distance };
+                    *a = smoothstep(-feather / 2.0, feather / 2.0, signed);
+                }
+            });
+    }
+
+    /// Distance from `p` to edge (i, j) and the feather at the closest point
+    fn edge_distance(&self, p: Point2<f32>, i: usize, j: usize) -> (f32, f32) {
+        let (a, b) = (self.points[i], self.points[j]);
+        let d = b - a;
+        let len2 = d.norm_squared();
+        let t = if len2 > 0.0 {
+            ((p - a).dot(&d) / len2).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let distance = (p - (a + d * t)).norm();
+        (distance, lerp(self.feather[i], self.feather[j], t))
+    }
 }
 
+/// Add horizontal coverage of the span [x0, x1) to a row of pixels
+fn add_span(row: &mut [f32], x0: f32, x1: f32, weight: f32) {
+    let x0 = x0.max(0.0);
+    let x1 = x1.min(row.len() as f32);
+    if x1 <= x0 {
+        return;
+    }
+    let last = (x1.ceil() as usize).min(row.len());
+    for (px, a) in row.iter_mut().enumerate().take(last).skip(x0 as usize) {
+        let cover = x1.min(px as f32 + 1.0) - x0.max(px as f32);
+        *a += cover.max(0.0) * weight;
+    }
+}
+
+/// Append points along a cubic bezier until each piece is within
+/// `FLATNESS` of the curve. Points carry their curve parameter.
+fn flatten_cubic(
+    p: [Point2<f32>; 4],
+    t0: f32,
+    t1: f32,
+    depth: u32,
+    out: &mut Vec<(Point2<f32>, f32)>,
+) {
+    let chord = p[3] - p[0];
+    let len = chord.norm();
+    let deviation = |c: Point2<f32>| {
+        let v = c - p[0];
+        if len > 1e-6 {
+            (v.x * chord.y - v.y * chord.x).abs() / len
+        } else {
+            v.norm()
+        }
+    };
+    if depth >= 16 || deviation(p[1]).max(deviation(p[2])) <= FLATNESS {
+        out.push((p[3], t1));
+        return;
+    }
+    // de Casteljau split at the midpoint
+    let mid = |a: Point2<f32>, b: Point2<f32>| Point2::from((a.coords + b.coords) / 2.0);
+    let (p01, p12, p23) = (mid(p[0], p[1]), mid(p[1], p[2]), mid(p[2], p[3]));
+    let (p012, p123) = (mid(p01, p12), mid(p12, p23));
+    let centre = mid(p012, p123);
+    let tm = (t0 + t1) / 2.0;
+    flatten_cubic([p[0], p01, p012, centre], t0, tm, depth + 1, out);
+    flatten_cubic([centre, p123, p23, p[3]], tm, t1, depth + 1, out);
+}
+
+const MASK_PROPERTIES: &[&str] = &["feather", "expansion", "opacity"];
+
 #[pyclass]
 #[derive(Clone)]
 pub struct Mask {
     pub points: Vec<BezierPoint>,
+    /// Animated shape; when non-empty it overrides `points`
+    pub path_keyframes: Vec<PathKeyframe>,
+    pub keyframes: PropertyTracks,
+    pub tracker: Option<Box<TrackBinding>>,
     #[pyo3(get, set)]
-    pub feather: f32,
+    pub feather: f32, // px, centred on the path
     #[pyo3(get, set)]
-    pub expansion: f32,
+    pub expansion: f32, // px, negative contracts
     #[pyo3(get, set)]
     pub inverted: bool,
     #[pyo3(get, set)]
     pub opacity: f32,
+    #[pyo3(get)]
+    pub mode: String, // none, add, subtract, intersect, difference
 }
 
 #[pymethods]
@@ -29,33 +459,310 @@ impl Mask {
     fn new() -> Self {
         Self {
             points: Vec::new(),
+            path_keyframes: Vec::new(),
+            keyframes: PropertyTracks::new(),
+            tracker: None,
             feather: 0.0,
             expansion: 0.0,
             inverted: false,
             opacity: 1.0,
+            mode: "add".to_string(),
         }
     }
 
+    fn set_mode(&mut self, mode: String) -> PyResult<()> {
+        parse_mask_mode(&mode)?;
+        self.mode = mode;
+        Ok(())
+    }
+
     fn add_point(&mut self, x: f32, y: f32) {
-        let point = BezierPoint {
+        self.add_bezier_point(x, y, x - 10.0, y, x + 10.0, y);
+    }
+
+    /// Vertex with explicit in and out handles
+    fn add_bezier_point(&mut self, x: f32, y: f32, in_x: f32, in_y: f32, out_x: f32, out_y: f32) {
+        self.points.push(BezierPoint {
             anchor: Point2::new(x, y),
-            handle_in: Point2::new(x - 10.0, y),
-            handle_out: Point2::new(x + 10.0, y),
-        };
-        self.points.push(point);
+            handle_in: Point2::new(in_x, in_y),
+            handle_out: Point2::new(out_x, out_y),
+            feather: None,
+        });
+    }
+
+    /// Sharp vertex with both handles on the anchor
+    fn add_corner_point(&mut self, x: f32, y: f32) {
+        self.add_bezier_point(x, y, x, y, x, y);
+    }
+
+    /// Override the feather at one vertex; it blends into its neighbours'
+    /// feather along the adjoining segments
+    fn set_point_feather(&mut self, index: usize, feather: f32) -> PyResult<()> {
+        self.point_mut(index)?.feather = Some(feather.max(0.0));
+        Ok(())
+    }
+
+    /// Replace one vertex and its handles
+    #[allow(clippy::too_many_arguments)]
+    fn set_point(
+        &mut self,
+        index: usize,
+        x: f32,
+        y: f32,
+        in_x: f32,
+        in_y: f32,
+        out_x: f32,
+        out_y: f32,
+    ) -> PyResult<()> {
+        let point = self.point_mut(index)?;
+        point.anchor = Point2::new(x, y);
+        point.handle_in = Point2::new(in_x, in_y);
+        point.handle_out = Point2::new(out_x, out_y);
+        Ok(())
+    }
+
+    /// Move one vertex together with its handles
+    fn move_point(&mut self, index: usize, x: f32, y: f32) -> PyResult<()> {
+        let point = self.point_mut(index)?;
+        let offset = Point2::new(x, y) - point.anchor;
+        *point = point.translated(offset);
+        Ok(())
+    }
+
+    /// Keyframe the current shape at `time`. Every path keyframe must have
+    /// the same number of vertices so shapes can be interpolated.
+    fn add_path_keyframe(&mut self, time: f32, easing: String) -> PyResult<()> {
+        if !time.is_finite() {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Mask path keyframe time must be finite, got {}",
+                time
+            )));
+        }
+        if let Some(first) = self.path_keyframes.first() {
+            if first.points.len() != self.points.len() {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Mask path keyframes need {} vertices, the current shape has {}",
+                    first.points.len(),
+                    self.points.len()
+                )));
+            }
+        }
+        self.path_keyframes.retain(|k| k.time != time);
+        self.path_keyframes.push(PathKeyframe {
+            time,
+            points: self.points.clone(),
+            easing,
+        });
+        self.path_keyframes
+            .sort_by(|a, b| a.time.total_cmp(&b.time));
+        Ok(())
+    }
+
+    fn clear_path_keyframes(&mut self) {
+        self.path_keyframes.clear();
+    }
+
+    fn add_keyframe(
+        &mut self,
+        property: &str,
+        time: f32,
+        value: f32,
+        easing: String,
+    ) -> PyResult<()> {
+        check_property("mask", MASK_PROPERTIES, property)?;
+        self.keyframes.add_keyframe(property, time, value, &easing);
+        Ok(())
+    }
+
+    /// Move the mask with a tracked point: at each time the shape is offset
+    /// by how far the track has moved since `reference_frame`
+    fn follow_tracker(&mut self, tracker: MotionTracker, reference_frame: u32, fps: f32) {
+        self.tracker = Some(Box::new(TrackBinding::new(
+            tracker,
+            None,
+            reference_frame,
+            fps,
+        )));
+    }
+
+    /// Move the mask with `primary` and rotate and scale it about that
+    /// point with the line from `primary` to `secondary`, as
+    /// `Node.bind_to_tracks` does
+    fn follow_trackers(
+        &mut self,
+        primary: MotionTracker,
+        secondary: MotionTracker,
+        reference_frame: u32,
+        fps: f32,
+    ) {
+        self.tracker = Some(Box::new(TrackBinding::new(
+            primary,
+            Some(secondary),
+            reference_frame,
+            fps,
+        )));
+    }
+
+    fn clear_tracker(&mut self) {
+        self.tracker = None;
+    }
+
+    /// Snapshot of the mask with its shape, animated properties and tracked
+    /// motion evaluated at `time`
+    fn at_time(&self, time: f32) -> Self {
+        let mut points = self.path_at(time);
+        if let Some(binding) = &self.tracker {
+            let (offset, rotation, scale) = binding.motion_at(time);
+            let pivot = binding.pivot().unwrap_or_else(Point2::origin);
+            let turn = Rotation2::new(rotation.to_radians());
+            let follow = |p: Point2<f32>| pivot + offset + turn * (p - pivot) * scale;
+            points.iter_mut().for_each(|p| *p = p.mapped(follow));
+        }
+        Self {
+            points,
+            path_keyframes: Vec::new(),
+            keyframes: PropertyTracks::new(),
+            tracker: None,
+            feather: self.keyframes.value_at("feather", time, self.feather),
+            expansion: self.keyframes.value_at("expansion", time, self.expansion),
+            inverted: self.inverted,
+            opacity: self.keyframes.value_at("opacity", time, self.opacity),
+            mode: self.mode.clone(),
+        }
     }
 
     fn invert(&mut self) {
         self.inverted = !self.inverted;
     }
 
-    fn contains(&self, _x: f32, _y: f32) -> bool {
-        // Simplified point-in-polygon test
-        // Full implementation would use bezier curve math
-        false
+    fn contains(&self, x: f32, y: f32) -> bool {
+        self.polygon().contains(x, y) != self.inverted
+    }
+
+    /// Alpha matte as one byte per pixel
+    #[pyo3(name = "rasterize")]
+    fn py_rasterize(&self, width: u32, height: u32) -> Vec<u8> {
+        self.rasterize(width, height).to_bytes()
+    }
+
+    /// Multiply the alpha of an RGBA8 frame by the mask
+    #[pyo3(name = "apply")]
+    fn py_apply(&self, pixels: Vec<u8>, width: u32, height: u32) -> PyResult<Vec<u8>> {
+        let mut frame = Frame::from_raw(width, height, pixels)?;
+        self.rasterize(width, height).apply(&mut frame.data);
+        Ok(frame.data)
     }
 }
 
+impl Mask {
+    fn point_mut(&mut self, index: usize) -> PyResult<&mut BezierPoint> {
+        self.points.get_mut(index).ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyIndexError, _>(format!("Mask has no point {}", index))
+        })
+    }
+
+    /// Vertices at `time`, interpolated between the surrounding path
+    /// keyframes with the earlier keyframe's easing
+    pub fn path_at(&self, time: f32) -> Vec<BezierPoint> {
+        let keys = &self.path_keyframes;
+        let (Some(first), Some(last)) = (keys.first(), keys.last()) else {
+            return self.points.clone();
+        };
+        if time <= first.time {
+            return first.points.clone();
+        }
+        if time >= last.time {
+            return last.points.clone();
+        }
+        let i = keys.iter().rposition(|k| k.time <= time).unwrap_or(0);
+        let (a, b) = (&keys[i], &keys[(i + 1).min(keys.len() - 1)]);
+        if b.time <= a.time {
+            return a.points.clone();
+        }
+        let t = ease_function(&a.easing, (time - a.time) / (b.time - a.time));
+        a.points
+            .iter()
+            .zip(&b.points)
+            .map(|(p, q)| p.lerp(q, t, self.feather))
+            .collect()
+    }
+
+    /// Flattened outline after expansion
+    pub fn polygon(&self) -> MaskPolygon {
+        let n = self.points.len();
+        let mut polygon = MaskPolygon::default();
+        if n < 2 {
+            return polygon;
+        }
+        let feather = |p: &BezierPoint| p.feather.unwrap_or(self.feather).max(0.0);
+        let mut flat = Vec::new();
+        for (i, a) in self.points.iter().enumerate() {
+            let b = &self.points[(i + 1) % n];
+            polygon.points.push(a.anchor);
+            polygon.feather.push(feather(a));
+            flat.clear();
+            flatten_cubic(
+                [a.anchor, a.handle_out, b.handle_in, b.anchor],
+                0.0,
+                1.0,
+                0,
+                &mut flat,
+            );
+            // The segment's end point starts the next segment
+            flat.pop();
+            for &(point, t) in &flat {
+                polygon.points.push(point);
+                polygon.feather.push(lerp(feather(a), feather(b), t));
+            }
+        }
+        dedup_points(&mut polygon);
+        polygon.offset(self.expansion)
+    }
+}
+
+impl MaskShape for Mask {
+    fn coverage(&self, width: u32, height: u32) -> Vec<f32> {
+        let polygon = self.polygon();
+        let mut alpha = polygon.coverage(width, height);
+        polygon.feather_edges(&mut alpha, width);
+        alpha
+    }
+
+    fn inverted(&self) -> bool {
+        self.inverted
+    }
+
+    fn opacity(&self) -> f32 {
+        self.opacity
+    }
+
+    fn mode(&self) -> MaskMode {
+        MaskMode::from_name(&self.mode).unwrap_or(MaskMode::Add)
+    }
+}
+
+/// Drop consecutive points closer than a hundredth of a pixel
+fn dedup_points(polygon: &mut MaskPolygon) {
+    let mut keep = MaskPolygon::default();
+    for (p, f) in polygon.points.iter().zip(&polygon.feather) {
+        if keep
+            .points
+            .last()
+            .is_none_or(|last| (p - last).norm() > 0.01)
+        {
+            keep.points.push(*p);
+            keep.feather.push(*f);
+        }
+    }
+    while keep.points.len() > 1
+        && (keep.points[0] - keep.points[keep.points.len() - 1]).norm() <= 0.01
+    {
+        keep.points.pop();
+        keep.feather.pop();
+    }
+    *polygon = keep;
+}
+
 #[pyclass]
 #[derive(Clone)]
 pub struct RectangleMask {
@@ -68,9 +775,13 @@ pub struct RectangleMask {
     #[pyo3(get, set)]
     pub height: f32,
     #[pyo3(get, set)]
-    pub feather: f32,
+    pub feather: f32, // px, centred on the edge
     #[pyo3(get, set)]
     pub inverted: bool,
+    #[pyo3(get, set)]
+    pub opacity: f32,
+    #[pyo3(get)]
+    pub mode: String, // none, add, subtract, intersect, difference
 }
 
 #[pymethods]
@@ -84,9 +795,23 @@ impl RectangleMask {
             height,
             feather: 0.0,
             inverted: false,
+            opacity: 1.0,
+            mode: "add".to_string(),
         }
     }
 
+    fn set_mode(&mut self, mode: String) -> PyResult<()> {
+        parse_mask_mode(&mode)?;
+        self.mode = mode;
+        Ok(())
+    }
+
+    /// Alpha matte as one byte per pixel
+    #[pyo3(name = "rasterize")]
+    fn py_rasterize(&self, width: u32, height: u32) -> Vec<u8> {
+        self.rasterize(width, height).to_bytes()
+    }
+
     fn contains(&self, px: f32, py: f32) -> bool {
         let inside =
             px >= self.x && px <= self.x + self.width && py >= self.y && py <= self.y + self.height;
@@ -110,9 +835,13 @@ pub struct EllipseMask {
     #[pyo3(get, set)]
     pub radius_y: f32,
     #[pyo3(get, set)]
-    pub feather: f32,
+    pub feather: f32, // px, centred on the edge
     #[pyo3(get, set)]
     pub inverted: bool,
+    #[pyo3(get, set)]
+    pub opacity: f32,
+    #[pyo3(get)]
+    pub mode: String, // none, add, subtract, intersect, difference
 }
 
 #[pymethods]
@@ -126,9 +855,23 @@ impl EllipseMask {
             radius_y,
             feather: 0.0,
             inverted: false,
+            opacity: 1.0,
+            mode: "add".to_string(),
         }
     }
 
+    fn set_mode(&mut self, mode: String) -> PyResult<()> {
+        parse_mask_mode(&mode)?;
+        self.mode = mode;
+        Ok(())
+    }
+
+    /// Alpha matte as one byte per pixel
+    #[pyo3(name = "rasterize")]
+    fn py_rasterize(&self, width: u32, height: u32) -> Vec<u8> {
+        self.rasterize(width, height).to_bytes()
+    }
+
     fn contains(&self, px: f32, py: f32) -> bool {
         let dx = (px - self.center_x) / self.radius_x;
         let dy = (py - self.center_y) / self.radius_y;
@@ -140,3 +883,402 @@ impl EllipseMask {
         }
     }
 }
+
+impl MaskShape for RectangleMask {
+    fn coverage(&self, width: u32, height: u32) -> Vec<f32> {
+        let (hw, hh) = (self.width.abs() / 2.0, self.height.abs() / 2.0);
+        let (cx, cy) = (self.x + self.width / 2.0, self.y + self.height / 2.0);
+        sdf_coverage(width, height, self.feather, |x, y| {
+            let qx = (x - cx).abs() - hw;
+            let qy = (y - cy).abs() - hh;
+            let outside = Vector2::new(qx.max(0.0), qy.max(0.0)).norm();
+            outside + qx.max(qy).min(0.0)
+        })
+    }
+
+    fn inverted(&self) -> bool {
+        self.inverted
+    }
+
+    fn opacity(&self) -> f32 {
+        self.opacity
+    }
+
+    fn mode(&self) -> MaskMode {
+        MaskMode::from_name(&self.mode).unwrap_or(MaskMode::Add)
+    }
+}
+
+impl MaskShape for EllipseMask {
+    fn coverage(&self, width: u32, height: u32) -> Vec<f32> {
+        let (rx, ry) = (self.radius_x.abs(), self.radius_y.abs());
+        if rx <= 0.0 || ry <= 0.0 {
+            return vec![0.0; (width * height) as usize];
+        }
+        sdf_coverage(width, height, self.feather, |x, y| {
+            // Distance estimate from the normalised radius and its gradient
+            let (dx, dy) = (x - self.center_x, y - self.center_y);
+            let k0 = ((dx / rx).powi(2) + (dy / ry).powi(2)).sqrt();
+            let k1 = ((dx / (rx * rx)).powi(2) + (dy / (ry * ry)).powi(2)).sqrt();
+            if k1 > 0.0 {
+                k0 * (k0 - 1.0) / k1
+            } else {
+                -rx.min(ry)
+            }
+        })
+    }
+
+    fn inverted(&self) -> bool {
+        self.inverted
+    }
+
+    fn opacity(&self) -> f32 {
+        self.opacity
+    }
+
+    fn mode(&self) -> MaskMode {
+        MaskMode::from_name(&self.mode).unwrap_or(MaskMode::Add)
+    }
+}
+
+/// Any of the mask shapes, as accepted by `Node.add_mask`
+#[derive(Clone, FromPyObject)]
+pub enum AnyMask {
+    Bezier(Mask),
+    Rectangle(RectangleMask),
+    Ellipse(EllipseMask),
+}
+
+impl AnyMask {
+    /// The mask as it is at `time`; only bezier masks animate
+    pub fn at_time(&self, time: f32) -> Self {
+        match self {
+            AnyMask::Bezier(mask) => AnyMask::Bezier(mask.at_time(time)),
+            other => other.clone(),
+        }
+    }
+
+    pub fn shape(&self) -> &dyn MaskShape {
+        match self {
+            AnyMask::Bezier(mask) => mask,
+            AnyMask::Rectangle(mask) => mask,
+            AnyMask::Ellipse(mask) => mask,
+        }
+    }
+}
+
+/// Masks on a layer, combined top to bottom by their modes
+#[derive(Clone, Default)]
+pub struct MaskStack {
+    pub masks: Vec<AnyMask>,
+}
+
+impl MaskStack {
+    pub fn push(&mut self, mask: AnyMask) {
+        self.masks.push(mask);
+    }
+
+    pub fn clear(&mut self) {
+        self.masks.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.masks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.masks.is_empty()
+    }
+
+    /// Combined matte at `time`, or `None` when no mask is active and the
+    /// layer is fully visible. A stack that opens with a subtract or
+    /// intersect mask starts from full coverage, otherwise from empty.
+    pub fn matte(&self, width: u32, height: u32, time: f32) -> Option<AlphaMatte> {
+        let masks: Vec<AnyMask> = self.masks.iter().map(|m| m.at_time(time)).collect();
+        let active: Vec<&dyn MaskShape> = masks
+            .iter()
+            .map(AnyMask::shape)
+            .filter(|m| m.mode() != MaskMode::None)
+            .collect();
+        let start = match active.first()?.mode() {
+            MaskMode::Subtract | MaskMode::Intersect => 1.0,
+            _ => 0.0,
+        };
+        let mut matte = AlphaMatte::new(width, height, start);
+        for mask in active {
+            let mode = mask.mode();
+            let layer = mask.rasterize(width, height);
+            matte
+                .alpha
+                .par_iter_mut()
+                .zip(layer.alpha.par_iter())
+                .for_each(|(a, m)| *a = mode.combine(*a, *m).clamp(0.0, 1.0));
+        }
+        Some(matte)
+    }
+}
+
+/// Which property of a matte layer sets the visibility of the layer below
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrackMatteMode {
+    Alpha,
+    AlphaInverted,
+    Luma,
+    LumaInverted,
+}
+
+impl TrackMatteMode {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "alpha" => Some(TrackMatteMode::Alpha),
+            "alpha_inverted" => Some(TrackMatteMode::AlphaInverted),
+            "luma" => Some(TrackMatteMode::Luma),
+            "luma_inverted" => Some(TrackMatteMode::LumaInverted),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TrackMatteMode::Alpha => "alpha",
+            TrackMatteMode::AlphaInverted => "alpha_inverted",
+            TrackMatteMode::Luma => "luma",
+            TrackMatteMode::LumaInverted => "luma_inverted",
+        }
+    }
+}
+
+pub(crate) fn parse_track_matte_mode(mode: &str) -> PyResult<TrackMatteMode> {
+    TrackMatteMode::from_name(mode).ok_or_else(|| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Unknown track matte mode '{}'; expected alpha, alpha_inverted, luma or luma_inverted",
+            mode
+        ))
+    })
+}
+
+/// A node's track matte: the layer `source` (a node id) defines where the
+/// node is visible
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackMatte {
+    pub source: String,
+    pub mode: TrackMatteMode,
+}
+
+/// Alpha matte taken from a matte layer's RGBA frame. Luma mattes use the
+/// Rec.709 luma of the encoded colour, with transparent pixels counting as
+/// black.
+pub fn track_matte_alpha<P: Pixel>(
+    matte: &[P],
+    width: u32,
+    height: u32,
+    mode: TrackMatteMode,
+) -> AlphaMatte {
+    debug_assert_eq!(matte.len(), (width * height * 4) as usize);
+    let alpha = matte
+        .par_chunks(4)
+        .map(|px| {
+            let a = px[3].to_f32().clamp(0.0, 1.0);
+            let luma = || {
+                let y = 0.2126 * px[0].to_f32() + 0.7152 * px[1].to_f32() + 0.0722 * px[2].to_f32();
+                (y * a).clamp(0.0, 1.0)
+            };
+            match mode {
+                TrackMatteMode::Alpha => a,
+                TrackMatteMode::AlphaInverted => 1.0 - a,
+                TrackMatteMode::Luma => luma(),
+                TrackMatteMode::LumaInverted => 1.0 - luma(),
+            }
+        })
+        .collect();
+    AlphaMatte {
+        width,
+        height,
+        alpha,
+    }
+}
+
+/// Multiply the alpha of an RGBA8 layer by a track matte made from another
+/// RGBA8 layer of the same size
+#[pyfunction]
+pub fn apply_track_matte(
+    pixels: Vec<u8>,
+    matte_pixels: Vec<u8>,
+    width: u32,
+    height: u32,
+    mode: &str,
+) -> PyResult<Vec<u8>> {
+    let mode = parse_track_matte_mode(mode)?;
+    let mut layer = Frame::from_raw(width, height, pixels)?;
+    let matte = Frame::from_raw(width, height, matte_pixels)?;
+    track_matte_alpha(&matte.data, width, height, mode).apply(&mut layer.data);
+    Ok(layer.data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(x0: f32, y0: f32, x1: f32, y1: f32) -> Mask {
+        let mut mask = Mask::new();
+        mask.add_corner_point(x0, y0);
+        mask.add_corner_point(x1, y0);
+        mask.add_corner_point(x1, y1);
+        mask.add_corner_point(x0, y1);
+        mask
+    }
+
+    #[test]
+    fn square_mask_is_anti_aliased() {
+        let mask = square(2.0, 2.0, 6.5, 6.0);
+        let matte = mask.rasterize(8, 8);
+        assert_eq!(matte.get(3, 3), 1.0);
+        assert_eq!(matte.get(0, 0), 0.0);
+        assert!((matte.get(6, 3) - 0.5).abs() < 1e-5);
+        assert!(mask.contains(4.0, 4.0) && !mask.contains(7.0, 4.0));
+
+        let mut frame = vec![255u8; 8 * 8 * 4];
+        matte.apply(&mut frame);
+        assert_eq!(frame[(3 * 8 + 3) * 4 + 3], 255);
+        assert_eq!(frame[3], 0);
+    }
+
+    #[test]
+    fn curved_segments_are_flattened() {
+        // A circle of radius 10 from four bezier arcs
+        let k = 10.0 * 0.5523;
+        let mut mask = Mask::new();
+        mask.add_bezier_point(20.0, 10.0, 20.0 - k, 10.0, 20.0 + k, 10.0);
+        mask.add_bezier_point(30.0, 20.0, 30.0, 20.0 - k, 30.0, 20.0 + k);
+        mask.add_bezier_point(20.0, 30.0, 20.0 + k, 30.0, 20.0 - k, 30.0);
+        mask.add_bezier_point(10.0, 20.0, 10.0, 20.0 + k, 10.0, 20.0 - k);
+        let area: f32 = mask.rasterize(40, 40).alpha.iter().sum();
+        assert!((area - std::f32::consts::PI * 100.0).abs() < 3.0);
+    }
+
+    #[test]
+    fn expansion_and_feather_soften_the_edge() {
+        let mut mask = square(10.0, 10.0, 30.0, 30.0);
+        mask.expansion = 2.0;
+        assert!(mask.contains(9.0, 20.0));
+        mask.expansion = -2.0;
+        assert!(!mask.contains(11.0, 20.0));
+
+        mask.expansion = 0.0;
+        mask.feather = 8.0;
+        let matte = mask.rasterize(40, 40);
+        let edge = matte.get(10, 20);
+        assert!(edge > 0.3 && edge < 0.7);
+        assert!(matte.get(7, 20) > 0.0 && matte.get(13, 20) < 1.0);
+
+        // A wider feather on one side only
+        mask.feather = 0.0;
+        mask.set_point_feather(0, 12.0).unwrap();
+        mask.set_point_feather(3, 12.0).unwrap();
+        let matte = mask.rasterize(40, 40);
+        assert!(matte.get(7, 20) > 0.0);
+        assert_eq!(matte.get(32, 20), 0.0);
+    }
+
+    #[test]
+    fn path_keyframes_interpolate_and_follow_tracks() {
+        let mut mask = square(0.0, 0.0, 10.0, 10.0);
+        mask.add_path_keyframe(0.0, "linear".to_string()).unwrap();
+        mask.move_point(2, 20.0, 20.0).unwrap();
+        mask.add_path_keyframe(2.0, "linear".to_string()).unwrap();
+        mask.add_keyframe("opacity", 2.0, 0.5, "linear".to_string())
+            .unwrap();
+
+        let mid = mask.at_time(1.0);
+        assert_eq!(mid.points[2].anchor, Point2::new(15.0, 15.0));
+        assert_eq!(mid.points[0].anchor, Point2::new(0.0, 0.0));
+        assert_eq!(mask.at_time(5.0).opacity, 0.5);
+
+        mask.points.pop();
+        assert!(mask.add_path_keyframe(3.0, "linear".to_string()).is_err());
+        assert!(mask
+            .add_path_keyframe(f32::NAN, "linear".to_string())
+            .is_err());
+
+        let track = |frame, x, y| crate::motion_tracking::TrackPoint {
+            frame,
+            position: Point2::new(x, y),
+            confidence: 1.0,
+        };
+        let tracker = MotionTracker {
+            tracks: vec![track(0, 100.0, 100.0), track(10, 110.0, 104.0)],
+            start: None,
+            search_radius: 50.0,
+            pattern_size: 20.0,
+            min_confidence: 0.7,
+            smoothing_method: "gaussian".to_string(),
+            outlier_threshold: 3.0,
+        };
+        mask.follow_tracker(tracker.clone(), 0, 10.0);
+        // Half way through the track, half way through the shapes
+        let moved = mask.at_time(0.5);
+        assert_eq!(moved.points[0].anchor, Point2::new(5.0, 2.0));
+        assert!(moved.contains(8.0, 6.0) && !moved.contains(1.0, 1.0));
+
+        // A second track turns the shape a quarter and doubles it about the
+        // first, the same as a node bound to both
+        let fixed = MotionTracker {
+            tracks: vec![track(0, 10.0, 10.0), track(10, 10.0, 10.0)],
+            ..tracker.clone()
+        };
+        let orbit = MotionTracker {
+            tracks: vec![track(0, 20.0, 10.0), track(10, 10.0, 30.0)],
+            ..tracker
+        };
+        let mut shape = Mask::new();
+        shape.add_point(15.0, 10.0);
+        shape.follow_trackers(fixed, orbit, 0, 10.0);
+        let turned = shape.at_time(1.0).points[0].clone();
+        assert!((turned.anchor - Point2::new(10.0, 20.0)).norm() < 1e-4);
+        assert!((turned.handle_out - Point2::new(10.0, 40.0)).norm() < 1e-4);
+    }
+
+    #[test]
+    fn stacked_masks_combine_by_mode() {
+        let left = AnyMask::Rectangle(RectangleMask::new(0.0, 0.0, 6.0, 4.0));
+        let mut right = RectangleMask::new(4.0, 0.0, 6.0, 4.0);
+        let row = |stack: &MaskStack| -> Vec<f32> {
+            let matte = stack.matte(10, 4, 0.0).unwrap();
+            [1, 5, 8].iter().map(|&x| matte.get(x, 2)).collect()
+        };
+
+        let mut stack = MaskStack::default();
+        assert!(stack.matte(10, 4, 0.0).is_none());
+        stack.push(left.clone());
+        stack.push(AnyMask::Rectangle(right.clone()));
+        assert_eq!(row(&stack), vec![1.0, 1.0, 1.0]);
+
+        for (mode, expected) in [
+            ("subtract", [1.0, 0.0, 0.0]),
+            ("intersect", [0.0, 1.0, 0.0]),
+            ("difference", [1.0, 0.0, 1.0]),
+        ] {
+            right.set_mode(mode.to_string()).unwrap();
+            stack.masks[1] = AnyMask::Rectangle(right.clone());
+            assert_eq!(row(&stack), expected.to_vec(), "{}", mode);
+        }
+
+        // Half-opacity subtract leaves half of the overlap
+        right.opacity = 0.5;
+        right.set_mode("subtract".to_string()).unwrap();
+        stack.masks[1] = AnyMask::Rectangle(right.clone());
+        assert_eq!(row(&stack), vec![1.0, 0.5, 0.0]);
+        assert!(right.set_mode("multiply".to_string()).is_err());
+    }
+
+    #[test]
+    fn ellipse_feather_is_soft() {
+        let mut ellipse = EllipseMask::new(20.0, 20.0, 10.0, 6.0);
+        ellipse.feather = 6.0;
+        let matte = ellipse.rasterize(40, 40);
+        assert_eq!(matte.get(20, 20), 1.0);
+        let edge = matte.get(29, 19);
+        assert!(edge > 0.2 && edge < 0.8);
+        assert_eq!(matte.get(0, 0), 0.0);
+    }
+}
@@ -1,6 +1,12 @@
-use nalgebra::Point2;
+use crate::frame::{from_f32_vec, to_f32_vec, BitDepth, Frame, FrameError, Pixel};
+use crate::geometry::Homography;
+use crate::sampling::{warp_frame, EdgeMode, Interpolation};
+use nalgebra::{Matrix4, Point2, Vector2, Vector4};
 use pyo3::prelude::*;
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
 
 #[derive(Debug, Clone)]
 pub struct TrackPoint {
@@ -9,14 +15,312 @@ pub struct TrackPoint {
     pub confidence: f32,
 }
 
+#[derive(Debug, Error)]
+pub enum TrackingError {
+    #[error("No start point; call track_point first")]
+    NoStartPoint,
+    #[error("Start frame {frame} is outside the {frames} frames given")]
+    StartOutOfRange { frame: u32, frames: usize },
+    #[error("The pattern around ({x}, {y}) has no detail to track")]
+    FlatPattern { x: f32, y: f32 },
+    #[error("Only {0} textured features inside the plane; at least 4 are needed")]
+    TooFewFeatures(usize),
+    #[error("Unknown tracking direction '{0}'; expected forward, backward or both")]
+    Direction(String),
+    #[error("Unknown stabilization method '{0}'; expected position, rotation, scale or all")]
+    Method(String),
+    #[error("Need at least 2 frames to stabilize, found {0}")]
+    TooFewFrames(usize),
+    #[error("{0}")]
+    UnsupportedInput(String),
+    #[error("{path}: {source}")]
+    Io {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error(transparent)]
+    Frame(#[from] FrameError),
+}
+
+impl From<TrackingError> for pyo3::PyErr {
+    fn from(err: TrackingError) -> Self {
+        match err {
+            TrackingError::Frame(err) => err.into(),
+            TrackingError::Io { .. } => {
+                pyo3::PyErr::new::<pyo3::exceptions::PyIOError, _>(err.to_string())
+            }
+            _ => pyo3::PyErr::new::<pyo3::exceptions::PyValueError, _>(err.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrackDirection {
+    Forward,
+    Backward,
+    Both,
+}
+
+impl TrackDirection {
+    pub fn from_name(name: &str) -> Result<Self, TrackingError> {
+        match name {
+            "forward" => Ok(TrackDirection::Forward),
+            "backward" => Ok(TrackDirection::Backward),
+            "both" => Ok(TrackDirection::Both),
+            _ => Err(TrackingError::Direction(name.to_string())),
+        }
+    }
+
+    /// Frame indices visited from `start` in each pass
+    fn runs(&self, start: usize, frames: usize) -> Vec<Vec<usize>> {
+        let forward: Vec<usize> = (start + 1..frames).collect();
+        let backward: Vec<usize> = (0..start.min(frames)).rev().collect();
+        match self {
+            TrackDirection::Forward => vec![forward],
+            TrackDirection::Backward => vec![backward],
+            TrackDirection::Both => vec![forward, backward],
+        }
+    }
+}
+
+fn frames_from_bytes(
+    frames: Vec<Vec<u8>>,
+    width: u32,
+    height: u32,
+) -> Result<Vec<Frame<u8>>, FrameError> {
+    frames
+        .into_iter()
+        .map(|pixels| Frame::from_raw(width, height, pixels))
+        .collect()
+}
+
+/// Small deterministic generator so RANSAC results are repeatable
+pub(crate) struct SampleRng(u64);
+
+impl SampleRng {
+    pub(crate) fn new(seed: u64) -> Self {
+        SampleRng(seed.wrapping_mul(6364136223846793005).wrapping_add(1))
+    }
+
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((self.0 >> 33) as usize) % n.max(1)
+    }
+
+    /// `k` distinct indices below `n`
+    pub(crate) fn distinct(&mut self, n: usize, k: usize) -> Vec<usize> {
+        let mut picked: Vec<usize> = Vec::with_capacity(k);
+        while picked.len() < k.min(n) {
+            let i = self.below(n);
+            if !picked.contains(&i) {
+                picked.push(i);
+            }
+        }
+        picked
+    }
+}
+
+/// Single-channel (Rec.709 luma) image used for matching
+#[derive(Debug, Clone)]
+pub struct LumaPlane {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<f32>,
+}
+
+impl LumaPlane {
+    pub fn from_frame<P: Pixel>(frame: &Frame<P>) -> Self {
+        let data = frame
+            .data
+            .par_chunks(4)
+            .map(|px| 0.2126 * px[0].to_f32() + 0.7152 * px[1].to_f32() + 0.0722 * px[2].to_f32())
+            .collect();
+        Self {
+            width: frame.width,
+            height: frame.height,
+            data,
+        }
+    }
+
+    pub(crate) fn get(&self, x: i32, y: i32) -> f32 {
+        let x = x.clamp(0, self.width as i32 - 1) as u32;
+        let y = y.clamp(0, self.height as i32 - 1) as u32;
+        self.data[(y * self.width + x) as usize]
+    }
+
+    /// Bilinear sample; pixel centres sit on integer coordinates
+    pub fn sample(&self, x: f32, y: f32) -> f32 {
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i32, y0 as i32);
+        let top = self.get(x0, y0) * (1.0 - fx) + self.get(x0 + 1, y0) * fx;
+        let bottom = self.get(x0, y0 + 1) * (1.0 - fx) + self.get(x0 + 1, y0 + 1) * fx;
+        top * (1.0 - fy) + bottom * fy
+    }
+}
+
+/// Zero-mean, unit-norm square patch for normalised cross-correlation
+#[derive(Debug, Clone)]
+pub struct Template {
+    pub half: i32,
+    values: Vec<f32>,
+}
+
+impl Template {
+    /// Patch of side `2 * half + 1` centred on `centre`, or `None` when it
+    /// is flat and cannot be matched
+    pub fn extract(plane: &LumaPlane, centre: Point2<f32>, half: i32) -> Option<Self> {
+        let mut values = Vec::with_capacity(((2 * half + 1) * (2 * half + 1)) as usize);
+        for dy in -half..=half {
+            for dx in -half..=half {
+                values.push(plane.sample(centre.x + dx as f32, centre.y + dy as f32));
+            }
+        }
+        let mean = values.iter().sum::<f32>() / values.len() as f32;
+        values.iter_mut().for_each(|v| *v -= mean);
+        let norm = values.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if norm < 1e-4 {
+            return None;
+        }
+        values.iter_mut().for_each(|v| *v /= norm);
+        Some(Self { half, values })
+    }
+
+    /// NCC with the patch centred on pixel (cx, cy); `None` if the patch
+    /// leaves the image
+    fn ncc_at(&self, plane: &LumaPlane, cx: i32, cy: i32) -> Option<f32> {
+        let h = self.half;
+        if cx - h < 0 || cy - h < 0 || cx + h >= plane.width as i32 || cy + h >= plane.height as i32
+        {
+            return None;
+        }
+        let (mut dot, mut sum, mut sum2) = (0.0, 0.0, 0.0);
+        let mut t = self.values.iter();
+        for y in cy - h..=cy + h {
+            let row = (y as u32 * plane.width) as usize;
+            for x in cx - h..=cx + h {
+                let p = plane.data[row + x as usize];
+                // Template values are zero-mean, so the patch mean drops out
+                dot += p * t.next().unwrap();
+                sum += p;
+                sum2 += p * p;
+            }
+        }
+        let n = self.values.len() as f32;
+        let variance = sum2 - sum * sum / n;
+        Some(if variance > 1e-8 {
+            dot / variance.sqrt()
+        } else {
+            0.0
+        })
+    }
+
+    /// Zero-mean, unit-norm patch sampled at a sub-pixel centre, with its
+    /// norm before normalising
+    fn sample_patch(&self, plane: &LumaPlane, centre: Point2<f32>) -> (Vec<f32>, f32) {
+        let h = self.half;
+        let mut values = Vec::with_capacity(self.values.len());
+        for dy in -h..=h {
+            for dx in -h..=h {
+                values.push(plane.sample(centre.x + dx as f32, centre.y + dy as f32));
+            }
+        }
+        let mean = values.iter().sum::<f32>() / values.len() as f32;
+        values.iter_mut().for_each(|v| *v -= mean);
+        let norm = values.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if norm > 1e-6 {
+            values.iter_mut().for_each(|v| *v /= norm);
+        }
+        (values, norm)
+    }
+
+    /// Lucas-Kanade refinement of a match position on the normalised
+    /// patch, which stays accurate between pixel centres
+    fn refine(&self, plane: &LumaPlane, start: Point2<f32>) -> Point2<f32> {
+        let h = self.half;
+        let mut position = start;
+        for _ in 0..10 {
+            let (patch, norm) = self.sample_patch(plane, position);
+            if norm <= 1e-6 {
+                break;
+            }
+            let (mut gxx, mut gxy, mut gyy, mut bx, mut by) = (0.0, 0.0, 0.0, 0.0, 0.0);
+            let mut i = 0;
+            for dy in -h..=h {
+                for dx in -h..=h {
+                    let (x, y) = (position.x + dx as f32, position.y + dy as f32);
+                    let gx = (plane.sample(x + 0.5, y) - plane.sample(x - 0.5, y)) / norm;
+                    let gy = (plane.sample(x, y + 0.5) - plane.sample(x, y - 0.5)) / norm;
+                    let residual = patch[i] - self.values[i];
+                    gxx += gx * gx;
+                    gxy += gx * gy;
+                    gyy += gy * gy;
+                    bx += gx * residual;
+                    by += gy * residual;
+                    i += 1;
+                }
+            }
+            let det = gxx * gyy - gxy * gxy;
+            if det.abs() < 1e-12 {
+                break;
+            }
+            let step = Vector2::new(-(gyy * bx - gxy * by) / det, -(gxx * by - gxy * bx) / det);
+            // Stay within a pixel of the integer peak
+            position = start + (position + step - start).map(|v| v.clamp(-1.0, 1.0));
+            if step.norm() < 0.005 {
+                break;
+            }
+        }
+        position
+    }
+
+    /// Best match within `radius` px of `around`: an exhaustive integer
+    /// search followed by sub-pixel refinement. Returns the position and its
+    /// NCC score (-1 to 1).
+    pub fn search(
+        &self,
+        plane: &LumaPlane,
+        around: Point2<f32>,
+        radius: i32,
+    ) -> Option<(Point2<f32>, f32)> {
+        let (ax, ay) = (around.x.round() as i32, around.y.round() as i32);
+        let (x, y, best) = (-radius..=radius)
+            .into_par_iter()
+            .flat_map_iter(|dy| (-radius..=radius).map(move |dx| (ax + dx, ay + dy)))
+            .filter_map(|(x, y)| self.ncc_at(plane, x, y).map(|s| (x, y, s)))
+            .max_by(|a, b| a.2.total_cmp(&b.2))?;
+        let position = self.refine(plane, Point2::new(x as f32, y as f32));
+        let (patch, _) = self.sample_patch(plane, position);
+        let score: f32 = patch.iter().zip(&self.values).map(|(p, t)| p * t).sum();
+        // Keep the integer match if refinement drifted somewhere worse
+        Some(if score >= best {
+            (position, score)
+        } else {
+            (Point2::new(x as f32, y as f32), best)
+        })
+    }
+}
+
 #[pyclass]
 #[derive(Clone)]
 pub struct MotionTracker {
     pub tracks: Vec<TrackPoint>,
+    /// Point and frame tracking starts from
+    pub start: Option<TrackPoint>,
     #[pyo3(get, set)]
-    pub search_radius: f32,
+    pub search_radius: f32, // px around the predicted position
     #[pyo3(get, set)]
-    pub pattern_size: f32,
+    pub pattern_size: f32, // px, side of the matched patch
+    #[pyo3(get, set)]
+    pub min_confidence: f32, // tracking stops below this NCC score
+    #[pyo3(get, set)]
+    pub smoothing_method: String, // moving_average, gaussian, savitzky_golay, one_euro
+    #[pyo3(get, set)]
+    pub outlier_threshold: f32, // robust deviations; 0 keeps every point
 }
 
 #[pymethods]
@@ -25,34 +329,867 @@ impl MotionTracker {
     fn new() -> Self {
         Self {
             tracks: Vec::new(),
+            start: None,
             search_radius: 50.0,
             pattern_size: 20.0,
+            min_confidence: 0.7,
+            smoothing_method: "gaussian".to_string(),
+            outlier_threshold: 3.0,
         }
     }
 
+    /// Set the point to track, replacing any previous track
     fn track_point(&mut self, start_x: f32, start_y: f32, start_frame: u32) -> PyResult<()> {
-        // Placeholder for actual tracking implementation
-        self.tracks.push(TrackPoint {
+        let start = TrackPoint {
             frame: start_frame,
             position: Point2::new(start_x, start_y),
             confidence: 1.0,
-        });
+        };
+        self.tracks = vec![start.clone()];
+        self.start = Some(start);
         Ok(())
     }
 
+    /// Track the start point through RGBA8 `frames` (frame numbers are list
+    /// indices) "forward", "backward" or "both". Returns how many frames
+    /// hold a tracked position afterwards.
+    #[pyo3(name = "track")]
+    fn py_track(
+        &mut self,
+        frames: Vec<Vec<u8>>,
+        width: u32,
+        height: u32,
+        direction: &str,
+    ) -> PyResult<usize> {
+        let direction = TrackDirection::from_name(direction)?;
+        let frames = frames_from_bytes(frames, width, height)?;
+        self.track(&frames, direction)?;
+        Ok(self.tracks.len())
+    }
+
+    fn get_confidence_at_frame(&self, frame: u32) -> Option<f32> {
+        self.tracks
+            .iter()
+            .find(|t| t.frame == frame)
+            .map(|t| t.confidence)
+    }
+
+    /// Every tracked frame as (frame, x, y, confidence)
+    fn get_track(&self) -> Vec<(u32, f32, f32, f32)> {
+        self.tracks
+            .iter()
+            .map(|t| (t.frame, t.position.x, t.position.y, t.confidence))
+            .collect()
+    }
+
+    /// Position at `frame`, interpolated across untracked frames inside the
+    /// track; `None` before the first or after the last tracked frame
     fn get_position_at_frame(&self, frame: u32) -> Option<(f32, f32)> {
+        let first = self.tracks.iter().map(|t| t.frame).min()?;
+        let last = self.tracks.iter().map(|t| t.frame).max()?;
+        if frame < first || frame > last {
+            return None;
+        }
+        self.position_at(frame as f32).map(|p| (p.x, p.y))
+    }
+
+    /// Filter the track with `smoothing_method`. `strength` runs from 0
+    /// (unchanged) to 1 (a 15-frame radius, or the slowest One-Euro
+    /// response). Points are weighted by confidence and outliers are replaced
+    /// by the estimate from their neighbours.
+    fn smooth_tracks(&mut self, strength: f32) -> PyResult<()> {
+        let method = SmoothingMethod::from_name(&self.smoothing_method).ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Unknown smoothing method '{}'; expected moving_average, gaussian, savitzky_golay or one_euro",
+                self.smoothing_method
+            ))
+        })?;
+        self.tracks = smooth_track(&self.tracks, method, strength, self.outlier_threshold);
+        Ok(())
+    }
+}
+
+impl MotionTracker {
+    /// Follow the start point through `frames` by normalised cross-correlation
+    /// against the pattern around it in the start frame.
+    ///
+    /// Each frame is searched within `search_radius` of the position predicted
+    /// from the last step's motion. A direction stops at the first frame whose
+    /// best score is under `min_confidence` (occlusion or the point leaving
+    /// the image), so the track never jumps to an unrelated match.
+    pub fn track<P: Pixel>(
+        &mut self,
+        frames: &[Frame<P>],
+        direction: TrackDirection,
+    ) -> Result<(), TrackingError> {
+        let start = self.start.clone().ok_or(TrackingError::NoStartPoint)?;
+        let first = start.frame as usize;
+        let Some(start_frame) = frames.get(first) else {
+            return Err(TrackingError::StartOutOfRange {
+                frame: start.frame,
+                frames: frames.len(),
+            });
+        };
+        let half = (self.pattern_size / 2.0).round().max(1.0) as i32;
+        let template = Template::extract(&LumaPlane::from_frame(start_frame), start.position, half)
+            .ok_or(TrackingError::FlatPattern {
+                x: start.position.x,
+                y: start.position.y,
+            })?;
+
+        let mut found = vec![start.clone()];
+        for run in direction.runs(first, frames.len()) {
+            let mut position = start.position;
+            let mut velocity = Vector2::zeros();
+            for index in run {
+                let plane = LumaPlane::from_frame(&frames[index]);
+                let radius = self.search_radius.round().max(1.0) as i32;
+                let Some((next, score)) = template.search(&plane, position + velocity, radius)
+                else {
+                    break;
+                };
+                if score < self.min_confidence {
+                    break;
+                }
+                velocity = next - position;
+                position = next;
+                found.push(TrackPoint {
+                    frame: index as u32,
+                    position,
+                    confidence: score.clamp(0.0, 1.0),
+                });
+            }
+        }
+
+        self.tracks
+            .retain(|t| !found.iter().any(|f| f.frame == t.frame));
+        self.tracks.extend(found);
+        self.tracks.sort_by_key(|t| t.frame);
+        Ok(())
+    }
+
+    /// Tracked position at a (fractional) frame, interpolated between the
+    /// nearest tracked frames and held beyond either end
+    pub fn position_at(&self, frame: f32) -> Option<Point2<f32>> {
+        let before = self
+            .tracks
+            .iter()
+            .filter(|t| t.frame as f32 <= frame)
+            .max_by_key(|t| t.frame);
+        let after = self
+            .tracks
+            .iter()
+            .filter(|t| t.frame as f32 >= frame)
+            .min_by_key(|t| t.frame);
+        match (before, after) {
+            (Some(a), Some(b)) if b.frame > a.frame => {
+                let t = (frame - a.frame as f32) / (b.frame - a.frame) as f32;
+                Some(a.position + (b.position - a.position) * t)
+            }
+            (Some(a), _) => Some(a.position),
+            (None, Some(b)) => Some(b.position),
+            (None, None) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmoothingMethod {
+    MovingAverage,
+    Gaussian,
+    /// Local quadratic fit; keeps peaks and accelerations that averaging
+    /// flattens
+    SavitzkyGolay,
+    /// Adaptive low-pass: heavy smoothing when still, little lag when fast
+    OneEuro,
+}
+
+impl SmoothingMethod {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "moving_average" | "average" => Some(SmoothingMethod::MovingAverage),
+            "gaussian" => Some(SmoothingMethod::Gaussian),
+            "savitzky_golay" => Some(SmoothingMethod::SavitzkyGolay),
+            "one_euro" => Some(SmoothingMethod::OneEuro),
+            _ => None,
+        }
+    }
+}
+
+/// Largest smoothing window radius, in frames, at strength 1
+const MAX_SMOOTHING_RADIUS: f32 = 15.0;
+
+/// Smooth a scalar series sampled at (possibly gapped) `frames`, weighting
+/// each sample by `weights`. Window sizes are measured in frames, so gaps do
+/// not pull distant samples together.
+pub fn smooth_series(
+    frames: &[f32],
+    values: &[f32],
+    weights: &[f32],
+    method: SmoothingMethod,
+    strength: f32,
+) -> Vec<f32> {
+    let strength = strength.clamp(0.0, 1.0);
+    if strength == 0.0 || values.len() < 2 {
+        return values.to_vec();
+    }
+    let radius = strength * MAX_SMOOTHING_RADIUS;
+    let window =
+        |i: usize| (0..values.len()).filter(move |&j| (frames[j] - frames[i]).abs() <= radius);
+    match method {
+        SmoothingMethod::MovingAverage | SmoothingMethod::Gaussian => {
+            let sigma = (radius / 2.0).max(0.5);
+            (0..values.len())
+                .map(|i| {
+                    let (mut sum, mut total) = (0.0, 0.0);
+                    for j in window(i) {
+                        let df = frames[j] - frames[i];
+                        let w = match method {
+                            SmoothingMethod::Gaussian => (-df * df / (2.0 * sigma * sigma)).exp(),
+                            _ => 1.0,
+                        } * weights[j];
+                        sum += w * values[j];
+                        total += w;
+                    }
+                    if total > 0.0 {
+                        sum / total
+                    } else {
+                        values[i]
+                    }
+                })
+                .collect()
+        }
+        SmoothingMethod::SavitzkyGolay => (0..values.len())
+            .map(|i| {
+                // Weighted least-squares quadratic in frame offset; its value
+                // at offset 0 is the smoothed sample
+                let mut ata = nalgebra::Matrix3::<f64>::zeros();
+                let mut atb = nalgebra::Vector3::<f64>::zeros();
+                let mut total = 0.0;
+                for j in window(i) {
+                    let x = (frames[j] - frames[i]) as f64;
+                    let w = weights[j] as f64;
+                    let row = nalgebra::Vector3::new(1.0, x, x * x);
+                    ata += row * row.transpose() * w;
+                    atb += row * (w * values[j] as f64);
+                    total += w;
+                }
+                match ata.try_inverse() {
+                    Some(inverse) if ata.determinant().abs() > 1e-9 => (inverse * atb)[0] as f32,
+                    _ if total > 0.0 => (atb[0] / total) as f32,
+                    _ => values[i],
+                }
+            })
+            .collect(),
+        SmoothingMethod::OneEuro => {
+            // Cut-offs in cycles per frame
+            let min_cutoff = 0.25 * (1.0 - strength).powi(2) + 0.005;
+            let (beta, derivative_cutoff) = (0.1, 0.1);
+            let alpha = |cutoff: f32, te: f32| {
+                1.0 / (1.0 + 1.0 / (2.0 * std::f32::consts::PI * cutoff * te))
+            };
+            let mut out = Vec::with_capacity(values.len());
+            let (mut x, mut dx) = (values[0], 0.0);
+            out.push(x);
+            for i in 1..values.len() {
+                let te = (frames[i] - frames[i - 1]).max(1.0);
+                let raw_dx = (values[i] - x) / te;
+                dx += alpha(derivative_cutoff, te) * (raw_dx - dx);
+                let a = alpha(min_cutoff + beta * dx.abs(), te) * weights[i].clamp(0.0, 1.0);
+                x += a * (values[i] - x);
+                out.push(x);
+            }
+            out
+        }
+    }
+}
+
+fn median(values: &mut [f32]) -> f32 {
+    values.sort_by(|a, b| a.total_cmp(b));
+    let n = values.len();
+    if n % 2 == 1 {
+        values[n / 2]
+    } else {
+        (values[n / 2 - 1] + values[n / 2]) / 2.0
+    }
+}
+
+/// Value at `frame` of a Theil-Sen line through `samples` of (frame, value):
+/// the median pairwise slope and the median intercept, so a few wild
+/// samples cannot tilt it
+fn theil_sen_at(samples: &[(f32, f32)], frame: f32) -> f32 {
+    let mut slopes = Vec::new();
+    for (i, a) in samples.iter().enumerate() {
+        for b in &samples[i + 1..] {
+            if b.0 != a.0 {
+                slopes.push((b.1 - a.1) / (b.0 - a.0));
+            }
+        }
+    }
+    let slope = if slopes.is_empty() {
+        0.0
+    } else {
+        median(&mut slopes)
+    };
+    let mut intercepts: Vec<f32> = samples
+        .iter()
+        .map(|(f, v)| v - slope * (f - frame))
+        .collect();
+    median(&mut intercepts)
+}
+
+/// Points further from the robust local trend of their neighbours than
+/// `threshold` robust standard deviations (scaled MAD), with the position
+/// that trend predicts for them
+fn track_outliers(points: &[TrackPoint], radius: f32, threshold: f32) -> Vec<(usize, Point2<f32>)> {
+    if threshold <= 0.0 || points.len() < 4 {
+        return Vec::new();
+    }
+    let radius = radius.max(2.0);
+    let predictions: Vec<Point2<f32>> = points
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let neighbours: Vec<&TrackPoint> = points
+                .iter()
+                .enumerate()
+                .filter(|(j, q)| *j != i && (q.frame as f32 - p.frame as f32).abs() <= radius)
+                .map(|(_, q)| q)
+                .collect();
+            if neighbours.is_empty() {
+                return p.position;
+            }
+            let xs: Vec<(f32, f32)> = neighbours
+                .iter()
+                .map(|q| (q.frame as f32, q.position.x))
+                .collect();
+            let ys: Vec<(f32, f32)> = neighbours
+                .iter()
+                .map(|q| (q.frame as f32, q.position.y))
+                .collect();
+            let frame = p.frame as f32;
+            Point2::new(theil_sen_at(&xs, frame), theil_sen_at(&ys, frame))
+        })
+        .collect();
+    let residuals: Vec<f32> = points
+        .iter()
+        .zip(&predictions)
+        .map(|(p, q)| (p.position - q).norm())
+        .collect();
+    let mad = 1.4826 * median(&mut residuals.clone());
+    // Never reject sub-pixel wobble on an otherwise perfect track
+    let limit = (threshold * mad).max(0.5);
+    residuals
+        .iter()
+        .enumerate()
+        .filter(|(_, r)| **r > limit)
+        .map(|(i, _)| (i, predictions[i]))
+        .collect()
+}
+
+/// Smoothed copy of a track. Outliers are first moved to the position their
+/// neighbours imply, then confidence weights each point in the filter.
+pub fn smooth_track(
+    points: &[TrackPoint],
+    method: SmoothingMethod,
+    strength: f32,
+    outlier_threshold: f32,
+) -> Vec<TrackPoint> {
+    let mut points = points.to_vec();
+    points.sort_by_key(|t| t.frame);
+    let radius = strength.clamp(0.0, 1.0) * MAX_SMOOTHING_RADIUS;
+    for (i, position) in track_outliers(&points, radius, outlier_threshold) {
+        points[i].position = position;
+    }
+    let weights: Vec<f32> = points.iter().map(|p| p.confidence.max(0.0)).collect();
+    let frames: Vec<f32> = points.iter().map(|p| p.frame as f32).collect();
+    let xs: Vec<f32> = points.iter().map(|p| p.position.x).collect();
+    let ys: Vec<f32> = points.iter().map(|p| p.position.y).collect();
+    let xs = smooth_series(&frames, &xs, &weights, method, strength);
+    let ys = smooth_series(&frames, &ys, &weights, method, strength);
+    for (p, (x, y)) in points.iter_mut().zip(xs.into_iter().zip(ys)) {
+        p.position = Point2::new(x, y);
+    }
+    points
+}
+
+/// Homography taking the start frame's plane to the plane in `frame`
+#[derive(Debug, Clone)]
+pub struct PlaneTrack {
+    pub frame: u32,
+    pub homography: Homography,
+    pub confidence: f32,
+}
+
+/// Tracks a planar surface (a screen, a sign) given by four corners.
+///
+/// A grid of features inside the corners is matched frame to frame and a
+/// homography is fitted to them with RANSAC, so features that are occluded
+/// or leave the plane are voted out instead of bending the result.
+#[pyclass]
+#[derive(Clone)]
+pub struct PlanarTracker {
+    /// Plane corners in the start frame, clockwise from top-left
+    pub corners: Option<[Point2<f32>; 4]>,
+    pub start_frame: u32,
+    pub tracks: Vec<PlaneTrack>,
+    #[pyo3(get, set)]
+    pub search_radius: f32, // px around the predicted position
+    #[pyo3(get, set)]
+    pub pattern_size: f32, // px, side of each feature patch
+    #[pyo3(get, set)]
+    pub features: u32, // grid points per side inside the plane
+    #[pyo3(get, set)]
+    pub min_confidence: f32, // tracking stops below this mean inlier score
+}
+
+/// Reprojection error (px) under which a feature counts as an inlier
+const PLANE_INLIER_ERROR: f32 = 2.0;
+const PLANE_RANSAC_ITERATIONS: usize = 200;
+
+#[pymethods]
+impl PlanarTracker {
+    #[new]
+    fn new() -> Self {
+        Self {
+            corners: None,
+            start_frame: 0,
+            tracks: Vec::new(),
+            search_radius: 30.0,
+            pattern_size: 15.0,
+            features: 6,
+            min_confidence: 0.5,
+        }
+    }
+
+    /// Set the plane as four (x, y) corners, clockwise from top-left, in
+    /// `start_frame`. Replaces any previous track.
+    fn set_corners(&mut self, corners: Vec<(f32, f32)>, start_frame: u32) -> PyResult<()> {
+        let corners: [(f32, f32); 4] = corners.try_into().map_err(|c: Vec<_>| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "A plane needs 4 corners, got {}",
+                c.len()
+            ))
+        })?;
+        self.corners = Some(corners.map(|(x, y)| Point2::new(x, y)));
+        self.start_frame = start_frame;
+        self.tracks = vec![PlaneTrack {
+            frame: start_frame,
+            homography: Homography::identity(),
+            confidence: 1.0,
+        }];
+        Ok(())
+    }
+
+    /// Track the plane through RGBA8 `frames` (frame numbers are list
+    /// indices) "forward", "backward" or "both". Returns how many frames
+    /// hold a tracked plane afterwards.
+    #[pyo3(name = "track")]
+    fn py_track(
+        &mut self,
+        frames: Vec<Vec<u8>>,
+        width: u32,
+        height: u32,
+        direction: &str,
+    ) -> PyResult<usize> {
+        let direction = TrackDirection::from_name(direction)?;
+        let frames = frames_from_bytes(frames, width, height)?;
+        self.track(&frames, direction)?;
+        Ok(self.tracks.len())
+    }
+
+    /// The four corners in `frame`, clockwise from top-left
+    fn get_corners_at_frame(&self, frame: u32) -> Option<Vec<(f32, f32)>> {
+        self.corners_at(frame)
+            .map(|corners| corners.iter().map(|p| (p.x, p.y)).collect())
+    }
+
+    /// Row-major 3x3 homography from the start frame to `frame`
+    fn get_homography_at_frame(&self, frame: u32) -> Option<Vec<f32>> {
         self.tracks
             .iter()
             .find(|t| t.frame == frame)
-            .map(|t| (t.position.x, t.position.y))
+            .map(|t| t.homography.to_row_major())
     }
 
-    fn smooth_tracks(&mut self, _strength: f32) {
-        // Smooth tracking data to reduce jitter
-        // Implementation would use moving average or similar
+    fn get_confidence_at_frame(&self, frame: u32) -> Option<f32> {
+        self.tracks
+            .iter()
+            .find(|t| t.frame == frame)
+            .map(|t| t.confidence)
     }
 }
 
+impl PlanarTracker {
+    pub fn corners_at(&self, frame: u32) -> Option<[Point2<f32>; 4]> {
+        let corners = self.corners?;
+        let track = self.tracks.iter().find(|t| t.frame == frame)?;
+        let mapped: Vec<Point2<f32>> = corners
+            .iter()
+            .filter_map(|p| track.homography.apply(*p))
+            .collect();
+        mapped.try_into().ok()
+    }
+
+    /// Feature grid inside the corners, spread bilinearly over the quad
+    fn feature_grid(&self, corners: &[Point2<f32>; 4]) -> Vec<Point2<f32>> {
+        let n = self.features.max(2);
+        let mut points = Vec::with_capacity((n * n) as usize);
+        for j in 0..n {
+            for i in 0..n {
+                let (u, v) = ((i as f32 + 0.5) / n as f32, (j as f32 + 0.5) / n as f32);
+                let top = corners[0].coords.lerp(&corners[1].coords, u);
+                let bottom = corners[3].coords.lerp(&corners[2].coords, u);
+                points.push(Point2::from(top.lerp(&bottom, v)));
+            }
+        }
+        points
+    }
+
+    pub fn track<P: Pixel>(
+        &mut self,
+        frames: &[Frame<P>],
+        direction: TrackDirection,
+    ) -> Result<(), TrackingError> {
+        let corners = self.corners.ok_or(TrackingError::NoStartPoint)?;
+        let first = self.start_frame as usize;
+        let Some(start_frame) = frames.get(first) else {
+            return Err(TrackingError::StartOutOfRange {
+                frame: self.start_frame,
+                frames: frames.len(),
+            });
+        };
+        let half = (self.pattern_size / 2.0).round().max(1.0) as i32;
+        let radius = self.search_radius.round().max(1.0) as i32;
+        let start_plane = LumaPlane::from_frame(start_frame);
+        let starts: Vec<Point2<f32>> = self
+            .feature_grid(&corners)
+            .into_iter()
+            .filter(|p| Template::extract(&start_plane, *p, half).is_some())
+            .collect();
+        if starts.len() < 4 {
+            return Err(TrackingError::TooFewFeatures(starts.len()));
+        }
+
+        let mut found = vec![PlaneTrack {
+            frame: self.start_frame,
+            homography: Homography::identity(),
+            confidence: 1.0,
+        }];
+        for run in direction.runs(first, frames.len()) {
+            let mut previous = start_plane.clone();
+            let mut homography = Homography::identity();
+            let mut before = homography;
+            for index in run {
+                let plane = LumaPlane::from_frame(&frames[index]);
+                // Match each feature from where it was in the previous frame,
+                // predicting its next position from the last step
+                let matches: Vec<(Point2<f32>, Point2<f32>, f32)> = starts
+                    .par_iter()
+                    .filter_map(|s| {
+                        let at = homography.apply(*s)?;
+                        let velocity = at - before.apply(*s)?;
+                        let template = Template::extract(&previous, at, half)?;
+                        let (next, score) = template.search(&plane, at + velocity, radius)?;
+                        (score >= self.min_confidence).then_some((*s, next, score))
+                    })
+                    .collect();
+                let Some((fitted, inliers)) = fit_homography_ransac(&matches, index as u64) else {
+                    break;
+                };
+                let confidence =
+                    inliers.iter().map(|&i| matches[i].2).sum::<f32>() / starts.len() as f32;
+                if confidence < self.min_confidence {
+                    break;
+                }
+                before = homography;
+                homography = fitted;
+                previous = plane;
+                found.push(PlaneTrack {
+                    frame: index as u32,
+                    homography,
+                    confidence,
+                });
+            }
+        }
+
+        self.tracks
+            .retain(|t| !found.iter().any(|f| f.frame == t.frame));
+        self.tracks.extend(found);
+        self.tracks.sort_by_key(|t| t.frame);
+        Ok(())
+    }
+}
+
+/// Robust homography from (source, target, score) matches: the 4-point
+/// sample with the most inliers wins and is refitted on all of them.
+/// Returns the fit and the indices of its inliers.
+fn fit_homography_ransac(
+    matches: &[(Point2<f32>, Point2<f32>, f32)],
+    seed: u64,
+) -> Option<(Homography, Vec<usize>)> {
+    if matches.len() < 4 {
+        return None;
+    }
+    let inliers_of = |h: &Homography| -> Vec<usize> {
+        matches
+            .iter()
+            .enumerate()
+            .filter(|(_, (s, t, _))| {
+                h.apply(*s)
+                    .is_some_and(|p| (p - t).norm() < PLANE_INLIER_ERROR)
+            })
+            .map(|(i, _)| i)
+            .collect()
+    };
+    let mut rng = SampleRng::new(seed);
+    let mut best: Vec<usize> = Vec::new();
+    for _ in 0..PLANE_RANSAC_ITERATIONS {
+        let sample = rng.distinct(matches.len(), 4);
+        let src: Vec<_> = sample.iter().map(|&i| matches[i].0).collect();
+        let dst: Vec<_> = sample.iter().map(|&i| matches[i].1).collect();
+        let Some(h) = Homography::from_points(&src, &dst) else {
+            continue;
+        };
+        let inliers = inliers_of(&h);
+        if inliers.len() > best.len() {
+            best = inliers;
+            if best.len() == matches.len() {
+                break;
+            }
+        }
+    }
+    if best.len() < 4 {
+        return None;
+    }
+    let src: Vec<_> = best.iter().map(|&i| matches[i].0).collect();
+    let dst: Vec<_> = best.iter().map(|&i| matches[i].1).collect();
+    let h = Homography::from_points(&src, &dst)?;
+    let inliers = inliers_of(&h);
+    Some((h, inliers))
+}
+
+/// Rotation (radians), uniform scale and translation about the frame centre
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Similarity {
+    pub dx: f32,
+    pub dy: f32,
+    pub angle: f32,
+    pub scale: f32,
+}
+
+impl Similarity {
+    pub fn identity() -> Self {
+        Self {
+            dx: 0.0,
+            dy: 0.0,
+            angle: 0.0,
+            scale: 1.0,
+        }
+    }
+
+    /// From x' = a x - b y + tx, y' = b x + a y + ty
+    fn from_linear(a: f32, b: f32, tx: f32, ty: f32) -> Self {
+        Self {
+            dx: tx,
+            dy: ty,
+            angle: b.atan2(a),
+            scale: a.hypot(b),
+        }
+    }
+
+    /// Map a point given relative to the centre
+    pub fn apply(&self, p: Vector2<f32>) -> Vector2<f32> {
+        let (sin, cos) = self.angle.sin_cos();
+        let (a, b) = (self.scale * cos, self.scale * sin);
+        Vector2::new(a * p.x - b * p.y + self.dx, b * p.x + a * p.y + self.dy)
+    }
+
+    pub fn inverse_apply(&self, q: Vector2<f32>) -> Vector2<f32> {
+        let (sin, cos) = self.angle.sin_cos();
+        let v = (q - Vector2::new(self.dx, self.dy)) / self.scale.max(1e-6);
+        Vector2::new(cos * v.x + sin * v.y, -sin * v.x + cos * v.y)
+    }
+
+    /// `self` followed by `next`
+    pub fn then(&self, next: &Similarity) -> Similarity {
+        let t = next.apply(Vector2::new(self.dx, self.dy));
+        Similarity {
+            dx: t.x,
+            dy: t.y,
+            angle: self.angle + next.angle,
+            scale: self.scale * next.scale,
+        }
+    }
+
+    pub fn inverse(&self) -> Similarity {
+        let t = self.inverse_apply(Vector2::zeros());
+        Similarity {
+            dx: t.x,
+            dy: t.y,
+            angle: -self.angle,
+            scale: 1.0 / self.scale.max(1e-6),
+        }
+    }
+}
+
+/// Least-squares similarity taking each source point (relative to the frame
+/// centre) onto its target
+fn fit_similarity(pairs: &[(Vector2<f32>, Vector2<f32>)]) -> Option<Similarity> {
+    let mut ata = Matrix4::<f64>::zeros();
+    let mut atb = Vector4::<f64>::zeros();
+    for (p, q) in pairs {
+        let (x, y) = (p.x as f64, p.y as f64);
+        for (row, target) in [
+            (Vector4::new(x, -y, 1.0, 0.0), q.x as f64),
+            (Vector4::new(y, x, 0.0, 1.0), q.y as f64),
+        ] {
+            ata += row * row.transpose();
+            atb += row * target;
+        }
+    }
+    let v = ata.try_inverse()? * atb;
+    Some(Similarity::from_linear(
+        v[0] as f32,
+        v[1] as f32,
+        v[2] as f32,
+        v[3] as f32,
+    ))
+}
+
+/// Reprojection error (px) under which a feature agrees with a motion model
+const STABILIZE_INLIER_ERROR: f32 = 1.5;
+const STABILIZE_RANSAC_ITERATIONS: usize = 100;
+/// Side of the patches matched between frames is `2 * half + 1`
+const STABILIZE_PATTERN_HALF: i32 = 7;
+/// Match scores below this are not used for motion estimation
+const STABILIZE_MIN_SCORE: f32 = 0.6;
+/// Largest automatic zoom used to hide the borders
+const MAX_STABLE_ZOOM: f32 = 2.0;
+
+/// Global motion between two frames: a grid of patches from `prev` is
+/// matched in `next` and a similarity is fitted with RANSAC, so moving
+/// subjects are voted out. Returns the motion and its inlier count; frames
+/// without enough texture report no motion.
+pub fn estimate_motion(
+    prev: &LumaPlane,
+    next: &LumaPlane,
+    grid: u32,
+    radius: i32,
+    seed: u64,
+) -> (Similarity, usize) {
+    let half = STABILIZE_PATTERN_HALF;
+    let margin = (half + radius) as f32;
+    let centre = Vector2::new(
+        (prev.width as f32 - 1.0) / 2.0,
+        (prev.height as f32 - 1.0) / 2.0,
+    );
+    let grid = grid.max(2);
+    let span = |size: u32, i: u32| {
+        let usable = (size as f32 - 1.0 - 2.0 * margin).max(0.0);
+        margin + usable * i as f32 / (grid - 1) as f32
+    };
+    let points: Vec<Point2<f32>> = (0..grid)
+        .flat_map(|j| (0..grid).map(move |i| (i, j)))
+        .map(|(i, j)| Point2::new(span(prev.width, i), span(prev.height, j)))
+        .collect();
+    let pairs: Vec<(Vector2<f32>, Vector2<f32>)> = points
+        .par_iter()
+        .filter_map(|p| {
+            let template = Template::extract(prev, *p, half)?;
+            let (q, score) = template.search(next, *p, radius)?;
+            (score >= STABILIZE_MIN_SCORE).then_some((p.coords - centre, q.coords - centre))
+        })
+        .collect();
+    if pairs.len() < 2 {
+        return (Similarity::identity(), 0);
+    }
+
+    let inliers_of = |m: &Similarity| -> Vec<usize> {
+        pairs
+            .iter()
+            .enumerate()
+            .filter(|(_, (p, q))| (m.apply(*p) - q).norm() < STABILIZE_INLIER_ERROR)
+            .map(|(i, _)| i)
+            .collect()
+    };
+    let mut rng = SampleRng::new(seed);
+    let mut best: Vec<usize> = Vec::new();
+    for _ in 0..STABILIZE_RANSAC_ITERATIONS {
+        let sample: Vec<_> = rng
+            .distinct(pairs.len(), 2)
+            .iter()
+            .map(|&i| pairs[i])
+            .collect();
+        if (sample[0].0 - sample[1].0).norm() < 1.0 {
+            continue;
+        }
+        let Some(model) = fit_similarity(&sample) else {
+            continue;
+        };
+        let inliers = inliers_of(&model);
+        if inliers.len() > best.len() {
+            best = inliers;
+        }
+    }
+    let inliers: Vec<_> = best.iter().map(|&i| pairs[i]).collect();
+    match fit_similarity(&inliers) {
+        Some(model) if inliers.len() >= 2 => (model, inliers.len()),
+        _ => (Similarity::identity(), 0),
+    }
+}
+
+/// Motion of one frame and the correction that stabilizes it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrameMotion {
+    pub frame: u32,
+    /// Motion from the previous frame
+    pub motion: Similarity,
+    pub inliers: usize,
+    pub correction: Similarity,
+}
+
+/// Result of analysing a shot, written out as JSON for reuse
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StabilizationReport {
+    pub method: String,
+    pub smoothing: f32,
+    /// Zoom applied so no frame shows a border (1.0 without crop_to_stable)
+    pub zoom: f32,
+    pub frames: Vec<FrameMotion>,
+}
+
+impl StabilizationReport {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("motion data serializes")
+    }
+}
+
+const SEQUENCE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "tif", "tiff", "exr", "bmp"];
+const VIDEO_EXTENSIONS: &[&str] = &["mp4", "mov", "mkv", "avi", "mxf", "webm", "m4v"];
+
+fn extension(path: &Path) -> String {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .unwrap_or_default()
+}
+
+/// Image files of a sequence directory in name order
+fn sequence_files(dir: &Path) -> Result<Vec<PathBuf>, TrackingError> {
+    let io_err = |source| TrackingError::Io {
+        path: dir.display().to_string(),
+        source,
+    };
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map_err(io_err)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| SEQUENCE_EXTENSIONS.contains(&extension(p).as_str()))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
 #[pyclass]
 #[derive(Clone)]
 pub struct Stabilizer {
@@ -62,6 +1199,10 @@ pub struct Stabilizer {
     pub method: String, // "position", "rotation", "scale", "all"
     #[pyo3(get, set)]
     pub crop_to_stable: bool,
+    #[pyo3(get, set)]
+    pub search_radius: f32, // px of camera motion between frames
+    #[pyo3(get, set)]
+    pub features: u32, // grid points per side used for motion estimation
 }
 
 #[pymethods]
@@ -72,12 +1213,543 @@ impl Stabilizer {
             smoothing: 0.5,
             method: "all".to_string(),
             crop_to_stable: true,
+            search_radius: 24.0,
+            features: 10,
+        }
+    }
+
+    /// Stabilize an image sequence directory. Frames are written to
+    /// `<dir>_stable` with the per-frame motion in `motion.json`; the output
+    /// directory is returned. Video files must be exported to an image
+    /// sequence first.
+    fn stabilize(&self, sequence_dir: &str) -> PyResult<String> {
+        let output = self.stabilize_sequence(Path::new(sequence_dir))?;
+        Ok(output.display().to_string())
+    }
+}
+
+impl Stabilizer {
+    /// Which of position, rotation and scale get corrected
+    fn components(&self) -> Result<[bool; 3], TrackingError> {
+        match self.method.as_str() {
+            "position" => Ok([true, false, false]),
+            "rotation" => Ok([false, true, false]),
+            "scale" => Ok([false, false, true]),
+            "all" => Ok([true, true, true]),
+            other => Err(TrackingError::Method(other.to_string())),
+        }
+    }
+
+    /// Estimate the camera path, smooth it and work out each frame's
+    /// correction and the zoom that hides the borders
+    pub fn analyze<P: Pixel>(
+        &self,
+        frames: &[Frame<P>],
+    ) -> Result<StabilizationReport, TrackingError> {
+        let [position, rotation, scale] = self.components()?;
+        if frames.len() < 2 {
+            return Err(TrackingError::TooFewFrames(frames.len()));
         }
+        let planes: Vec<LumaPlane> = frames.par_iter().map(LumaPlane::from_frame).collect();
+        let radius = self.search_radius.round().max(1.0) as i32;
+        let mut motions = vec![(Similarity::identity(), 0)];
+        motions.extend(
+            planes
+                .par_windows(2)
+                .enumerate()
+                .map(|(i, pair)| {
+                    estimate_motion(&pair[0], &pair[1], self.features, radius, i as u64)
+                })
+                .collect::<Vec<_>>(),
+        );
+
+        // Camera path: the frame-to-frame motions chained from the first
+        // frame, smoothed as x, y, angle and log scale
+        let mut cameras = vec![Similarity::identity()];
+        for (m, _) in &motions[1..] {
+            cameras.push(cameras[cameras.len() - 1].then(m));
+        }
+        let path: Vec<[f32; 4]> = cameras
+            .iter()
+            .map(|c| [c.dx, c.dy, c.angle, c.scale.max(1e-6).ln()])
+            .collect();
+        let index: Vec<f32> = (0..path.len()).map(|i| i as f32).collect();
+        let ones = vec![1.0; path.len()];
+        let smoothed: Vec<Vec<f32>> = (0..4)
+            .map(|c| {
+                let values: Vec<f32> = path.iter().map(|p| p[c]).collect();
+                smooth_series(
+                    &index,
+                    &values,
+                    &ones,
+                    SmoothingMethod::Gaussian,
+                    self.smoothing,
+                )
+            })
+            .collect();
+        let target = |i: usize, c: usize, on: bool| if on { smoothed[c][i] } else { path[i][c] };
+
+        let frames_motion: Vec<FrameMotion> = motions
+            .iter()
+            .enumerate()
+            .map(|(i, (motion, inliers))| FrameMotion {
+                frame: i as u32,
+                motion: *motion,
+                inliers: *inliers,
+                // Undo the real camera, then follow the smoothed one
+                correction: cameras[i].inverse().then(&Similarity {
+                    dx: target(i, 0, position),
+                    dy: target(i, 1, position),
+                    angle: target(i, 2, rotation),
+                    scale: target(i, 3, scale).exp(),
+                }),
+            })
+            .collect();
+        let zoom = if self.crop_to_stable {
+            let corrections: Vec<Similarity> = frames_motion.iter().map(|f| f.correction).collect();
+            crop_zoom(
+                &corrections,
+                frames[0].width as f32,
+                frames[0].height as f32,
+            )
+        } else {
+            1.0
+        };
+        Ok(StabilizationReport {
+            method: self.method.clone(),
+            smoothing: self.smoothing,
+            zoom,
+            frames: frames_motion,
+        })
+    }
+
+    /// Warp frames by the corrections of a report
+    pub fn apply<P: Pixel>(
+        &self,
+        frames: &[Frame<P>],
+        report: &StabilizationReport,
+    ) -> Vec<Frame<P>> {
+        let edge = if self.crop_to_stable {
+            EdgeMode::Clamp
+        } else {
+            EdgeMode::Transparent
+        };
+        frames
+            .iter()
+            .zip(&report.frames)
+            .map(|(frame, motion)| {
+                let (w, h) = (frame.width, frame.height);
+                let centre = Vector2::new(w as f32 / 2.0, h as f32 / 2.0);
+                let src = to_f32_vec(&frame.data);
+                let out = warp_frame(&src, w, h, Interpolation::Bilinear, edge, |x, y| {
+                    let q = (Vector2::new(x, y) - centre) / report.zoom;
+                    let p = motion.correction.inverse_apply(q) + centre;
+                    (p.x, p.y)
+                });
+                Frame {
+                    width: w,
+                    height: h,
+                    data: from_f32_vec(&out),
+                }
+            })
+            .collect()
     }
 
-    fn stabilize(&self, video_path: &str) -> PyResult<String> {
-        // Placeholder for stabilization implementation
-        println!("Stabilizing: {}", video_path);
-        Ok(format!("{}_stable.mp4", video_path))
+    /// Stabilize every image of a sequence directory into `<dir>_stable`
+    pub fn stabilize_sequence(&self, input: &Path) -> Result<PathBuf, TrackingError> {
+        if input.is_file() || VIDEO_EXTENSIONS.contains(&extension(input).as_str()) {
+            return Err(TrackingError::UnsupportedInput(format!(
+                "{}: video decoding is not available; export the clip as an image sequence \
+                 directory and stabilize that",
+                input.display()
+            )));
+        }
+        let files = sequence_files(input)?;
+        let frames = files
+            .iter()
+            .map(Frame::open)
+            .collect::<Result<Vec<_>, _>>()?;
+        let report = self.analyze(&frames)?;
+        let stable = self.apply(&frames, &report);
+
+        let name = input
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "sequence".to_string());
+        let output = input.with_file_name(format!("{}_stable", name));
+        let io_err = |source| TrackingError::Io {
+            path: output.display().to_string(),
+            source,
+        };
+        std::fs::create_dir_all(&output).map_err(io_err)?;
+        for (file, frame) in files.iter().zip(&stable) {
+            // PNG and TIFF keep their container, other formats become PNG
+            let (ext, depth) = match extension(file).as_str() {
+                "exr" => ("exr", BitDepth::Float),
+                "tif" | "tiff" => ("tif", BitDepth::Sixteen),
+                _ => ("png", BitDepth::Eight),
+            };
+            let stem = file.file_stem().unwrap_or_default().to_string_lossy();
+            frame.save(output.join(format!("{}.{}", stem, ext)), depth)?;
+        }
+        std::fs::write(output.join("motion.json"), report.to_json()).map_err(io_err)?;
+        Ok(output)
+    }
+}
+
+/// Smallest zoom (up to `MAX_STABLE_ZOOM`) at which every corrected frame
+/// still covers the whole output
+fn crop_zoom(corrections: &[Similarity], width: f32, height: f32) -> f32 {
+    let (hw, hh) = (width / 2.0, height / 2.0);
+    let corners = [
+        Vector2::new(-hw, -hh),
+        Vector2::new(hw, -hh),
+        Vector2::new(hw, hh),
+        Vector2::new(-hw, hh),
+    ];
+    let covers = |zoom: f32| {
+        corrections.iter().all(|c| {
+            corners.iter().all(|q| {
+                let p = c.inverse_apply(q / zoom);
+                p.x.abs() <= hw + 1e-3 && p.y.abs() <= hh + 1e-3
+            })
+        })
+    };
+    if covers(1.0) {
+        return 1.0;
+    }
+    if !covers(MAX_STABLE_ZOOM) {
+        return MAX_STABLE_ZOOM;
+    }
+    let (mut low, mut high) = (1.0, MAX_STABLE_ZOOM);
+    for _ in 0..24 {
+        let mid = (low + high) / 2.0;
+        if covers(mid) {
+            high = mid;
+        } else {
+            low = mid;
+        }
+    }
+    high
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Asymmetric cluster of soft blobs centred at (cx, cy)
+    fn blob_frame(cx: f32, cy: f32) -> Frame<f32> {
+        let mut frame = Frame::new(64, 64);
+        for y in 0..64 {
+            for x in 0..64 {
+                let v: f32 = [(0.0, 0.0, 1.0), (4.0, -2.0, 0.6), (-3.0, 3.0, 0.8)]
+                    .iter()
+                    .map(|(ox, oy, k)| {
+                        let (dx, dy) = (x as f32 - cx - ox, y as f32 - cy - oy);
+                        k * (-(dx * dx + dy * dy) / 6.0).exp()
+                    })
+                    .sum();
+                let i = (y * 64 + x) * 4;
+                frame.data[i..i + 4].copy_from_slice(&[v, v, v, 1.0]);
+            }
+        }
+        frame
+    }
+
+    fn tracker() -> MotionTracker {
+        let mut tracker = MotionTracker::new();
+        tracker.search_radius = 6.0;
+        tracker.pattern_size = 14.0;
+        tracker
+    }
+
+    #[test]
+    fn tracks_sub_pixel_motion_in_both_directions() {
+        let frames: Vec<_> = (0..6)
+            .map(|i| blob_frame(20.0 + 2.5 * i as f32, 30.0 - 1.25 * i as f32))
+            .collect();
+        let mut tracker = tracker();
+        tracker.track_point(27.5, 26.25, 3).unwrap();
+        tracker.track(&frames, TrackDirection::Both).unwrap();
+
+        assert_eq!(tracker.tracks.len(), 6);
+        for t in &tracker.tracks {
+            let expected = Point2::new(20.0 + 2.5 * t.frame as f32, 30.0 - 1.25 * t.frame as f32);
+            assert!((t.position - expected).norm() < 0.1, "{:?}", t);
+            assert!(t.confidence > 0.9);
+        }
+    }
+
+    #[test]
+    fn planar_track_follows_a_perspective_warp() {
+        // Scattered soft blobs give the plane texture without repeats
+        let mut rng = SampleRng::new(7);
+        let blobs: Vec<(f32, f32)> = (0..90)
+            .map(|_| (rng.below(1000) as f32 / 10.0, rng.below(1000) as f32 / 10.0))
+            .collect();
+        let texture = |u: f32, v: f32| -> f32 {
+            blobs
+                .iter()
+                .map(|(bx, by)| (-((u - bx).powi(2) + (v - by).powi(2)) / 8.0).exp())
+                .sum()
+        };
+        let warps: Vec<Homography> = (0..4)
+            .map(|i| {
+                let k = i as f64;
+                Homography(nalgebra::Matrix3::new(
+                    1.0 + 0.01 * k,
+                    0.02 * k,
+                    2.0 * k,
+                    -0.01 * k,
+                    1.0,
+                    1.0 * k,
+                    0.0002 * k,
+                    0.0,
+                    1.0,
+                ))
+            })
+            .collect();
+        let frames: Vec<Frame<f32>> = warps
+            .iter()
+            .map(|h| {
+                let inverse = h.inverse().unwrap();
+                let mut frame = Frame::new(100, 100);
+                for y in 0..100 {
+                    for x in 0..100 {
+                        let p = inverse.apply(Point2::new(x as f32, y as f32)).unwrap();
+                        let v = texture(p.x, p.y);
+                        let i = (y * 100 + x) * 4;
+                        frame.data[i..i + 4].copy_from_slice(&[v, v, v, 1.0]);
+                    }
+                }
+                frame
+            })
+            .collect();
+
+        let mut tracker = PlanarTracker::new();
+        tracker.search_radius = 8.0;
+        tracker.pattern_size = 11.0;
+        tracker.features = 5;
+        let corners = vec![(25.0, 25.0), (70.0, 25.0), (70.0, 70.0), (25.0, 70.0)];
+        tracker.set_corners(corners.clone(), 0).unwrap();
+        tracker.track(&frames, TrackDirection::Forward).unwrap();
+
+        assert_eq!(tracker.tracks.len(), 4);
+        let tracked = tracker.corners_at(3).unwrap();
+        for ((x, y), p) in corners.iter().zip(tracked) {
+            let expected = warps[3].apply(Point2::new(*x, *y)).unwrap();
+            assert!((p - expected).norm() < 0.5, "{:?} vs {:?}", p, expected);
+        }
+        assert!(tracker.set_corners(vec![(0.0, 0.0)], 0).is_err());
+    }
+
+    fn noisy_line() -> Vec<TrackPoint> {
+        let mut rng = SampleRng::new(3);
+        (0..40)
+            .map(|f| TrackPoint {
+                frame: f,
+                position: Point2::new(
+                    2.0 * f as f32 + (rng.below(100) as f32 / 100.0 - 0.5),
+                    10.0 + (rng.below(100) as f32 / 100.0 - 0.5),
+                ),
+                confidence: 1.0,
+            })
+            .collect()
+    }
+
+    fn line_error(points: &[TrackPoint]) -> f32 {
+        points
+            .iter()
+            .map(|p| (p.position - Point2::new(2.0 * p.frame as f32, 10.0)).norm())
+            .fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn smoothing_filters_reduce_jitter_and_drop_outliers() {
+        let mut track = noisy_line();
+        track[20].position.y += 40.0;
+        for method in [
+            SmoothingMethod::MovingAverage,
+            SmoothingMethod::Gaussian,
+            SmoothingMethod::SavitzkyGolay,
+        ] {
+            let smoothed = smooth_track(&track, method, 0.4, 3.0);
+            // Interior points, away from the one-sided windows at the ends
+            assert!(line_error(&smoothed[6..34]) < 0.4, "{:?}", method);
+        }
+
+        // One-Euro lags a little on the ramp but still removes the spike
+        let smoothed = smooth_track(&track, SmoothingMethod::OneEuro, 0.5, 3.0);
+        assert!((smoothed[20].position.y - 10.0).abs() < 1.0);
+
+        // Savitzky-Golay keeps a parabola exactly
+        let parabola: Vec<TrackPoint> = (0..20)
+            .map(|f| TrackPoint {
+                frame: f,
+                position: Point2::new(f as f32, 0.1 * (f * f) as f32),
+                confidence: 1.0,
+            })
+            .collect();
+        let smoothed = smooth_track(&parabola, SmoothingMethod::SavitzkyGolay, 0.3, 0.0);
+        for (a, b) in parabola.iter().zip(&smoothed) {
+            assert!((a.position - b.position).norm() < 1e-2);
+        }
+    }
+
+    /// Frame of scattered blobs shifted by (dx, dy)
+    fn textured_frame(dx: f32, dy: f32) -> Frame<f32> {
+        moved_frame(&Similarity {
+            dx,
+            dy,
+            ..Similarity::identity()
+        })
+    }
+
+    /// Frame of scattered blobs moved by `camera` about the centre
+    fn moved_frame(camera: &Similarity) -> Frame<f32> {
+        let mut rng = SampleRng::new(11);
+        let blobs: Vec<(f32, f32)> = (0..120)
+            .map(|_| (rng.below(960) as f32 / 10.0, rng.below(960) as f32 / 10.0))
+            .collect();
+        let mut frame = Frame::new(96, 96);
+        for y in 0..96 {
+            for x in 0..96 {
+                let p = camera.inverse_apply(Vector2::new(x as f32 - 48.0, y as f32 - 48.0));
+                let v: f32 = blobs
+                    .iter()
+                    .map(|(bx, by)| {
+                        let d2 = (p.x + 48.0 - bx).powi(2) + (p.y + 48.0 - by).powi(2);
+                        0.5 * (-d2 / 6.0).exp()
+                    })
+                    .sum();
+                let i = (y * 96 + x) * 4;
+                frame.data[i..i + 4].copy_from_slice(&[v, v, v, 1.0]);
+            }
+        }
+        frame
+    }
+
+    #[test]
+    fn stabilizer_estimates_shake_and_smooths_it_out() {
+        let shake = [
+            (0.0, 0.0),
+            (3.0, -1.0),
+            (-2.0, 2.0),
+            (4.0, 1.0),
+            (-1.0, -2.0),
+            (2.0, 0.0),
+        ];
+        let frames: Vec<_> = shake.iter().map(|(x, y)| textured_frame(*x, *y)).collect();
+        let mut stabilizer = Stabilizer::new();
+        stabilizer.smoothing = 1.0;
+        stabilizer.search_radius = 8.0;
+        let report = stabilizer.analyze(&frames).unwrap();
+
+        for (i, f) in report.frames.iter().enumerate().skip(1) {
+            let expected = (shake[i].0 - shake[i - 1].0, shake[i].1 - shake[i - 1].1);
+            assert!((f.motion.dx - expected.0).abs() < 0.2, "{:?}", f);
+            assert!((f.motion.dy - expected.1).abs() < 0.2, "{:?}", f);
+            assert!(f.motion.angle.abs() < 0.01);
+        }
+        // Shake plus correction leaves a nearly still camera
+        let corrected: Vec<f32> = report
+            .frames
+            .iter()
+            .zip(&shake)
+            .map(|(f, s)| s.0 + f.correction.dx)
+            .collect();
+        let spread = corrected.iter().fold(f32::MIN, |a, b| a.max(*b))
+            - corrected.iter().fold(f32::MAX, |a, b| a.min(*b));
+        assert!(spread < 1.0, "{:?}", corrected);
+        assert!(report.zoom > 1.0 && report.zoom < 1.2);
+        assert!(report.to_json().contains("\"correction\""));
+
+        stabilizer.method = "wobble".to_string();
+        assert!(matches!(
+            stabilizer.analyze(&frames),
+            Err(TrackingError::Method(_))
+        ));
+    }
+
+    #[test]
+    fn stabilizer_chains_rotation_into_the_camera_path() {
+        let shake = [
+            (0.0, 0.0, 0.0),
+            (4.0, -3.0, 0.06),
+            (-3.0, 4.0, -0.05),
+            (5.0, 2.0, 0.07),
+            (-4.0, -3.0, -0.06),
+            (3.0, 1.0, 0.04),
+        ];
+        let cameras: Vec<Similarity> = shake
+            .iter()
+            .map(|&(dx, dy, angle)| Similarity {
+                dx,
+                dy,
+                angle,
+                scale: 1.0,
+            })
+            .collect();
+        let frames: Vec<_> = cameras.iter().map(moved_frame).collect();
+        let mut stabilizer = Stabilizer::new();
+        stabilizer.smoothing = 1.0;
+        stabilizer.search_radius = 14.0;
+        let report = stabilizer.analyze(&frames).unwrap();
+
+        // Following each real camera with its correction should leave
+        // nearly the same (smoothed) camera on every frame
+        let stable: Vec<Similarity> = cameras
+            .iter()
+            .zip(&report.frames)
+            .map(|(camera, f)| camera.then(&f.correction))
+            .collect();
+        let probe = Vector2::new(30.0, -20.0);
+        for s in &stable[1..] {
+            let drift = (s.apply(probe) - stable[0].apply(probe)).norm();
+            assert!(drift < 1.5, "{:?}", stable);
+            assert!((s.angle - stable[0].angle).abs() < 0.02, "{:?}", stable);
+        }
+    }
+
+    #[test]
+    fn stabilize_reads_and_writes_image_sequences() {
+        // The stabilised copy lands next to the input, so keep both inside
+        // a directory of this test's own
+        let root =
+            std::env::temp_dir().join(format!("fluxreel_stabilize_test_{}", std::process::id()));
+        let dir = root.join("shot");
+        std::fs::create_dir_all(&dir).unwrap();
+        for (i, (x, y)) in [(0.0, 0.0), (2.0, 1.0), (-1.0, 2.0)].iter().enumerate() {
+            textured_frame(*x, *y)
+                .save(dir.join(format!("frame_{:03}.png", i)), BitDepth::Eight)
+                .unwrap();
+        }
+        let mut stabilizer = Stabilizer::new();
+        stabilizer.search_radius = 8.0;
+        let output = stabilizer.stabilize_sequence(&dir).unwrap();
+        assert!(output.join("frame_002.png").exists());
+        assert!(output.join("motion.json").exists());
+        assert!(output.starts_with(&root));
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let video = stabilizer.stabilize_sequence(Path::new("shaky_footage.mp4"));
+        assert!(matches!(video, Err(TrackingError::UnsupportedInput(_))));
+    }
+
+    #[test]
+    fn tracking_stops_when_the_pattern_is_lost() {
+        let mut frames = vec![blob_frame(30.0, 30.0), blob_frame(31.0, 30.0)];
+        frames.push(Frame::new(64, 64));
+        frames.push(blob_frame(33.0, 30.0));
+        let mut tracker = tracker();
+        tracker.track_point(30.0, 30.0, 0).unwrap();
+        tracker.track(&frames, TrackDirection::Forward).unwrap();
+        assert_eq!(tracker.get_track().len(), 2);
+
+        tracker.track_point(5.0, 5.0, 2).unwrap();
+        assert!(matches!(
+            tracker.track(&frames, TrackDirection::Forward),
+            Err(TrackingError::FlatPattern { .. })
+        ));
     }
 }
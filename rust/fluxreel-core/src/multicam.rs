@@ -1,3 +1,5 @@
+use crate::color_grading::ColorGrading;
+use crate::frame::Frame;
 use pyo3::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -21,6 +23,8 @@ pub struct CameraAngle {
     #[pyo3(get, set)]
     pub enabled: bool,
     pub sync_method: SyncMethod,
+    #[pyo3(get, set)]
+    pub grading: Option<ColorGrading>, // per-angle correction, e.g. from match_angles
 }
 
 #[pymethods]
@@ -33,6 +37,7 @@ impl CameraAngle {
             offset: 0.0,
             enabled: true,
             sync_method: SyncMethod::Timecode,
+            grading: None,
         }
     }
 
@@ -161,6 +166,45 @@ impl MultiCamSequence {
         }
     }
 
+    /// Grade every angle to match the reference angle.
+    ///
+    /// `frames` holds one representative RGBA8 still per angle, all of the
+    /// same size and ideally showing the same moment. The reference angle
+    /// gets a neutral grade.
+    fn match_angles(
+        &mut self,
+        reference_index: usize,
+        frames: Vec<Vec<u8>>,
+        width: u32,
+        height: u32,
+    ) -> PyResult<()> {
+        if reference_index >= self.angles.len() {
+            return Err(PyErr::new::<pyo3::exceptions::PyIndexError, _>(
+                "Angle index out of range",
+            ));
+        }
+        if frames.len() != self.angles.len() {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Expected {} frames (one per angle), got {}",
+                self.angles.len(),
+                frames.len()
+            )));
+        }
+        let frames = frames
+            .into_iter()
+            .map(|pixels| Frame::from_raw(width, height, pixels))
+            .collect::<Result<Vec<_>, _>>()?;
+        let reference = &frames[reference_index];
+        for (i, (angle, frame)) in self.angles.iter_mut().zip(&frames).enumerate() {
+            let mut grading = ColorGrading::default();
+            if i != reference_index {
+                grading.match_frame(&frame.data, &reference.data);
+            }
+            angle.grading = Some(grading);
+        }
+        Ok(())
+    }
+
     fn preview_all_angles(&self) -> Vec<String> {
         self.angles
             .iter()
@@ -1,3 +1,8 @@
+use crate::color_management::{parse_space, ColorConfig, ColorTransform};
+use crate::effects::CornerPinEffect;
+use crate::masking::{parse_track_matte_mode, AnyMask, MaskStack, TrackMatte};
+use crate::motion_tracking::MotionTracker;
+use crate::speed_ramping::TimeRemap;
 use nalgebra::{Point2, Vector2};
 use pyo3::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -23,12 +28,112 @@ impl Default for NodeProperties {
     }
 }
 
+/// Node transform driven by motion tracks: one track moves the node, a
+/// second one adds the rotation and scale of the line between the two.
+///
+/// Motion is taken relative to `reference_frame`, so the node keeps its own
+/// placement there and follows the track from it.
+#[derive(Clone)]
+pub struct TrackBinding {
+    pub primary: MotionTracker,
+    pub secondary: Option<MotionTracker>,
+    pub reference_frame: u32,
+    pub fps: f32,
+    /// Node position units per tracked pixel
+    pub units_per_pixel: f32,
+    pub position_offset: Vector2<f32>,
+    pub rotation_offset: f32,
+    pub scale_offset: f32,
+    /// Moving-average radius in frames
+    pub smoothing: u32,
+}
+
+impl TrackBinding {
+    pub(crate) fn new(
+        primary: MotionTracker,
+        secondary: Option<MotionTracker>,
+        frame: u32,
+        fps: f32,
+    ) -> Self {
+        Self {
+            primary,
+            secondary,
+            reference_frame: frame,
+            fps,
+            units_per_pixel: 1.0,
+            position_offset: Vector2::zeros(),
+            rotation_offset: 0.0,
+            scale_offset: 1.0,
+            smoothing: 0,
+        }
+    }
+
+    /// Track position averaged over the smoothing window; gaps between
+    /// tracked frames are interpolated
+    fn smoothed(&self, tracker: &MotionTracker, frame: f32) -> Option<Point2<f32>> {
+        let r = self.smoothing as i32;
+        let samples: Vec<Point2<f32>> = (-r..=r)
+            .filter_map(|i| tracker.position_at(frame + i as f32))
+            .collect();
+        if samples.is_empty() {
+            return None;
+        }
+        let sum = samples
+            .iter()
+            .fold(Vector2::zeros(), |acc, p| acc + p.coords);
+        Some(Point2::from(sum / samples.len() as f32))
+    }
+
+    /// Primary track at the reference frame, which tracked rotation and
+    /// scale turn about
+    pub(crate) fn pivot(&self) -> Option<Point2<f32>> {
+        self.smoothed(&self.primary, self.reference_frame as f32)
+    }
+
+    /// Position delta, rotation delta (degrees) and scale factor at `time`
+    pub(crate) fn motion_at(&self, time: f32) -> (Vector2<f32>, f32, f32) {
+        let (frame, reference) = (time * self.fps, self.reference_frame as f32);
+        let (Some(p), Some(p0)) = (
+            self.smoothed(&self.primary, frame),
+            self.smoothed(&self.primary, reference),
+        ) else {
+            return (
+                self.position_offset,
+                self.rotation_offset,
+                self.scale_offset,
+            );
+        };
+        let position = (p - p0) * self.units_per_pixel + self.position_offset;
+
+        let line = |at: f32, from: Point2<f32>| {
+            let tracker = self.secondary.as_ref()?;
+            Some(self.smoothed(tracker, at)? - from)
+        };
+        let (rotation, scale) = match (line(frame, p), line(reference, p0)) {
+            (Some(now), Some(then)) if then.norm() > 1e-6 => {
+                let angle = (now.y.atan2(now.x) - then.y.atan2(then.x)).to_degrees();
+                let angle = (angle + 540.0).rem_euclid(360.0) - 180.0;
+                (angle, now.norm() / then.norm())
+            }
+            _ => (0.0, 1.0),
+        };
+        (
+            position,
+            rotation + self.rotation_offset,
+            scale * self.scale_offset,
+        )
+    }
+}
+
 #[pyclass]
 #[derive(Clone)]
 pub struct Node {
     #[pyo3(get, set)]
     pub id: String,
     pub properties: NodeProperties,
+    pub masks: MaskStack,
+    pub track_matte: Option<TrackMatte>,
+    pub track_binding: Option<TrackBinding>,
 }
 
 #[pymethods]
@@ -38,9 +143,118 @@ impl Node {
         Self {
             id,
             properties: NodeProperties::default(),
+            masks: MaskStack::default(),
+            track_matte: None,
+            track_binding: None,
         }
     }
 
+    /// Move the node with a tracked point, relative to where the point is at
+    /// `reference_frame`
+    fn bind_to_track(&mut self, tracker: MotionTracker, reference_frame: u32, fps: f32) {
+        self.track_binding = Some(TrackBinding::new(tracker, None, reference_frame, fps));
+    }
+
+    /// Move the node with `primary` and rotate and scale it with the line
+    /// from `primary` to `secondary`
+    fn bind_to_tracks(
+        &mut self,
+        primary: MotionTracker,
+        secondary: MotionTracker,
+        reference_frame: u32,
+        fps: f32,
+    ) {
+        self.track_binding = Some(TrackBinding::new(
+            primary,
+            Some(secondary),
+            reference_frame,
+            fps,
+        ));
+    }
+
+    /// Offsets added on top of the tracked motion: position in node units,
+    /// rotation in degrees and a scale multiplier
+    fn set_track_offset(&mut self, x: f32, y: f32, rotation: f32, scale: f32) -> PyResult<()> {
+        let binding = self.binding_mut()?;
+        binding.position_offset = Vector2::new(x, y);
+        binding.rotation_offset = rotation;
+        binding.scale_offset = scale;
+        Ok(())
+    }
+
+    /// Average tracked positions over `frames` either side of each frame
+    fn set_track_smoothing(&mut self, frames: u32) -> PyResult<()> {
+        self.binding_mut()?.smoothing = frames;
+        Ok(())
+    }
+
+    /// Node position units per tracked pixel, e.g. 1 / width for positions
+    /// normalised to the frame
+    fn set_track_units(&mut self, units_per_pixel: f32) -> PyResult<()> {
+        self.binding_mut()?.units_per_pixel = units_per_pixel;
+        Ok(())
+    }
+
+    fn unbind_track(&mut self) {
+        self.track_binding = None;
+    }
+
+    /// ((x, y), rotation, (scale_x, scale_y)) at `time`, tracking included
+    fn transform_at(&self, time: f32) -> ((f32, f32), f32, (f32, f32)) {
+        let p = self.properties_at(time);
+        (
+            (p.position.x, p.position.y),
+            p.rotation,
+            (p.scale.x, p.scale.y),
+        )
+    }
+
+    /// Show this node only where the layer `source_id` is opaque ("alpha")
+    /// or bright ("luma"); the "_inverted" modes reverse that. The matte
+    /// layer itself is hidden when composited.
+    fn set_track_matte(&mut self, source_id: String, mode: &str) -> PyResult<()> {
+        self.track_matte = Some(TrackMatte {
+            source: source_id,
+            mode: parse_track_matte_mode(mode)?,
+        });
+        Ok(())
+    }
+
+    fn clear_track_matte(&mut self) {
+        self.track_matte = None;
+    }
+
+    /// (source id, mode) of the track matte, if any
+    #[getter]
+    fn get_track_matte(&self) -> Option<(String, String)> {
+        self.track_matte
+            .as_ref()
+            .map(|m| (m.source.clone(), m.mode.name().to_string()))
+    }
+
+    /// Add a `Mask`, `RectangleMask` or `EllipseMask` to the layer's stack
+    fn add_mask(&mut self, mask: AnyMask) {
+        self.masks.push(mask);
+    }
+
+    fn clear_masks(&mut self) {
+        self.masks.clear();
+    }
+
+    #[getter]
+    fn mask_count(&self) -> usize {
+        self.masks.len()
+    }
+
+    /// Combined mask alpha at `time` as one byte per pixel; opaque without
+    /// masks
+    fn mask_matte(&self, width: u32, height: u32, time: f32) -> Vec<u8> {
+        self.masks
+            .matte(width, height, time)
+            .map(|matte| matte.to_bytes())
+            .unwrap_or_else(|| vec![255; (width * height) as usize])
+    }
+
     fn align(&mut self, alignment: &str) {
         // Simplified alignment logic
         match alignment {
@@ -114,6 +328,76 @@ impl Node {
     }
 }
 
+impl Node {
+    fn binding_mut(&mut self) -> PyResult<&mut TrackBinding> {
+        self.track_binding.as_mut().ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Node '{}' is not bound to a track",
+                self.id
+            ))
+        })
+    }
+
+    /// Properties at `time` with any track binding applied
+    pub fn properties_at(&self, time: f32) -> NodeProperties {
+        let mut properties = self.properties.clone();
+        if let Some(binding) = &self.track_binding {
+            let (position, rotation, scale) = binding.motion_at(time);
+            properties.position += position;
+            properties.rotation += rotation;
+            properties.scale *= scale;
+        }
+        properties
+    }
+}
+
+/// Give a wrapper node (one holding a `node: Node`) the `Node` track
+/// binding methods. The `node` getter hands Python a copy, so these have to
+/// be called on the wrapper itself.
+macro_rules! forward_track_binding {
+    ($wrapper:ty) => {
+        #[pymethods]
+        impl $wrapper {
+            fn bind_to_track(&mut self, tracker: MotionTracker, reference_frame: u32, fps: f32) {
+                self.node.bind_to_track(tracker, reference_frame, fps);
+            }
+
+            fn bind_to_tracks(
+                &mut self,
+                primary: MotionTracker,
+                secondary: MotionTracker,
+                reference_frame: u32,
+                fps: f32,
+            ) {
+                self.node
+                    .bind_to_tracks(primary, secondary, reference_frame, fps);
+            }
+
+            fn set_track_offset(
+                &mut self,
+                x: f32,
+                y: f32,
+                rotation: f32,
+                scale: f32,
+            ) -> PyResult<()> {
+                self.node.set_track_offset(x, y, rotation, scale)
+            }
+
+            fn set_track_smoothing(&mut self, frames: u32) -> PyResult<()> {
+                self.node.set_track_smoothing(frames)
+            }
+
+            fn set_track_units(&mut self, units_per_pixel: f32) -> PyResult<()> {
+                self.node.set_track_units(units_per_pixel)
+            }
+
+            fn unbind_track(&mut self) {
+                self.node.unbind_track();
+            }
+        }
+    };
+}
+
 #[pyclass]
 #[derive(Clone)]
 pub struct TextNode {
@@ -129,6 +413,8 @@ pub struct TextNode {
     pub font: Option<String>,
 }
 
+forward_track_binding!(TextNode);
+
 #[pymethods]
 impl TextNode {
     #[new]
@@ -153,6 +439,18 @@ impl TextNode {
     fn scale(&mut self, start: f32, end: f32, ease: &str) {
         self.node.scale(start, end, ease);
     }
+
+    fn add_mask(&mut self, mask: AnyMask) {
+        self.node.add_mask(mask);
+    }
+
+    fn clear_masks(&mut self) {
+        self.node.clear_masks();
+    }
+
+    fn set_track_matte(&mut self, source_id: String, mode: &str) -> PyResult<()> {
+        self.node.set_track_matte(source_id, mode)
+    }
 }
 
 #[pyclass]
@@ -162,8 +460,14 @@ pub struct ImageNode {
     pub node: Node,
     #[pyo3(get, set)]
     pub path: String,
+    #[pyo3(get)]
+    pub color_space: Option<String>, // input space override, see ColorConfig
+    #[pyo3(get, set)]
+    pub corner_pin: Option<CornerPinEffect>,
 }
 
+forward_track_binding!(ImageNode);
+
 #[pymethods]
 impl ImageNode {
     #[new]
@@ -171,9 +475,21 @@ impl ImageNode {
         Self {
             node: Node::new(format!("image_{}", path.len())),
             path,
+            color_space: None,
+            corner_pin: None,
         }
     }
 
+    /// Declare the asset's colour space; None falls back to ColorConfig
+    #[setter]
+    fn set_color_space(&mut self, space: Option<String>) -> PyResult<()> {
+        self.color_space = match space {
+            Some(space) => Some(parse_space(&space)?.name().to_string()),
+            None => None,
+        };
+        Ok(())
+    }
+
     fn align(&mut self, alignment: &str) {
         self.node.align(alignment);
     }
@@ -181,6 +497,25 @@ impl ImageNode {
     fn fade_in(&mut self, duration: f32) {
         self.node.fade_in(duration);
     }
+
+    fn add_mask(&mut self, mask: AnyMask) {
+        self.node.add_mask(mask);
+    }
+
+    fn clear_masks(&mut self) {
+        self.node.clear_masks();
+    }
+
+    fn set_track_matte(&mut self, source_id: String, mode: &str) -> PyResult<()> {
+        self.node.set_track_matte(source_id, mode)
+    }
+}
+
+impl ImageNode {
+    /// Transform from this asset into the project's working space
+    pub fn input_transform(&self, config: &ColorConfig) -> ColorTransform {
+        config.input_transform(&self.path, self.color_space.as_deref())
+    }
 }
 
 #[pyclass]
@@ -190,8 +525,16 @@ pub struct VideoNode {
     pub node: Node,
     #[pyo3(get, set)]
     pub path: String,
+    #[pyo3(get)]
+    pub color_space: Option<String>, // input space override, see ColorConfig
+    #[pyo3(get, set)]
+    pub corner_pin: Option<CornerPinEffect>,
+    #[pyo3(get, set)]
+    pub time_remap: Option<TimeRemap>,
 }
 
+forward_track_binding!(VideoNode);
+
 #[pymethods]
 impl VideoNode {
     #[new]
@@ -199,8 +542,56 @@ impl VideoNode {
         Self {
             node: Node::new(format!("video_{}", path.len())),
             path,
+            color_space: None,
+            corner_pin: None,
+            time_remap: None,
         }
     }
+
+    /// Declare the asset's colour space; None falls back to ColorConfig
+    #[setter]
+    fn set_color_space(&mut self, space: Option<String>) -> PyResult<()> {
+        self.color_space = match space {
+            Some(space) => Some(parse_space(&space)?.name().to_string()),
+            None => None,
+        };
+        Ok(())
+    }
+
+    /// Source time (seconds) shown at a clip-local time
+    fn source_time_at(&self, time: f32) -> f32 {
+        self.time_remap
+            .as_ref()
+            .map_or(time, |remap| remap.source_time(time))
+    }
+
+    /// Length of the clip on the timeline for `source_duration` seconds of
+    /// footage
+    fn output_duration(&self, source_duration: f32) -> Option<f32> {
+        match &self.time_remap {
+            Some(remap) => remap.output_duration(source_duration),
+            None => Some(source_duration),
+        }
+    }
+
+    fn add_mask(&mut self, mask: AnyMask) {
+        self.node.add_mask(mask);
+    }
+
+    fn clear_masks(&mut self) {
+        self.node.clear_masks();
+    }
+
+    fn set_track_matte(&mut self, source_id: String, mode: &str) -> PyResult<()> {
+        self.node.set_track_matte(source_id, mode)
+    }
+}
+
+impl VideoNode {
+    /// Transform from this asset into the project's working space
+    pub fn input_transform(&self, config: &ColorConfig) -> ColorTransform {
+        config.input_transform(&self.path, self.color_space.as_deref())
+    }
 }
 
 #[pyclass]
@@ -214,6 +605,8 @@ pub struct ShapeNode {
     pub color: String,
 }
 
+forward_track_binding!(ShapeNode);
+
 #[pymethods]
 impl ShapeNode {
     #[new]
@@ -224,4 +617,97 @@ impl ShapeNode {
             color,
         }
     }
+
+    fn add_mask(&mut self, mask: AnyMask) {
+        self.node.add_mask(mask);
+    }
+
+    fn clear_masks(&mut self) {
+        self.node.clear_masks();
+    }
+
+    fn set_track_matte(&mut self, source_id: String, mode: &str) -> PyResult<()> {
+        self.node.set_track_matte(source_id, mode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::motion_tracking::TrackPoint;
+
+    fn tracker(points: &[(u32, f32, f32)]) -> MotionTracker {
+        MotionTracker {
+            tracks: points
+                .iter()
+                .map(|&(frame, x, y)| TrackPoint {
+                    frame,
+                    position: Point2::new(x, y),
+                    confidence: 1.0,
+                })
+                .collect(),
+            start: None,
+            search_radius: 50.0,
+            pattern_size: 20.0,
+            min_confidence: 0.7,
+            smoothing_method: "gaussian".to_string(),
+            outlier_threshold: 3.0,
+        }
+    }
+
+    #[test]
+    fn bound_node_follows_tracks_across_gaps() {
+        let mut node = Node::new("logo".to_string());
+        node.move_to(10.0, 10.0);
+        // Frames 1-3 are missing from both tracks
+        let a = tracker(&[(0, 100.0, 100.0), (4, 140.0, 120.0)]);
+        let b = tracker(&[(0, 200.0, 100.0), (4, 140.0, 220.0)]);
+        node.bind_to_tracks(a, b, 0, 1.0);
+
+        let p = node.properties_at(2.0);
+        assert_eq!(p.position, Point2::new(30.0, 20.0));
+        let end = node.properties_at(4.0);
+        assert!((end.rotation - 90.0).abs() < 1e-3);
+        assert!((end.scale.x - 1.0).abs() < 1e-5);
+
+        node.set_track_offset(5.0, 0.0, 10.0, 2.0).unwrap();
+        let end = node.properties_at(4.0);
+        assert_eq!(end.position, Point2::new(55.0, 30.0));
+        assert!((end.rotation - 100.0).abs() < 1e-3);
+        assert!((end.scale.x - 2.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn track_smoothing_removes_jitter() {
+        let jittery: Vec<(u32, f32, f32)> = (0..9)
+            .map(|f| (f, f as f32 + if f % 2 == 0 { 1.0 } else { -1.0 }, 0.0))
+            .collect();
+        let mut node = Node::new("label".to_string());
+        node.bind_to_track(tracker(&jittery), 0, 1.0);
+        node.set_track_smoothing(1).unwrap();
+        let (a, b) = (node.properties_at(3.0), node.properties_at(4.0));
+        // Three-frame averages: frames 2-4 give 10/3 and 3-5 give 11/3. The
+        // reference frame 0 holds the first position before the track
+        // starts, so it averages (1 + 1 + 0) / 3
+        assert!((a.position.x - 8.0 / 3.0).abs() < 1e-5);
+        assert!((b.position.x - 3.0).abs() < 1e-5);
+        assert!(Node::new("n".to_string()).set_track_smoothing(2).is_err());
+    }
+
+    #[test]
+    fn wrapper_nodes_expose_the_whole_track_binding() {
+        let mut text = TextNode::new("Hi".to_string(), 40.0, "#fff".to_string());
+        let a = tracker(&[(0, 100.0, 100.0), (4, 140.0, 120.0)]);
+        let b = tracker(&[(0, 200.0, 100.0), (4, 140.0, 220.0)]);
+        text.bind_to_tracks(a, b, 0, 1.0);
+        text.set_track_offset(0.0, 0.0, 10.0, 2.0).unwrap();
+        let end = text.node.properties_at(4.0);
+        assert!((end.rotation - 100.0).abs() < 1e-3);
+        assert!((end.scale.x - 2.0).abs() < 1e-5);
+        text.set_track_smoothing(1).unwrap();
+        assert_eq!(text.node.track_binding.as_ref().unwrap().smoothing, 1);
+
+        text.unbind_track();
+        assert!(text.set_track_smoothing(1).is_err());
+    }
 }
@@ -0,0 +1,508 @@
+use crate::frame::{from_f32_vec, to_f32_vec, Frame, Pixel};
+use crate::motion_tracking::LumaPlane;
+use crate::sampling::{sample_rgba, warp_frame, EdgeMode, Interpolation};
+use pyo3::prelude::*;
+use rayon::prelude::*;
+
+/// Per-pixel motion in pixels between two frames: the content at (x, y) in
+/// the first frame sits at (x + u, y + v) in the second
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowField {
+    pub width: u32,
+    pub height: u32,
+    pub vectors: Vec<[f32; 2]>,
+}
+
+impl FlowField {
+    pub fn zero(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            vectors: vec![[0.0; 2]; (width * height) as usize],
+        }
+    }
+
+    pub fn get(&self, x: i32, y: i32) -> [f32; 2] {
+        let x = x.clamp(0, self.width as i32 - 1) as u32;
+        let y = y.clamp(0, self.height as i32 - 1) as u32;
+        self.vectors[(y * self.width + x) as usize]
+    }
+
+    /// Bilinear sample; pixel centres sit on integer coordinates
+    pub fn sample(&self, x: f32, y: f32) -> [f32; 2] {
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i32, y0 as i32);
+        let mut out = [0.0; 2];
+        for (c, o) in out.iter_mut().enumerate() {
+            let top = self.get(x0, y0)[c] * (1.0 - fx) + self.get(x0 + 1, y0)[c] * fx;
+            let bottom = self.get(x0, y0 + 1)[c] * (1.0 - fx) + self.get(x0 + 1, y0 + 1)[c] * fx;
+            *o = top * (1.0 - fy) + bottom * fy;
+        }
+        out
+    }
+
+    /// Resample to a finer level, scaling the vectors with the image
+    fn upsample(&self, width: u32, height: u32) -> Self {
+        let sx = self.width as f32 / width as f32;
+        let sy = self.height as f32 / height as f32;
+        let vectors = (0..height)
+            .into_par_iter()
+            .flat_map_iter(|y| {
+                (0..width).map(move |x| {
+                    let [u, v] =
+                        self.sample((x as f32 + 0.5) * sx - 0.5, (y as f32 + 0.5) * sy - 0.5);
+                    [u / sx, v / sy]
+                })
+            })
+            .collect();
+        Self {
+            width,
+            height,
+            vectors,
+        }
+    }
+
+    /// Vectors interleaved as u, v per pixel
+    pub fn to_interleaved(&self) -> Vec<f32> {
+        self.vectors.iter().flatten().copied().collect()
+    }
+}
+
+/// Half-resolution plane by 2x2 averaging
+fn downsample(plane: &LumaPlane) -> LumaPlane {
+    let (width, height) = ((plane.width / 2).max(1), (plane.height / 2).max(1));
+    let data = (0..height)
+        .into_par_iter()
+        .flat_map_iter(|y| {
+            (0..width).map(move |x| {
+                let (x, y) = (2 * x as i32, 2 * y as i32);
+                (plane.get(x, y)
+                    + plane.get(x + 1, y)
+                    + plane.get(x, y + 1)
+                    + plane.get(x + 1, y + 1))
+                    / 4.0
+            })
+        })
+        .collect();
+    LumaPlane {
+        width,
+        height,
+        data,
+    }
+}
+
+/// Coarsest levels are dropped when they would be smaller than this
+const MIN_LEVEL_SIZE: u32 = 16;
+/// Structure tensors with a smaller determinant are treated as flat and keep
+/// the flow propagated from the coarser level
+const MIN_STRUCTURE: f32 = 1e-6;
+
+/// Lucas–Kanade refinement of one pyramid level, starting from `guess`
+fn refine_level(
+    prev: &LumaPlane,
+    next: &LumaPlane,
+    guess: &FlowField,
+    window: i32,
+    iterations: u32,
+) -> FlowField {
+    let (w, h) = (prev.width as i32, prev.height as i32);
+    let gradient: Vec<[f32; 2]> = (0..h)
+        .flat_map(|y| (0..w).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            [
+                (prev.get(x + 1, y) - prev.get(x - 1, y)) / 2.0,
+                (prev.get(x, y + 1) - prev.get(x, y - 1)) / 2.0,
+            ]
+        })
+        .collect();
+    let grad = |x: i32, y: i32| gradient[(y.clamp(0, h - 1) * w + x.clamp(0, w - 1)) as usize];
+
+    let vectors = (0..h)
+        .into_par_iter()
+        .flat_map_iter(|y| {
+            let grad = &grad;
+            (0..w).map(move |x| {
+                let mut flow = guess.get(x, y);
+                let (mut gxx, mut gxy, mut gyy) = (0.0, 0.0, 0.0);
+                for dy in -window..=window {
+                    for dx in -window..=window {
+                        let [ix, iy] = grad(x + dx, y + dy);
+                        gxx += ix * ix;
+                        gxy += ix * iy;
+                        gyy += iy * iy;
+                    }
+                }
+                let det = gxx * gyy - gxy * gxy;
+                if det < MIN_STRUCTURE {
+                    return flow;
+                }
+                for _ in 0..iterations {
+                    let (mut bx, mut by) = (0.0, 0.0);
+                    for dy in -window..=window {
+                        for dx in -window..=window {
+                            let (px, py) = (x + dx, y + dy);
+                            let diff = prev.get(px, py)
+                                - next.sample(px as f32 + flow[0], py as f32 + flow[1]);
+                            let [ix, iy] = grad(px, py);
+                            bx += ix * diff;
+                            by += iy * diff;
+                        }
+                    }
+                    let du = (gyy * bx - gxy * by) / det;
+                    let dv = (gxx * by - gxy * bx) / det;
+                    flow[0] += du;
+                    flow[1] += dv;
+                    if du * du + dv * dv < 1e-4 {
+                        break;
+                    }
+                }
+                flow
+            })
+        })
+        .collect();
+    FlowField {
+        width: prev.width,
+        height: prev.height,
+        vectors,
+    }
+}
+
+/// Dense flow from `prev` to `next` by pyramidal Lucas–Kanade on luma.
+///
+/// `levels` halvings let the estimate follow motion of roughly
+/// `window * 2^levels` pixels; `window` is the half-size of the matching
+/// window. The result only depends on its inputs, so re-running it gives
+/// identical fields.
+pub fn estimate_flow<P: Pixel>(
+    prev: &Frame<P>,
+    next: &Frame<P>,
+    levels: u32,
+    window: u32,
+    iterations: u32,
+) -> FlowField {
+    let mut pyramid = vec![(LumaPlane::from_frame(prev), LumaPlane::from_frame(next))];
+    while pyramid.len() < levels.max(1) as usize {
+        let (a, b) = &pyramid[pyramid.len() - 1];
+        if a.width / 2 < MIN_LEVEL_SIZE || a.height / 2 < MIN_LEVEL_SIZE {
+            break;
+        }
+        let coarser = (downsample(a), downsample(b));
+        pyramid.push(coarser);
+    }
+
+    let (coarsest, _) = &pyramid[pyramid.len() - 1];
+    let mut flow = FlowField::zero(coarsest.width, coarsest.height);
+    for (a, b) in pyramid.iter().rev() {
+        if flow.width != a.width || flow.height != a.height {
+            flow = flow.upsample(a.width, a.height);
+        }
+        flow = refine_level(a, b, &flow, window as i32, iterations);
+    }
+    flow
+}
+
+/// In-between frame at `t` (0 = `a`, 1 = `b`) given the flow each way.
+///
+/// Each output pixel is fetched back along the flow from both frames and
+/// cross-faded, which keeps edges sharp where plain frame blending ghosts.
+pub fn interpolate_frames<P: Pixel>(
+    a: &Frame<P>,
+    b: &Frame<P>,
+    forward: &FlowField,
+    backward: &FlowField,
+    t: f32,
+) -> Frame<P> {
+    let t = t.clamp(0.0, 1.0);
+    let (w, h) = (a.width, a.height);
+    let (src_a, src_b) = (to_f32_vec(&a.data), to_f32_vec(&b.data));
+    let mut out = vec![0.0; src_a.len()];
+    out.par_chunks_mut((w * 4) as usize)
+        .enumerate()
+        .for_each(|(y, row)| {
+            for x in 0..w as usize {
+                let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+                let [fu, fv] = forward.get(x as i32, y as i32);
+                let [bu, bv] = backward.get(x as i32, y as i32);
+                let from_a = sample_rgba(
+                    &src_a,
+                    w,
+                    h,
+                    px - t * fu,
+                    py - t * fv,
+                    Interpolation::Bilinear,
+                    EdgeMode::Clamp,
+                );
+                let from_b = sample_rgba(
+                    &src_b,
+                    w,
+                    h,
+                    px - (1.0 - t) * bu,
+                    py - (1.0 - t) * bv,
+                    Interpolation::Bilinear,
+                    EdgeMode::Clamp,
+                );
+                for c in 0..4 {
+                    row[x * 4 + c] = from_a[c] * (1.0 - t) + from_b[c] * t;
+                }
+            }
+        });
+    Frame {
+        width: w,
+        height: h,
+        data: from_f32_vec(&out),
+    }
+}
+
+/// Smear a float RGBA frame along its flow vectors.
+///
+/// `shutter` is the fraction of each vector covered (1.0 = a 360° shutter)
+/// and the smear is centred on the pixel, like a camera exposing around the
+/// frame time.
+pub fn vector_blur(
+    src: &[f32],
+    width: u32,
+    height: u32,
+    flow: &FlowField,
+    shutter: f32,
+    samples: u32,
+) -> Vec<f32> {
+    let samples = samples.max(1);
+    warp_sum(src, width, height, samples, |x, y, i| {
+        let [u, v] = flow.sample(x - 0.5, y - 0.5);
+        let s = if samples == 1 {
+            0.0
+        } else {
+            shutter * (i as f32 / (samples - 1) as f32 - 0.5)
+        };
+        (x + s * u, y + s * v)
+    })
+}
+
+/// Average of `samples` warps of `src`
+fn warp_sum<F>(src: &[f32], width: u32, height: u32, samples: u32, map: F) -> Vec<f32>
+where
+    F: Fn(f32, f32, u32) -> (f32, f32) + Sync,
+{
+    let mut out = vec![0.0; src.len()];
+    for i in 0..samples {
+        let warped = warp_frame(
+            src,
+            width,
+            height,
+            Interpolation::Bilinear,
+            EdgeMode::Clamp,
+            |x, y| map(x, y, i),
+        );
+        out.iter_mut()
+            .zip(warped)
+            .for_each(|(o, v)| *o += v / samples as f32);
+    }
+    out
+}
+
+#[pyclass]
+#[derive(Clone)]
+pub struct OpticalFlow {
+    #[pyo3(get, set)]
+    pub levels: u32, // pyramid levels, 1 = full resolution only
+    #[pyo3(get, set)]
+    pub window: u32, // half-size of the matching window in px
+    #[pyo3(get, set)]
+    pub iterations: u32, // refinement steps per level
+}
+
+#[pymethods]
+impl OpticalFlow {
+    #[new]
+    fn new() -> Self {
+        Self {
+            levels: 4,
+            window: 3,
+            iterations: 5,
+        }
+    }
+
+    /// Flow from frame `a` to frame `b` as interleaved u, v floats
+    fn estimate(&self, a: Vec<u8>, b: Vec<u8>, width: u32, height: u32) -> PyResult<Vec<f32>> {
+        let a = Frame::from_raw(width, height, a)?;
+        let b = Frame::from_raw(width, height, b)?;
+        Ok(self.flow(&a, &b).to_interleaved())
+    }
+
+    /// Synthesise the frame at `t` between `a` (0.0) and `b` (1.0)
+    #[pyo3(name = "interpolate")]
+    fn py_interpolate(
+        &self,
+        a: Vec<u8>,
+        b: Vec<u8>,
+        width: u32,
+        height: u32,
+        t: f32,
+    ) -> PyResult<Vec<u8>> {
+        let a = Frame::from_raw(width, height, a)?;
+        let b = Frame::from_raw(width, height, b)?;
+        Ok(self.interpolate(&a, &b, t).data)
+    }
+
+    /// Motion-blur `pixels` along its flow towards `next_pixels`
+    #[pyo3(name = "motion_blur")]
+    fn py_motion_blur(
+        &self,
+        pixels: Vec<u8>,
+        next_pixels: Vec<u8>,
+        width: u32,
+        height: u32,
+        shutter: f32,
+        samples: u32,
+    ) -> PyResult<Vec<u8>> {
+        let frame = Frame::from_raw(width, height, pixels)?;
+        let next = Frame::from_raw(width, height, next_pixels)?;
+        Ok(self.motion_blur(&frame, &next, shutter, samples).data)
+    }
+}
+
+impl Default for OpticalFlow {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OpticalFlow {
+    pub fn flow<P: Pixel>(&self, prev: &Frame<P>, next: &Frame<P>) -> FlowField {
+        estimate_flow(prev, next, self.levels, self.window, self.iterations)
+    }
+
+    pub fn interpolate<P: Pixel>(&self, a: &Frame<P>, b: &Frame<P>, t: f32) -> Frame<P> {
+        let (forward, backward) = rayon::join(|| self.flow(a, b), || self.flow(b, a));
+        interpolate_frames(a, b, &forward, &backward, t)
+    }
+
+    pub fn motion_blur<P: Pixel>(
+        &self,
+        frame: &Frame<P>,
+        next: &Frame<P>,
+        shutter: f32,
+        samples: u32,
+    ) -> Frame<P> {
+        let flow = self.flow(frame, next);
+        let blurred = vector_blur(
+            &to_f32_vec(&frame.data),
+            frame.width,
+            frame.height,
+            &flow,
+            shutter,
+            samples,
+        );
+        Frame {
+            width: frame.width,
+            height: frame.height,
+            data: from_f32_vec(&blurred),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::motion_tracking::SampleRng;
+
+    /// 64x64 frame of soft blobs shifted by (dx, dy)
+    fn blobs(dx: f32, dy: f32) -> Frame<f32> {
+        let mut rng = SampleRng::new(5);
+        let centres: Vec<(f32, f32)> = (0..60)
+            .map(|_| (rng.below(640) as f32 / 10.0, rng.below(640) as f32 / 10.0))
+            .collect();
+        let mut frame = Frame::new(64, 64);
+        for y in 0..64 {
+            for x in 0..64 {
+                let v: f32 = centres
+                    .iter()
+                    .map(|(cx, cy)| {
+                        let d2 = (x as f32 - cx - dx).powi(2) + (y as f32 - cy - dy).powi(2);
+                        0.5 * (-d2 / 8.0).exp()
+                    })
+                    .sum();
+                let i = (y * 64 + x) * 4;
+                frame.data[i..i + 4].copy_from_slice(&[v, v, v, 1.0]);
+            }
+        }
+        frame
+    }
+
+    /// Mean flow over the pixels at least `margin` from the border
+    fn interior_mean(flow: &FlowField, margin: i32) -> [f32; 2] {
+        let mut sum = [0.0; 2];
+        let mut n = 0.0;
+        for y in margin..flow.height as i32 - margin {
+            for x in margin..flow.width as i32 - margin {
+                let [u, v] = flow.get(x, y);
+                sum[0] += u;
+                sum[1] += v;
+                n += 1.0;
+            }
+        }
+        [sum[0] / n, sum[1] / n]
+    }
+
+    #[test]
+    fn flow_recovers_translation_deterministically() {
+        let flow = OpticalFlow::new();
+        let (a, b) = (blobs(0.0, 0.0), blobs(5.5, -3.25));
+        let field = flow.flow(&a, &b);
+        let [u, v] = interior_mean(&field, 10);
+        assert!(
+            (u - 5.5).abs() < 0.15 && (v + 3.25).abs() < 0.15,
+            "{} {}",
+            u,
+            v
+        );
+        assert_eq!(field, flow.flow(&a, &b));
+    }
+
+    #[test]
+    fn interpolation_lands_between_frames() {
+        let flow = OpticalFlow::new();
+        let middle = flow.interpolate(&blobs(0.0, 0.0), &blobs(4.0, 2.0), 0.5);
+        let expected = blobs(2.0, 1.0);
+        let blended: Vec<f32> = blobs(0.0, 0.0)
+            .data
+            .iter()
+            .zip(&blobs(4.0, 2.0).data)
+            .map(|(a, b)| (a + b) / 2.0)
+            .collect();
+        let error = |data: &[f32]| {
+            let mut sum = 0.0;
+            for y in 8..56 {
+                for x in 8..56 {
+                    let i = (y * 64 + x) * 4;
+                    sum += (data[i] - expected.data[i]).abs();
+                }
+            }
+            sum / (48.0 * 48.0)
+        };
+        assert!(error(&middle.data) < 0.02, "{}", error(&middle.data));
+        assert!(error(&middle.data) < error(&blended) / 3.0);
+    }
+
+    #[test]
+    fn vector_blur_smears_along_motion() {
+        // Single bright column moving right by 8px
+        let mut src = vec![0.0; 32 * 32 * 4];
+        for y in 0..32 {
+            let i = (y * 32 + 16) * 4;
+            src[i..i + 4].copy_from_slice(&[1.0; 4]);
+        }
+        let still = vector_blur(&src, 32, 32, &FlowField::zero(32, 32), 1.0, 9);
+        assert!(still.iter().zip(&src).all(|(a, b)| (a - b).abs() < 1e-6));
+
+        let mut moving = FlowField::zero(32, 32);
+        moving.vectors.iter_mut().for_each(|v| *v = [8.0, 0.0]);
+        let blurred = vector_blur(&src, 32, 32, &moving, 1.0, 9);
+        let row = |x: usize| blurred[(10 * 32 + x) * 4];
+        assert!(row(16) < 0.5);
+        assert!(row(13) > 0.05 && row(19) > 0.05);
+        assert_eq!(blurred[(10 * 32 + 16) * 4 + 1], row(16));
+        assert!(row(25) < 1e-6);
+    }
+}
@@ -1,7 +1,9 @@
+use crate::color_management::{parse_space, ColorConfig};
+use crate::frame::{BitDepth, Frame, FrameError};
 use crate::scene::Scene;
 use pyo3::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::Path;
 
 #[pyclass]
 pub struct Renderer {
@@ -9,6 +11,8 @@ pub struct Renderer {
     height: u32,
     fps: u32,
     scenes: Vec<Scene>,
+    color: ColorConfig,
+    bit_depth: BitDepth,
 }
 
 #[pymethods]
@@ -20,6 +24,8 @@ impl Renderer {
             height,
             fps,
             scenes: Vec::new(),
+            color: ColorConfig::default(),
+            bit_depth: BitDepth::Eight,
         }
     }
 
@@ -27,6 +33,44 @@ impl Renderer {
         self.scenes.push(scene);
     }
 
+    fn set_color_management(&mut self, working_space: &str, output_space: &str) -> PyResult<()> {
+        self.color.working_space = parse_space(working_space)?.name().to_string();
+        self.color.output_space = parse_space(output_space)?.name().to_string();
+        Ok(())
+    }
+
+    fn set_color_config(&mut self, config: ColorConfig) {
+        self.color = config;
+    }
+
+    fn color_config(&self) -> ColorConfig {
+        self.color.clone()
+    }
+
+    /// Bits per channel of written frames: 8, 10, 16 (half float in EXR) or
+    /// 32 (float, EXR only)
+    fn set_bit_depth(&mut self, bits: u32) -> PyResult<()> {
+        self.bit_depth = BitDepth::from_bits(bits).ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Unsupported bit depth {}. Use 8, 10, 16 or 32",
+                bits
+            ))
+        })?;
+        Ok(())
+    }
+
+    fn bit_depth(&self) -> u32 {
+        self.bit_depth.bits()
+    }
+
+    /// Write one working-space RGBA frame (row-major floats at the render
+    /// size) through the output transform at the configured bit depth
+    fn save_frame(&self, data: Vec<f32>, path: &str) -> PyResult<()> {
+        let frame = Frame::from_raw(self.width, self.height, data)?;
+        self.write_frame(&frame, path)?;
+        Ok(())
+    }
+
     fn render(&self, output_path: &str) -> PyResult<()> {
         // Placeholder for actual rendering logic
         // This would use ffmpeg or wgpu to render frames
@@ -37,3 +81,76 @@ impl Renderer {
         Ok(())
     }
 }
+
+impl Renderer {
+    /// Every frame the renderer writes goes through here: it is converted
+    /// from the working space into the output space, then saved at the
+    /// configured bit depth.
+    pub fn write_frame<Q: AsRef<Path>>(
+        &self,
+        frame: &Frame<f32>,
+        path: Q,
+    ) -> Result<(), FrameError> {
+        if (frame.width, frame.height) != (self.width, self.height) {
+            return Err(FrameError::SizeMismatch {
+                expected: (self.width * self.height * 4) as usize,
+                actual: frame.data.len(),
+            });
+        }
+        let mut output = frame.clone();
+        self.color
+            .output_transform()
+            .apply_frame(&mut output.data, output.width, output.height);
+        output.save(path, self.bit_depth)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn written_frames_use_the_output_space_and_bit_depth() {
+        let dir = std::env::temp_dir().join(format!("fluxreel_renderer_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut renderer = Renderer::new(2, 1, 30);
+        let frame = Frame::from_raw(2, 1, vec![0.5, 0.2, 0.05, 1.0, 0.0, 0.0, 0.0, 1.0]).unwrap();
+
+        let path = dir.join("frame.png");
+        renderer.write_frame(&frame, &path).unwrap();
+        let expected = renderer.color.output_transform().convert([0.5, 0.2, 0.05]);
+        let written = Frame::open(&path).unwrap().pixel(0, 0);
+        for (w, e) in written.iter().zip(expected) {
+            assert!(
+                (w - e).abs() < 1.0 / 255.0,
+                "{:?} vs {:?}",
+                written,
+                expected
+            );
+        }
+        // Linear working values would be written noticeably darker
+        assert!(written[0] > 0.6);
+
+        renderer.set_bit_depth(32).unwrap();
+        assert!(renderer.write_frame(&frame, dir.join("float.png")).is_err());
+        renderer
+            .set_color_management("linear_srgb", "linear_srgb")
+            .unwrap();
+        renderer.color.tone_mapping = "clip".to_string();
+        let path = dir.join("frame.exr");
+        renderer.write_frame(&frame, &path).unwrap();
+        let written = Frame::open(&path).unwrap();
+        assert!(written
+            .data
+            .iter()
+            .zip(&frame.data)
+            .all(|(w, e)| (w - e).abs() < 1e-5));
+
+        let small = Frame::new(1, 1);
+        assert!(matches!(
+            renderer.write_frame(&small, dir.join("small.exr")),
+            Err(FrameError::SizeMismatch { .. })
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,197 @@
+use rayon::prelude::*;
+
+/// How samples that fall outside the frame are resolved
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeMode {
+    Clamp,
+    Wrap,
+    Mirror,
+    Transparent,
+}
+
+impl EdgeMode {
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "wrap" | "repeat" => EdgeMode::Wrap,
+            "mirror" => EdgeMode::Mirror,
+            "transparent" => EdgeMode::Transparent,
+            _ => EdgeMode::Clamp,
+        }
+    }
+}
+
+/// Reconstruction filter used when sampling between pixel centres
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interpolation {
+    Nearest,
+    Bilinear,
+    Bicubic,
+}
+
+impl Interpolation {
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "nearest" => Interpolation::Nearest,
+            "bicubic" | "cubic" => Interpolation::Bicubic,
+            _ => Interpolation::Bilinear,
+        }
+    }
+}
+
+fn resolve(coord: i32, size: i32, edge: EdgeMode) -> Option<i32> {
+    if (0..size).contains(&coord) {
+        return Some(coord);
+    }
+    match edge {
+        EdgeMode::Clamp => Some(coord.clamp(0, size - 1)),
+        EdgeMode::Wrap => Some(coord.rem_euclid(size)),
+        EdgeMode::Mirror => {
+            let m = coord.rem_euclid(2 * size);
+            Some(if m >= size { 2 * size - 1 - m } else { m })
+        }
+        EdgeMode::Transparent => None,
+    }
+}
+
+/// Fetch a single texel as premultiplied floats
+fn fetch(frame: &[f32], width: u32, height: u32, x: i32, y: i32, edge: EdgeMode) -> [f32; 4] {
+    let (Some(x), Some(y)) = (
+        resolve(x, width as i32, edge),
+        resolve(y, height as i32, edge),
+    ) else {
+        return [0.0; 4];
+    };
+    let i = ((y as u32 * width + x as u32) * 4) as usize;
+    let a = frame[i + 3];
+    [frame[i] * a, frame[i + 1] * a, frame[i + 2] * a, a]
+}
+
+fn catmull_rom(t: f32) -> [f32; 4] {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    [
+        -0.5 * t3 + t2 - 0.5 * t,
+        1.5 * t3 - 2.5 * t2 + 1.0,
+        -1.5 * t3 + 2.0 * t2 + 0.5 * t,
+        0.5 * t3 - 0.5 * t2,
+    ]
+}
+
+/// Sample a float RGBA frame at a continuous position (pixel centres sit at
+/// +0.5).
+///
+/// Filtering happens on premultiplied colour so transparent edges do not
+/// bleed dark fringes. Returns straight (non-premultiplied) values.
+pub fn sample_rgba(
+    frame: &[f32],
+    width: u32,
+    height: u32,
+    x: f32,
+    y: f32,
+    interpolation: Interpolation,
+    edge: EdgeMode,
+) -> [f32; 4] {
+    let sx = x - 0.5;
+    let sy = y - 0.5;
+
+    let premultiplied = match interpolation {
+        Interpolation::Nearest => fetch(
+            frame,
+            width,
+            height,
+            sx.round() as i32,
+            sy.round() as i32,
+            edge,
+        ),
+        Interpolation::Bilinear => {
+            let x0 = sx.floor();
+            let y0 = sy.floor();
+            let fx = sx - x0;
+            let fy = sy - y0;
+            let (x0, y0) = (x0 as i32, y0 as i32);
+            let p00 = fetch(frame, width, height, x0, y0, edge);
+            let p10 = fetch(frame, width, height, x0 + 1, y0, edge);
+            let p01 = fetch(frame, width, height, x0, y0 + 1, edge);
+            let p11 = fetch(frame, width, height, x0 + 1, y0 + 1, edge);
+            let mut out = [0.0; 4];
+            for c in 0..4 {
+                let top = p00[c] + (p10[c] - p00[c]) * fx;
+                let bottom = p01[c] + (p11[c] - p01[c]) * fx;
+                out[c] = top + (bottom - top) * fy;
+            }
+            out
+        }
+        Interpolation::Bicubic => {
+            let x0 = sx.floor();
+            let y0 = sy.floor();
+            let wx = catmull_rom(sx - x0);
+            let wy = catmull_rom(sy - y0);
+            let (x0, y0) = (x0 as i32, y0 as i32);
+            let mut out = [0.0; 4];
+            for (j, wyj) in wy.iter().enumerate() {
+                for (i, wxi) in wx.iter().enumerate() {
+                    let p = fetch(
+                        frame,
+                        width,
+                        height,
+                        x0 + i as i32 - 1,
+                        y0 + j as i32 - 1,
+                        edge,
+                    );
+                    let w = wxi * wyj;
+                    for c in 0..4 {
+                        out[c] += p[c] * w;
+                    }
+                }
+            }
+            // Catmull-Rom rings below zero; alpha also cannot exceed one
+            for v in out.iter_mut() {
+                *v = v.max(0.0);
+            }
+            out[3] = out[3].min(1.0);
+            out
+        }
+    };
+
+    let a = premultiplied[3];
+    if a <= 0.0 {
+        return [0.0; 4];
+    }
+    [
+        premultiplied[0] / a,
+        premultiplied[1] / a,
+        premultiplied[2] / a,
+        a,
+    ]
+}
+
+/// Resample a frame through a coordinate mapping.
+///
+/// `map` receives the centre of each destination pixel and returns the
+/// source position to sample. Rows are processed in parallel.
+pub fn warp_frame<F>(
+    src: &[f32],
+    width: u32,
+    height: u32,
+    interpolation: Interpolation,
+    edge: EdgeMode,
+    map: F,
+) -> Vec<f32>
+where
+    F: Fn(f32, f32) -> (f32, f32) + Sync,
+{
+    let mut out = vec![0.0; src.len()];
+    if width == 0 || height == 0 {
+        return out;
+    }
+    out.par_chunks_mut((width * 4) as usize)
+        .enumerate()
+        .for_each(|(y, row)| {
+            for x in 0..width as usize {
+                let (sx, sy) = map(x as f32 + 0.5, y as f32 + 0.5);
+                let p = sample_rgba(src, width, height, sx, sy, interpolation, edge);
+                row[x * 4..x * 4 + 4].copy_from_slice(&p);
+            }
+        });
+    out
+}
@@ -1,3 +1,8 @@
+use crate::blend_modes::blend_frames;
+use crate::color_management::ColorSpace;
+use crate::frame::Pixel;
+use crate::masking::track_matte_alpha;
+use crate::node::Node;
 use pyo3::prelude::*;
 use serde::{Deserialize, Serialize};
 
@@ -36,3 +41,137 @@ impl Scene {
         self.duration = duration;
     }
 }
+
+/// A rendered layer: its node and its RGBA frame at the composition size
+pub struct Layer<'a, P: Pixel> {
+    pub node: &'a Node,
+    pub pixels: &'a [P],
+}
+
+/// Composite layers bottom to top over transparent black.
+///
+/// Each layer's masks are evaluated at `time` and, when it has a track
+/// matte, the named layer's alpha or luma (after that layer's own masks)
+/// limits where it shows. Layers used as a track matte are not drawn.
+pub fn composite_layers<P: Pixel>(
+    layers: &[Layer<P>],
+    width: u32,
+    height: u32,
+    time: f32,
+    space: ColorSpace,
+) -> Vec<P> {
+    let size = (width * height * 4) as usize;
+    let mut out = vec![P::default(); size];
+    let masked = |layer: &Layer<P>| {
+        let mut pixels = layer.pixels.to_vec();
+        if let Some(matte) = layer.node.masks.matte(width, height, time) {
+            matte.apply(&mut pixels);
+        }
+        pixels
+    };
+    let is_matte = |id: &str| {
+        layers
+            .iter()
+            .any(|l| l.node.track_matte.as_ref().is_some_and(|m| m.source == id))
+    };
+
+    for layer in layers {
+        let node = layer.node;
+        if !node.properties.visible || layer.pixels.len() != size || is_matte(&node.id) {
+            continue;
+        }
+        let mut pixels = masked(layer);
+        if let Some(matte) = &node.track_matte {
+            match layers.iter().find(|l| l.node.id == matte.source) {
+                Some(source) if source.pixels.len() == size => {
+                    track_matte_alpha(&masked(source), width, height, matte.mode)
+                        .apply(&mut pixels);
+                }
+                // A missing matte layer leaves nothing visible
+                _ => continue,
+            }
+        }
+        blend_frames(
+            &mut out,
+            &pixels,
+            width,
+            height,
+            "normal",
+            node.properties.opacity,
+            space,
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::masking::{MaskStack, TrackMatte, TrackMatteMode};
+    use crate::node::NodeProperties;
+
+    fn node(id: &str, track_matte: Option<TrackMatte>) -> Node {
+        Node {
+            id: id.to_string(),
+            properties: NodeProperties::default(),
+            masks: MaskStack::default(),
+            track_matte,
+            track_binding: None,
+        }
+    }
+
+    #[test]
+    fn track_matte_limits_the_layer_below_it() {
+        let matte = |mode| {
+            Some(TrackMatte {
+                source: "text".to_string(),
+                mode,
+            })
+        };
+        let background = node("background", None);
+        let fill = node("fill", matte(TrackMatteMode::Alpha));
+        let text = node("text", None);
+
+        // Opaque grey background, opaque red fill, "text" covering the left
+        // pixel only
+        let grey = [0.5f32, 0.5, 0.5, 1.0, 0.5, 0.5, 0.5, 1.0];
+        let red = [1.0f32, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0];
+        let glyph = [1.0f32, 1.0, 1.0, 1.0, 0.0, 0.0, 0.0, 0.0];
+        let layers = [
+            Layer {
+                node: &background,
+                pixels: &grey,
+            },
+            Layer {
+                node: &fill,
+                pixels: &red,
+            },
+            Layer {
+                node: &text,
+                pixels: &glyph,
+            },
+        ];
+        let out = composite_layers(&layers, 2, 1, 0.0, ColorSpace::LinearSrgb);
+        assert_eq!(&out[..4], &[1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(&out[4..], &[0.5, 0.5, 0.5, 1.0]);
+
+        let fill = node("fill", matte(TrackMatteMode::LumaInverted));
+        let layers = [
+            Layer {
+                node: &background,
+                pixels: &grey,
+            },
+            Layer {
+                node: &fill,
+                pixels: &red,
+            },
+            Layer {
+                node: &text,
+                pixels: &glyph,
+            },
+        ];
+        let out = composite_layers(&layers, 2, 1, 0.0, ColorSpace::LinearSrgb);
+        assert_eq!(&out[..4], &[0.5, 0.5, 0.5, 1.0]);
+        assert_eq!(&out[4..], &[1.0, 0.0, 0.0, 1.0]);
+    }
+}
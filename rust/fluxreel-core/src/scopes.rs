@@ -0,0 +1,321 @@
+use crate::frame::{Frame, Pixel};
+use pyo3::prelude::*;
+use rayon::prelude::*;
+
+/// Rec.709 luma weights
+const LUMA: [f32; 3] = [0.2126, 0.7152, 0.0722];
+
+fn luma(r: f32, g: f32, b: f32) -> f32 {
+    LUMA[0] * r + LUMA[1] * g + LUMA[2] * b
+}
+
+/// Bin of a 0-1 value; out-of-range values land in the end bins
+fn bin(v: f32, bins: usize) -> usize {
+    ((v.clamp(0.0, 1.0) * (bins - 1) as f32).round() as usize).min(bins - 1)
+}
+
+/// Per-channel and luma value counts
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    pub red: Vec<u32>,
+    pub green: Vec<u32>,
+    pub blue: Vec<u32>,
+    pub luma: Vec<u32>,
+}
+
+impl Histogram {
+    fn empty(bins: usize) -> Self {
+        Self {
+            red: vec![0; bins],
+            green: vec![0; bins],
+            blue: vec![0; bins],
+            luma: vec![0; bins],
+        }
+    }
+
+    fn merge(mut self, other: Self) -> Self {
+        for (a, b) in [
+            (&mut self.red, &other.red),
+            (&mut self.green, &other.green),
+            (&mut self.blue, &other.blue),
+            (&mut self.luma, &other.luma),
+        ] {
+            a.iter_mut().zip(b).for_each(|(x, y)| *x += y);
+        }
+        self
+    }
+}
+
+/// Histogram of an RGBA frame. Values are clamped to 0-1 before binning.
+pub fn histogram<P: Pixel>(frame: &[P], bins: usize) -> Histogram {
+    let bins = bins.max(2);
+    frame
+        .par_chunks(4)
+        .fold(
+            || Histogram::empty(bins),
+            |mut h, px| {
+                let (r, g, b) = (px[0].to_f32(), px[1].to_f32(), px[2].to_f32());
+                h.red[bin(r, bins)] += 1;
+                h.green[bin(g, bins)] += 1;
+                h.blue[bin(b, bins)] += 1;
+                h.luma[bin(luma(r, g, b), bins)] += 1;
+                h
+            },
+        )
+        .reduce(|| Histogram::empty(bins), Histogram::merge)
+}
+
+/// Hit counts on a scope graticule; row 0 is the top of the display
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScopeImage {
+    pub width: u32,
+    pub height: u32,
+    pub counts: Vec<u32>,
+}
+
+impl ScopeImage {
+    fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            counts: vec![0; (width * height) as usize],
+        }
+    }
+
+    pub fn count(&self, x: u32, y: u32) -> u32 {
+        self.counts[(y * self.width + x) as usize]
+    }
+
+    /// Trace brightness on a log scale so sparse values stay visible
+    fn intensity(&self, count: u32, max: u32) -> f32 {
+        if count == 0 || max == 0 {
+            0.0
+        } else {
+            (count as f32).ln_1p() / (max as f32).ln_1p()
+        }
+    }
+
+    /// Render the trace in `tint` on black
+    pub fn render(&self, tint: [f32; 3]) -> Frame<u8> {
+        let max = self.counts.iter().copied().max().unwrap_or(0);
+        let mut data = Vec::with_capacity(self.counts.len() * 4);
+        for &c in &self.counts {
+            let k = self.intensity(c, max);
+            data.extend(tint.map(|t| u8::from_f32(t * k)));
+            data.push(255);
+        }
+        Frame {
+            width: self.width,
+            height: self.height,
+            data,
+        }
+    }
+}
+
+/// Accumulate (column, level) hits for one value per pixel
+fn trace<P, F>(frame: &[P], width: u32, levels: u32, value: F) -> ScopeImage
+where
+    P: Pixel,
+    F: Fn(&[P]) -> f32 + Sync,
+{
+    let w = width as usize;
+    let rows = frame.len() / 4 / w.max(1);
+    let mut scope = ScopeImage::new(width, levels);
+    let columns: Vec<Vec<u32>> = (0..w)
+        .into_par_iter()
+        .map(|x| {
+            let mut column = vec![0u32; levels as usize];
+            for y in 0..rows {
+                let i = (y * w + x) * 4;
+                let level = bin(value(&frame[i..i + 4]), levels as usize);
+                column[levels as usize - 1 - level] += 1;
+            }
+            column
+        })
+        .collect();
+    for (x, column) in columns.iter().enumerate() {
+        for (y, c) in column.iter().enumerate() {
+            scope.counts[y * w + x] = *c;
+        }
+    }
+    scope
+}
+
+/// Luma waveform: one column per frame column, `levels` rows from 1.0 (top)
+/// to 0.0 (bottom)
+pub fn waveform<P: Pixel>(frame: &[P], width: u32, height: u32, levels: u32) -> ScopeImage {
+    debug_assert_eq!(frame.len(), (width * height * 4) as usize);
+    trace(frame, width, levels.max(2), |px| {
+        luma(px[0].to_f32(), px[1].to_f32(), px[2].to_f32())
+    })
+}
+
+/// Red, green and blue waveforms side by side
+#[derive(Debug, Clone, PartialEq)]
+pub struct RgbParade {
+    pub red: ScopeImage,
+    pub green: ScopeImage,
+    pub blue: ScopeImage,
+}
+
+impl RgbParade {
+    /// The three traces next to each other in their channel colours
+    pub fn render(&self) -> Frame<u8> {
+        let parts = [
+            self.red.render([1.0, 0.2, 0.2]),
+            self.green.render([0.2, 1.0, 0.2]),
+            self.blue.render([0.3, 0.4, 1.0]),
+        ];
+        let (w, h) = (self.red.width as usize, self.red.height as usize);
+        let mut out = Frame::new(self.red.width * 3, self.red.height);
+        for (i, part) in parts.iter().enumerate() {
+            for y in 0..h {
+                let src = &part.data[y * w * 4..(y + 1) * w * 4];
+                let dst = (y * w * 3 + i * w) * 4;
+                out.data[dst..dst + w * 4].copy_from_slice(src);
+            }
+        }
+        out
+    }
+}
+
+pub fn rgb_parade<P: Pixel>(frame: &[P], width: u32, height: u32, levels: u32) -> RgbParade {
+    debug_assert_eq!(frame.len(), (width * height * 4) as usize);
+    let levels = levels.max(2);
+    RgbParade {
+        red: trace(frame, width, levels, |px| px[0].to_f32()),
+        green: trace(frame, width, levels, |px| px[1].to_f32()),
+        blue: trace(frame, width, levels, |px| px[2].to_f32()),
+    }
+}
+
+/// Vectorscope: BT.709 Cb (x) against Cr (y, up) on a `size` square, with
+/// neutral at the centre and ±0.5 chroma at the edges
+pub fn vectorscope<P: Pixel>(frame: &[P], width: u32, height: u32, size: u32) -> ScopeImage {
+    debug_assert_eq!(frame.len(), (width * height * 4) as usize);
+    let size = size.max(2);
+    let hits: Vec<usize> = frame
+        .par_chunks(4)
+        .map(|px| {
+            let (r, g, b) = (px[0].to_f32(), px[1].to_f32(), px[2].to_f32());
+            let y = luma(r, g, b);
+            let cb = (b - y) / 1.8556;
+            let cr = (r - y) / 1.5748;
+            let x = bin(cb + 0.5, size as usize);
+            let row = size as usize - 1 - bin(cr + 0.5, size as usize);
+            row * size as usize + x
+        })
+        .collect();
+    let mut scope = ScopeImage::new(size, size);
+    for i in hits {
+        scope.counts[i] += 1;
+    }
+    scope
+}
+
+fn frame_from_bytes(pixels: Vec<u8>, width: u32, height: u32) -> PyResult<Frame<u8>> {
+    Frame::from_raw(width, height, pixels).map_err(PyErr::from)
+}
+
+/// Red, green, blue and luma counts
+type HistogramCounts = (Vec<u32>, Vec<u32>, Vec<u32>, Vec<u32>);
+
+/// Histogram of an RGBA8 frame as (red, green, blue, luma) count lists
+#[pyfunction]
+pub fn scope_histogram(
+    pixels: Vec<u8>,
+    width: u32,
+    height: u32,
+    bins: usize,
+) -> PyResult<HistogramCounts> {
+    let frame = frame_from_bytes(pixels, width, height)?;
+    let h = histogram(&frame.data, bins);
+    Ok((h.red, h.green, h.blue, h.luma))
+}
+
+/// Luma waveform rendered as RGBA8 bytes of `width` x `levels`
+#[pyfunction]
+pub fn scope_waveform(pixels: Vec<u8>, width: u32, height: u32, levels: u32) -> PyResult<Vec<u8>> {
+    let frame = frame_from_bytes(pixels, width, height)?;
+    Ok(waveform(&frame.data, width, height, levels)
+        .render([0.4, 1.0, 0.4])
+        .data)
+}
+
+/// RGB parade rendered as RGBA8 bytes of `3 * width` x `levels`
+#[pyfunction]
+pub fn scope_rgb_parade(
+    pixels: Vec<u8>,
+    width: u32,
+    height: u32,
+    levels: u32,
+) -> PyResult<Vec<u8>> {
+    let frame = frame_from_bytes(pixels, width, height)?;
+    Ok(rgb_parade(&frame.data, width, height, levels).render().data)
+}
+
+/// Vectorscope rendered as RGBA8 bytes of `size` x `size`
+#[pyfunction]
+pub fn scope_vectorscope(pixels: Vec<u8>, width: u32, height: u32, size: u32) -> PyResult<Vec<u8>> {
+    let frame = frame_from_bytes(pixels, width, height)?;
+    Ok(vectorscope(&frame.data, width, height, size)
+        .render([1.0, 1.0, 1.0])
+        .data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Horizontal black-to-white ramp
+    fn ramp(width: u32, height: u32) -> Vec<f32> {
+        (0..height)
+            .flat_map(|_| {
+                (0..width).flat_map(move |x| {
+                    let v = x as f32 / (width - 1) as f32;
+                    [v, v, v, 1.0]
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn histogram_counts_every_pixel() {
+        let frame = ramp(256, 2);
+        let h = histogram(&frame, 256);
+        assert!(h.luma.iter().all(|&c| c == 2));
+        assert_eq!(h.red.iter().sum::<u32>(), 512);
+    }
+
+    #[test]
+    fn waveform_of_ramp_is_a_diagonal() {
+        let scope = waveform(&ramp(8, 4), 8, 4, 8);
+        // Black on the left at the bottom, white on the right at the top
+        assert_eq!(scope.count(0, 7), 4);
+        assert_eq!(scope.count(7, 0), 4);
+        assert_eq!(scope.counts.iter().sum::<u32>(), 32);
+    }
+
+    #[test]
+    fn neutral_pixels_sit_at_vectorscope_centre() {
+        let scope = vectorscope(&ramp(16, 1), 16, 1, 65);
+        assert_eq!(scope.count(32, 32), 16);
+
+        // Pure red points up and to the left of blue's axis
+        let red = vectorscope(&[1.0f32, 0.0, 0.0, 1.0], 1, 1, 65);
+        let hit = red.counts.iter().position(|&c| c == 1).unwrap() as u32;
+        let (x, y) = (hit % 65, hit / 65);
+        assert!(x < 32 && y < 32);
+    }
+
+    #[test]
+    fn parade_separates_channels() {
+        let frame = [1.0f32, 0.0, 0.5, 1.0];
+        let parade = rgb_parade(&frame, 1, 1, 3);
+        assert_eq!(parade.red.count(0, 0), 1);
+        assert_eq!(parade.green.count(0, 2), 1);
+        assert_eq!(parade.blue.count(0, 1), 1);
+        let image = parade.render();
+        assert_eq!((image.width, image.height), (3, 3));
+    }
+}
@@ -1,3 +1,6 @@
+use crate::frame::{from_f32_vec, Frame, Pixel};
+use crate::optical_flow::OpticalFlow;
+use crate::time_stretch::{varispeed, wsola};
 use pyo3::prelude::*;
 use serde::{Deserialize, Serialize};
 
@@ -40,6 +43,66 @@ impl SpeedRamp {
     }
 
     fn get_speed_at_time(&self, time: f32) -> f32 {
+        self.speed_at(time)
+    }
+
+    /// Source time (seconds) shown at an output time
+    fn source_time_at(&self, time: f32) -> f32 {
+        self.source_time(time)
+    }
+
+    /// Output length needed to play `source_duration` seconds of source, or
+    /// None if the ramp stops before reaching the end
+    fn output_duration(&self, source_duration: f32) -> Option<f32> {
+        self.output_time(source_duration)
+    }
+
+    /// Source frames and blend weights that make up an output frame
+    fn source_frames(&self, output_frame: u32, fps: f32) -> PyResult<Vec<(u32, f32)>> {
+        check_fps(fps)?;
+        Ok(self.frame_weights(output_frame, fps))
+    }
+
+    /// Render an output frame from decoded RGBA8 source frames
+    #[pyo3(name = "render_frame")]
+    fn py_render_frame(
+        &self,
+        frames: Vec<Vec<u8>>,
+        width: u32,
+        height: u32,
+        output_frame: u32,
+        fps: f32,
+    ) -> PyResult<Vec<u8>> {
+        check_fps(fps)?;
+        let frames = frames
+            .into_iter()
+            .map(|pixels| Frame::from_raw(width, height, pixels))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.render_frame(&frames, output_frame, fps)
+            .map(|frame| frame.data)
+            .ok_or_else(|| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>("No source frames to render from")
+            })
+    }
+}
+
+fn check_fps(fps: f32) -> PyResult<()> {
+    if fps <= 0.0 || !fps.is_finite() {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "fps must be positive",
+        ));
+    }
+    Ok(())
+}
+
+/// Subintervals per keyframe segment when integrating speed
+const INTEGRATION_STEPS: usize = 64;
+/// Upper bound (seconds) searched when inverting the time mapping
+const MAX_OUTPUT_TIME: f32 = 1.0e6;
+
+impl SpeedRamp {
+    /// Instantaneous speed at an output time
+    pub fn speed_at(&self, time: f32) -> f32 {
         if self.keyframes.is_empty() {
             return 1.0;
         }
@@ -66,14 +129,171 @@ impl SpeedRamp {
 
         self.keyframes[self.keyframes.len() - 1].speed
     }
+
+    /// Integral of speed from 0 to `time`. Negative speeds count as stopped;
+    /// reverse playback is expressed with time-remap curves instead.
+    pub fn source_time(&self, time: f32) -> f32 {
+        if time <= 0.0 {
+            return 0.0;
+        }
+        let mut bounds = vec![0.0];
+        bounds.extend(
+            self.keyframes
+                .iter()
+                .map(|k| k.time)
+                .filter(|&t| t > 0.0 && t < time),
+        );
+        bounds.push(time);
+        bounds
+            .windows(2)
+            .map(|pair| self.integrate(pair[0], pair[1]))
+            .sum()
+    }
+
+    /// Midpoint rule over a span without keyframes inside it. Speed is
+    /// never sampled on the span's ends, where stacked keyframes make it
+    /// ambiguous.
+    fn integrate(&self, from: f32, to: f32) -> f32 {
+        let h = (to - from) / INTEGRATION_STEPS as f32;
+        (0..INTEGRATION_STEPS)
+            .map(|i| self.speed_at(from + h * (i as f32 + 0.5)).max(0.0))
+            .sum::<f32>()
+            * h
+    }
+
+    /// Output time at which `source_time` is reached
+    pub fn output_time(&self, source_time: f32) -> Option<f32> {
+        if source_time <= 0.0 {
+            return Some(0.0);
+        }
+        let mut high = 1.0;
+        while self.source_time(high) < source_time {
+            high *= 2.0;
+            if high > MAX_OUTPUT_TIME {
+                return None;
+            }
+        }
+        let mut low = 0.0;
+        for _ in 0..48 {
+            let mid = (low + high) / 2.0;
+            if self.source_time(mid) < source_time {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        Some(high)
+    }
+
+    /// Source frames and weights for an output frame.
+    ///
+    /// Without `frame_blending` this is the nearest source frame. With it,
+    /// slow motion cross-fades the two frames either side of the source
+    /// time and fast motion averages every source frame the output frame's
+    /// interval passes over.
+    pub fn frame_weights(&self, output_frame: u32, fps: f32) -> Vec<(u32, f32)> {
+        debug_assert!(fps > 0.0, "fps must be positive");
+        let start = self.source_time(output_frame as f32 / fps) * fps;
+        if !self.frame_blending {
+            return vec![(start.round().max(0.0) as u32, 1.0)];
+        }
+        let end = self.source_time((output_frame + 1) as f32 / fps) * fps;
+        if end - start <= 1.0 {
+            let base = start.floor();
+            let t = start - base;
+            let base = base.max(0.0) as u32;
+            if t < 1e-4 {
+                return vec![(base, 1.0)];
+            }
+            return vec![(base, 1.0 - t), (base + 1, t)];
+        }
+        // Box filter over [start, end) with partial coverage at the ends
+        let span = end - start;
+        let mut weights = Vec::new();
+        let mut frame = start.floor();
+        while frame < end {
+            let covered = (frame + 1.0).min(end) - frame.max(start);
+            if covered > 1e-4 {
+                weights.push((frame as u32, covered / span));
+            }
+            frame += 1.0;
+        }
+        weights
+    }
+
+    /// Output frame built from `frames` (indexed by source frame number,
+    /// out-of-range indices hold the last frame). Two-frame blends use
+    /// optical flow when `optical_flow` is set.
+    pub fn render_frame<P: Pixel>(
+        &self,
+        frames: &[Frame<P>],
+        output_frame: u32,
+        fps: f32,
+    ) -> Option<Frame<P>> {
+        let last = frames.len().checked_sub(1)?;
+        let fetch = |i: u32| &frames[(i as usize).min(last)];
+        let weights = self.frame_weights(output_frame, fps);
+        if let [(a, wa), (b, wb)] = weights[..] {
+            if self.optical_flow && b == a + 1 {
+                let flow = OpticalFlow::default();
+                return Some(flow.interpolate(fetch(a), fetch(b), wb / (wa + wb)));
+            }
+        }
+        let first = fetch(weights[0].0);
+        let mut out = vec![0.0; first.data.len()];
+        for (index, weight) in weights {
+            for (o, v) in out.iter_mut().zip(&fetch(index).data) {
+                *o += v.to_f32() * weight;
+            }
+        }
+        Some(Frame {
+            width: first.width,
+            height: first.height,
+            data: from_f32_vec(&out),
+        })
+    }
+}
+
+/// Direct mapping point: at output `time` the clip shows `source_time`.
+/// Keys sharing a time make the source jump, which is how reversed
+/// segments connect to their neighbours.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemapKeyframe {
+    pub time: f32,
+    pub source_time: f32,
+    pub ease: String, // towards the next key
+}
+
+/// Ease that plays the same shape backwards in time
+fn reversed_ease(ease: &str) -> String {
+    if ease.ends_with("_in_out") {
+        ease.to_string()
+    } else if let Some(stem) = ease.strip_suffix("_in") {
+        format!("{}_out", stem)
+    } else if let Some(stem) = ease.strip_suffix("_out") {
+        format!("{}_in", stem)
+    } else if ease == "elastic" {
+        "elastic_in".to_string()
+    } else {
+        ease.to_string()
+    }
 }
 
+/// Points per segment checked when validating eased curves, which may
+/// overshoot their keys
+const VALIDATION_SAMPLES: usize = 16;
+/// Keys per speed keyframe segment when a speed ramp is baked into a curve
+const BAKE_STEPS: usize = 16;
+
 #[pyclass]
 #[derive(Clone)]
 pub struct TimeRemap {
     pub speed_ramp: SpeedRamp,
+    pub keyframes: Vec<RemapKeyframe>,
     #[pyo3(get, set)]
     pub maintain_pitch: bool,
+    #[pyo3(get, set)]
+    pub source_duration: Option<f32>, // seconds; enables validation
 }
 
 #[pymethods]
@@ -82,7 +302,9 @@ impl TimeRemap {
     fn new() -> Self {
         Self {
             speed_ramp: SpeedRamp::new(),
+            keyframes: Vec::new(),
             maintain_pitch: false,
+            source_duration: None,
         }
     }
 
@@ -90,4 +312,525 @@ impl TimeRemap {
         self.speed_ramp
             .add_keyframe(time, speed, "linear".to_string());
     }
+
+    /// Keyframe the source time directly. Once any are set they replace
+    /// the speed ramp; after the last key the clip holds its final frame.
+    #[pyo3(signature = (time, source_time, ease="linear".to_string()))]
+    fn add_remap_keyframe(&mut self, time: f32, source_time: f32, ease: String) -> PyResult<()> {
+        let mut keyframes = self.keyframes.clone();
+        let index = keyframes.partition_point(|k| k.time <= time);
+        keyframes.insert(
+            index,
+            RemapKeyframe {
+                time,
+                source_time,
+                ease,
+            },
+        );
+        self.commit(keyframes)
+    }
+
+    fn clear_remap_keyframes(&mut self) {
+        self.keyframes.clear();
+    }
+
+    /// Hold the frame shown at `time` for `length` seconds, pushing the
+    /// rest of the clip later
+    fn freeze_frame(&mut self, time: f32, length: f32) -> PyResult<()> {
+        if length <= 0.0 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "Freeze length must be positive",
+            ));
+        }
+        let mut keyframes = self.curve()?;
+        let held = self.curve_at(&keyframes, time);
+        let ease = Self::ease_at(&keyframes, time);
+        let split = keyframes.partition_point(|k| k.time <= time);
+        let mut after = keyframes.split_off(split);
+        after.iter_mut().for_each(|k| k.time += length);
+        keyframes.push(RemapKeyframe {
+            time,
+            source_time: held,
+            ease: "linear".to_string(),
+        });
+        keyframes.push(RemapKeyframe {
+            time: time + length,
+            source_time: held,
+            ease,
+        });
+        keyframes.extend(after);
+        self.commit(keyframes)
+    }
+
+    /// Play the output span `start`..`end` backwards in place
+    fn reverse(&mut self, start: f32, end: f32) -> PyResult<()> {
+        if end <= start {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "Reverse range must have end after start",
+            ));
+        }
+        let curve = self.curve()?;
+        let split = |keys: &[RemapKeyframe], t: f32| RemapKeyframe {
+            time: t,
+            source_time: self.curve_at(keys, t),
+            ease: Self::ease_at(keys, t),
+        };
+        let mut inner = vec![split(&curve, start)];
+        inner.extend(
+            curve
+                .iter()
+                .filter(|k| k.time > start && k.time < end)
+                .cloned(),
+        );
+        inner.push(split(&curve, end));
+
+        let mut keyframes: Vec<RemapKeyframe> =
+            curve.iter().filter(|k| k.time < start).cloned().collect();
+        // Arrive at the span's first frame, jump to its last and play back
+        keyframes.push(RemapKeyframe {
+            ease: "linear".to_string(),
+            ..inner[0].clone()
+        });
+        for pair in inner.windows(2).rev() {
+            keyframes.push(RemapKeyframe {
+                time: start + end - pair[1].time,
+                source_time: pair[1].source_time,
+                ease: reversed_ease(&pair[0].ease),
+            });
+        }
+        keyframes.push(RemapKeyframe {
+            time: end,
+            source_time: inner[0].source_time,
+            ease: "linear".to_string(),
+        });
+        keyframes.push(inner[inner.len() - 1].clone());
+        keyframes.extend(curve.iter().filter(|k| k.time > end).cloned());
+        self.commit(keyframes)
+    }
+
+    /// Play the clip forwards then backwards, doubling its length
+    fn boomerang(&mut self) -> PyResult<()> {
+        let mut keyframes = self.curve()?;
+        let n = keyframes.len().saturating_sub(1);
+        if n == 0 {
+            return Ok(());
+        }
+        // Each segment on the way back mirrors one on the way out
+        let turn = keyframes[n].time;
+        let back: Vec<RemapKeyframe> = (0..n)
+            .rev()
+            .map(|j| RemapKeyframe {
+                time: 2.0 * turn - keyframes[j].time,
+                source_time: keyframes[j].source_time,
+                ease: match j {
+                    0 => "linear".to_string(),
+                    _ => reversed_ease(&keyframes[j - 1].ease),
+                },
+            })
+            .collect();
+        keyframes[n].ease = reversed_ease(&keyframes[n - 1].ease);
+        keyframes.extend(back);
+        self.commit(keyframes)
+    }
+
+    /// Check the mapping stays within `source_duration`
+    fn validate(&self) -> PyResult<()> {
+        self.check(&self.keyframes)
+    }
+
+    /// Source time (seconds) shown at an output time
+    fn source_time_at(&self, time: f32) -> f32 {
+        self.source_time(time)
+    }
+
+    /// First output time showing `source_time`, or None if it never shows
+    #[pyo3(name = "output_time")]
+    fn py_output_time(&self, source_time: f32) -> Option<f32> {
+        self.output_time(source_time)
+    }
+
+    /// Length of the retimed clip for `source_duration` seconds of source,
+    /// or None if playback stops before reaching the end
+    #[pyo3(name = "output_duration")]
+    fn py_output_duration(&self, source_duration: f32) -> Option<f32> {
+        self.output_duration(source_duration)
+    }
+
+    /// Retime interleaved audio along the same curve as the picture:
+    /// pitch-preserving (WSOLA) when `maintain_pitch` is set, varispeed
+    /// otherwise
+    #[pyo3(name = "retime_audio")]
+    fn py_retime_audio(
+        &self,
+        samples: Vec<f32>,
+        channels: usize,
+        sample_rate: u32,
+    ) -> PyResult<Vec<f32>> {
+        self.retime_audio(&samples, channels, sample_rate)
+    }
+
+    #[getter]
+    fn get_remap_keyframes(&self) -> Vec<(f32, f32, String)> {
+        self.keyframes
+            .iter()
+            .map(|k| (k.time, k.source_time, k.ease.clone()))
+            .collect()
+    }
+}
+
+impl Default for TimeRemap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimeRemap {
+    pub fn source_time(&self, time: f32) -> f32 {
+        if self.keyframes.is_empty() {
+            self.speed_ramp.source_time(time)
+        } else {
+            self.curve_at(&self.keyframes, time)
+        }
+    }
+
+    /// First output time at which the clip shows `source_time`, or None if
+    /// it never does. Remap curves are searched key by key, so footage
+    /// that plays twice (reversed or boomeranged) gives its first showing.
+    pub fn output_time(&self, source_time: f32) -> Option<f32> {
+        let Some(first) = self.keyframes.first() else {
+            return self.speed_ramp.output_time(source_time);
+        };
+        if (first.source_time - source_time).abs() < 1e-6 {
+            return Some(first.time);
+        }
+        self.keyframes.windows(2).find_map(|pair| {
+            let (a, b) = (pair[0].source_time, pair[1].source_time);
+            // Stacked keys jump over the frames between them
+            if pair[1].time <= pair[0].time
+                || !(a.min(b) - 1e-6..=a.max(b) + 1e-6).contains(&source_time)
+            {
+                return None;
+            }
+            let (mut low, mut high) = (pair[0].time, pair[1].time);
+            let rising = b >= a;
+            for _ in 0..40 {
+                let mid = (low + high) / 2.0;
+                if (self.curve_at(&self.keyframes, mid) < source_time) == rising {
+                    low = mid;
+                } else {
+                    high = mid;
+                }
+            }
+            Some(high)
+        })
+    }
+
+    /// Output length of the remap curve, which ends at its last key. None
+    /// without remap keys, where the length depends on the source.
+    pub fn curve_duration(&self) -> Option<f32> {
+        self.keyframes.last().map(|k| k.time)
+    }
+
+    /// Length of the retimed clip for `source_duration` seconds of source:
+    /// the remap curve when keyed, else the time the speed ramp takes to
+    /// reach the end. None if the ramp never gets there.
+    pub fn output_duration(&self, source_duration: f32) -> Option<f32> {
+        self.curve_duration()
+            .or_else(|| self.speed_ramp.output_time(source_duration))
+    }
+
+    pub fn retime_audio(
+        &self,
+        samples: &[f32],
+        channels: usize,
+        sample_rate: u32,
+    ) -> PyResult<Vec<f32>> {
+        if channels == 0 || !samples.len().is_multiple_of(channels) {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "{} samples do not divide into {} channels",
+                samples.len(),
+                channels
+            )));
+        }
+        if sample_rate == 0 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "Audio sample rate must be positive",
+            ));
+        }
+        let duration = samples.len() as f32 / channels as f32 / sample_rate as f32;
+        let length = self.output_duration(duration).ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "Time remap never reaches the end of the audio",
+            )
+        })?;
+        let frames = (length * sample_rate as f32).round() as usize;
+        let source_time = |t: f32| self.source_time(t);
+        Ok(if self.maintain_pitch {
+            wsola(samples, channels, sample_rate, source_time, frames)
+        } else {
+            varispeed(samples, channels, sample_rate, source_time, frames)
+        })
+    }
+
+    fn curve_at(&self, keyframes: &[RemapKeyframe], time: f32) -> f32 {
+        let index = keyframes.partition_point(|k| k.time <= time);
+        match (
+            index.checked_sub(1).map(|i| &keyframes[i]),
+            keyframes.get(index),
+        ) {
+            (None, Some(first)) => first.source_time,
+            (Some(k1), Some(k2)) => {
+                let t = (time - k1.time) / (k2.time - k1.time);
+                let eased = crate::utils::ease_function(&k1.ease, t);
+                k1.source_time + (k2.source_time - k1.source_time) * eased
+            }
+            (Some(last), None) => last.source_time,
+            (None, None) => time,
+        }
+    }
+
+    /// Ease of the segment running through `time`
+    fn ease_at(keyframes: &[RemapKeyframe], time: f32) -> String {
+        let index = keyframes.partition_point(|k| k.time <= time);
+        index
+            .checked_sub(1)
+            .map_or("linear".to_string(), |i| keyframes[i].ease.clone())
+    }
+
+    /// Current remap curve, baking the speed ramp into keys if none are set
+    fn curve(&self) -> PyResult<Vec<RemapKeyframe>> {
+        if !self.keyframes.is_empty() {
+            return Ok(self.keyframes.clone());
+        }
+        let duration = self.source_duration.ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "Set source_duration or add remap keyframes before editing the time mapping",
+            )
+        })?;
+        let end = self.speed_ramp.output_time(duration).ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "Speed ramp never reaches the end of the source",
+            )
+        })?;
+        let mut times = vec![0.0];
+        times.extend(
+            self.speed_ramp
+                .keyframes
+                .iter()
+                .map(|k| k.time)
+                .filter(|&t| t > 0.0 && t < end),
+        );
+        times.push(end);
+        let mut keyframes: Vec<RemapKeyframe> = times
+            .windows(2)
+            .flat_map(|pair| {
+                let step = (pair[1] - pair[0]) / BAKE_STEPS as f32;
+                (0..BAKE_STEPS).map(move |i| pair[0] + step * i as f32)
+            })
+            .map(|time| RemapKeyframe {
+                time,
+                source_time: self.speed_ramp.source_time(time),
+                ease: "linear".to_string(),
+            })
+            .collect();
+        keyframes.push(RemapKeyframe {
+            time: end,
+            source_time: duration,
+            ease: "linear".to_string(),
+        });
+        Ok(keyframes)
+    }
+
+    /// Reject curves that show source times outside `0..source_duration`
+    fn check(&self, keyframes: &[RemapKeyframe]) -> PyResult<()> {
+        let Some(duration) = self.source_duration else {
+            return Ok(());
+        };
+        let check_point = |time: f32, source: f32| {
+            if (-1e-4..=duration + 1e-4).contains(&source) {
+                return Ok(());
+            }
+            Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Time remap shows source time {:.3}s at {:.3}s, outside the source's {:.3}s",
+                source, time, duration
+            )))
+        };
+        for k in keyframes {
+            check_point(k.time, k.source_time)?;
+        }
+        for pair in keyframes.windows(2) {
+            for i in 1..VALIDATION_SAMPLES {
+                let t = pair[0].time
+                    + (pair[1].time - pair[0].time) * i as f32 / VALIDATION_SAMPLES as f32;
+                check_point(t, self.curve_at(keyframes, t))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Validate and store a new curve
+    fn commit(&mut self, keyframes: Vec<RemapKeyframe>) -> PyResult<()> {
+        self.check(&keyframes)?;
+        self.keyframes = keyframes;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 1x1 frames whose red channel is the frame number / 10
+    fn numbered(count: u32) -> Vec<Frame<f32>> {
+        (0..count)
+            .map(|i| Frame::from_raw(1, 1, vec![i as f32 / 10.0, 0.0, 0.0, 1.0]).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn speed_curve_integrates_into_source_time() {
+        let mut ramp = SpeedRamp::new();
+        assert!((ramp.source_time(3.0) - 3.0).abs() < 1e-4);
+
+        // Linear 1.0 -> 0.5 over two seconds, then half speed
+        ramp.add_keyframe(2.0, 0.5, "linear".to_string());
+        assert!((ramp.source_time(2.0) - 1.5).abs() < 1e-4);
+        assert!((ramp.source_time(4.0) - 2.5).abs() < 1e-4);
+        assert!((ramp.output_time(2.5).unwrap() - 4.0).abs() < 1e-3);
+
+        ramp.add_keyframe(5.0, 0.0, "linear".to_string());
+        assert_eq!(ramp.output_time(100.0), None);
+    }
+
+    #[test]
+    fn frame_blending_weights_neighbours() {
+        let mut ramp = SpeedRamp::new();
+        ramp.keyframes[0].speed = 0.5;
+        assert_eq!(ramp.frame_weights(3, 10.0), vec![(2, 1.0)]);
+        ramp.frame_blending = true;
+        assert_eq!(ramp.frame_weights(3, 10.0), vec![(1, 0.5), (2, 0.5)]);
+        let frame = ramp.render_frame(&numbered(5), 3, 10.0).unwrap();
+        assert!((frame.data[0] - 0.15).abs() < 1e-5);
+
+        // Double speed averages the two frames each output frame spans
+        ramp.keyframes[0].speed = 2.0;
+        let weights = ramp.frame_weights(1, 10.0);
+        assert_eq!(weights.iter().map(|w| w.0).collect::<Vec<_>>(), vec![2, 3]);
+        let frame = ramp.render_frame(&numbered(5), 1, 10.0).unwrap();
+        assert!((frame.data[0] - 0.25).abs() < 1e-4);
+
+        for fps in [0.0, -24.0, f32::NAN] {
+            assert!(ramp.source_frames(1, fps).is_err());
+            assert!(ramp
+                .py_render_frame(vec![vec![0; 4]], 1, 1, 1, fps)
+                .is_err());
+        }
+    }
+
+    fn remap(duration: f32) -> TimeRemap {
+        let mut remap = TimeRemap::new();
+        remap.source_duration = Some(duration);
+        remap
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-3
+    }
+
+    #[test]
+    fn remap_keyframes_map_source_time_directly() {
+        let mut curve = remap(5.0);
+        curve
+            .add_remap_keyframe(0.0, 0.0, "linear".to_string())
+            .unwrap();
+        curve
+            .add_remap_keyframe(2.0, 4.0, "linear".to_string())
+            .unwrap();
+        assert!(close(curve.source_time(1.0), 2.0));
+        assert!(close(curve.source_time(3.0), 4.0));
+        assert!(close(curve.output_time(3.0).unwrap(), 1.5));
+        assert_eq!(curve.output_time(5.0), None);
+        assert_eq!(curve.output_duration(5.0), Some(2.0));
+
+        // Running past the source is rejected and leaves the curve alone
+        assert!(curve
+            .add_remap_keyframe(3.0, 6.0, "linear".to_string())
+            .is_err());
+        assert!(curve
+            .add_remap_keyframe(3.0, 5.0, "back_out".to_string())
+            .is_ok());
+        assert!(curve
+            .add_remap_keyframe(4.0, 5.0, "linear".to_string())
+            .is_ok());
+        assert!(curve.validate().is_ok());
+        let mut eased = remap(3.0);
+        eased
+            .add_remap_keyframe(0.0, 0.0, "back_out".to_string())
+            .unwrap();
+        assert!(eased
+            .add_remap_keyframe(1.0, 3.0, "linear".to_string())
+            .is_err());
+        assert_eq!(eased.keyframes.len(), 1);
+    }
+
+    #[test]
+    fn freeze_reverse_and_boomerang_edit_the_curve() {
+        let mut frozen = remap(5.0);
+        frozen.freeze_frame(1.0, 2.0).unwrap();
+        assert!(close(frozen.source_time(0.5), 0.5));
+        assert!(close(frozen.source_time(2.0), 1.0));
+        assert!(close(frozen.source_time(3.5), 1.5));
+        assert!(close(frozen.output_time(1.0).unwrap(), 1.0));
+        assert!(close(frozen.output_time(2.0).unwrap(), 4.0));
+        assert!(close(frozen.output_duration(5.0).unwrap(), 7.0));
+
+        let mut reversed = remap(5.0);
+        reversed.reverse(1.0, 3.0).unwrap();
+        assert!(close(reversed.source_time(0.5), 0.5));
+        assert!(close(reversed.source_time(1.5), 2.5));
+        assert!(close(reversed.source_time(2.9), 1.1));
+        assert!(close(reversed.source_time(3.5), 3.5));
+        assert!(close(reversed.output_time(2.0).unwrap(), 2.0));
+        assert!(close(reversed.output_time(4.0).unwrap(), 4.0));
+
+        let mut boomerang = TimeRemap::new();
+        boomerang
+            .add_remap_keyframe(0.0, 0.0, "ease_in".to_string())
+            .unwrap();
+        boomerang
+            .add_remap_keyframe(2.0, 2.0, "linear".to_string())
+            .unwrap();
+        boomerang.boomerang().unwrap();
+        assert!(close(
+            boomerang.source_time(3.0),
+            boomerang.source_time(1.0)
+        ));
+        assert!(close(boomerang.source_time(4.0), 0.0));
+        assert!(close(boomerang.output_time(2.0).unwrap(), 2.0));
+        assert_eq!(boomerang.curve_duration(), Some(4.0));
+
+        // Editing a plain speed ramp needs to know where the source ends
+        assert!(TimeRemap::new().freeze_frame(1.0, 1.0).is_err());
+        assert_eq!(reversed_ease("cubic_in"), "cubic_out");
+        assert_eq!(reversed_ease("ease_in_out"), "ease_in_out");
+    }
+
+    #[test]
+    fn audio_follows_the_picture_retime() {
+        let rate = 1000;
+        let tone: Vec<f32> = (0..rate)
+            .map(|i| (2.0 * std::f32::consts::PI * 50.0 * i as f32 / rate as f32).sin())
+            .collect();
+        let mut slow = TimeRemap::new();
+        slow.set_speed(0.0, 0.5);
+        let stretched = slow.retime_audio(&tone, 1, rate).unwrap();
+        assert!((stretched.len() as i32 - 2 * rate as i32).abs() <= 1);
+        slow.maintain_pitch = true;
+        assert_eq!(
+            slow.retime_audio(&tone, 1, rate).unwrap().len(),
+            stretched.len()
+        );
+        assert!(slow.retime_audio(&tone, 3, rate).is_err());
+        assert!(slow.retime_audio(&tone, 1, 0).is_err());
+    }
 }
@@ -0,0 +1,195 @@
+use std::f32::consts::PI;
+
+/// Playback slower than this fades to silence, so freeze frames are quiet
+/// rather than buzzing on a repeated grain
+const MIN_AUDIBLE_SPEED: f32 = 0.1;
+/// WSOLA grain length in seconds
+const GRAIN_SECONDS: f32 = 0.04;
+
+/// Gain for a local playback speed
+fn audible(speed: f32) -> f32 {
+    ((speed.abs() - MIN_AUDIBLE_SPEED / 2.0) / (MIN_AUDIBLE_SPEED / 2.0)).clamp(0.0, 1.0)
+}
+
+/// Catmull-Rom sample of one channel at a fractional frame position
+fn cubic_at(samples: &[f32], channels: usize, channel: usize, position: f32) -> f32 {
+    let frames = (samples.len() / channels) as isize;
+    let fetch = |i: isize| {
+        if (0..frames).contains(&i) {
+            samples[i as usize * channels + channel]
+        } else {
+            0.0
+        }
+    };
+    let i = position.floor() as isize;
+    let t = position - i as f32;
+    let (p0, p1, p2, p3) = (fetch(i - 1), fetch(i), fetch(i + 1), fetch(i + 2));
+    p1 + 0.5
+        * t
+        * (p2 - p0 + t * (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3 + t * (3.0 * (p1 - p2) + p3 - p0)))
+}
+
+/// Tape-style retiming: the source is read at the speed of the curve, so
+/// pitch follows speed. `samples` are interleaved and `source_time` maps
+/// output seconds to source seconds. Speeding up is not band-limited, so
+/// ramps well above 2x can alias.
+pub fn varispeed<F: Fn(f32) -> f32>(
+    samples: &[f32],
+    channels: usize,
+    sample_rate: u32,
+    source_time: F,
+    output_frames: usize,
+) -> Vec<f32> {
+    let rate = sample_rate as f32;
+    let mut out = Vec::with_capacity(output_frames * channels);
+    for j in 0..output_frames {
+        let t = j as f32 / rate;
+        let position = source_time(t) * rate;
+        let gain = audible((source_time(t + 1.0 / rate) - source_time(t)) * rate);
+        for c in 0..channels {
+            out.push(cubic_at(samples, channels, c, position) * gain);
+        }
+    }
+    out
+}
+
+/// Dot product of two mono spans, skipping samples outside the buffer
+fn similarity(mono: &[f32], a: isize, b: isize, length: usize) -> f32 {
+    let len = mono.len() as isize;
+    (0..length as isize)
+        .step_by(2)
+        .filter(|i| (0..len).contains(&(a + i)) && (0..len).contains(&(b + i)))
+        .map(|i| mono[(a + i) as usize] * mono[(b + i) as usize])
+        .sum()
+}
+
+/// Pitch-preserving retiming by waveform-similarity overlap-add (WSOLA).
+///
+/// Hann-windowed grains are laid down at a fixed output hop. Each grain is
+/// read near the curve's source position, shifted within a quarter grain
+/// to line up with the natural continuation of the previous one so the
+/// overlap adds in phase. Arguments are as for [`varispeed`].
+pub fn wsola<F: Fn(f32) -> f32>(
+    samples: &[f32],
+    channels: usize,
+    sample_rate: u32,
+    source_time: F,
+    output_frames: usize,
+) -> Vec<f32> {
+    let rate = sample_rate as f32;
+    let grain = ((rate * GRAIN_SECONDS) as usize / 2 * 2).max(4);
+    let hop = grain / 2;
+    let tolerance = (grain / 4) as isize;
+    let window: Vec<f32> = (0..grain)
+        .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / grain as f32).cos())
+        .collect();
+    let mono: Vec<f32> = samples
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect();
+    let frames = mono.len() as isize;
+
+    let mut out = vec![0.0; (output_frames + grain) * channels];
+    let mut norm = vec![0.0; output_frames + grain];
+    let mut previous: Option<isize> = None;
+    for start in (0..output_frames).step_by(hop) {
+        let centre = (start + hop) as f32 / rate;
+        let target = (source_time(centre) * rate) as isize - hop as isize;
+        let speed =
+            (source_time(centre + hop as f32 / rate) - source_time(centre)) * rate / hop as f32;
+        let gain = audible(speed);
+
+        let position = match previous {
+            Some(prev) => {
+                let natural = if speed >= 0.0 {
+                    prev + hop as isize
+                } else {
+                    prev - hop as isize
+                };
+                (-tolerance..=tolerance)
+                    .map(|delta| target + delta)
+                    .max_by(|a, b| {
+                        similarity(&mono, natural, *a, grain)
+                            .total_cmp(&similarity(&mono, natural, *b, grain))
+                    })
+                    .unwrap_or(target)
+            }
+            None => target,
+        };
+        for (i, w) in window.iter().enumerate() {
+            let src = position + i as isize;
+            norm[start + i] += w;
+            if !(0..frames).contains(&src) {
+                continue;
+            }
+            for c in 0..channels {
+                out[(start + i) * channels + c] += samples[src as usize * channels + c] * w * gain;
+            }
+        }
+        previous = Some(position);
+    }
+
+    out.truncate(output_frames * channels);
+    for (frame, n) in out.chunks_mut(channels).zip(&norm) {
+        if *n > 1e-3 {
+            frame.iter_mut().for_each(|s| *s /= n);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 8000;
+
+    fn sine(frequency: f32, seconds: f32) -> Vec<f32> {
+        (0..(RATE as f32 * seconds) as usize)
+            .map(|i| (2.0 * PI * frequency * i as f32 / RATE as f32).sin())
+            .collect()
+    }
+
+    /// Frequency from rising zero crossings over the middle half
+    fn frequency(signal: &[f32]) -> f32 {
+        let middle = &signal[signal.len() / 4..signal.len() * 3 / 4];
+        let crossings = middle
+            .windows(2)
+            .filter(|w| w[0] < 0.0 && w[1] >= 0.0)
+            .count();
+        crossings as f32 * RATE as f32 / middle.len() as f32
+    }
+
+    #[test]
+    fn varispeed_shifts_pitch_with_speed() {
+        let source = sine(200.0, 1.0);
+        let out = varispeed(&source, 1, RATE, |t| 2.0 * t, RATE as usize / 2);
+        assert_eq!(out.len(), 4000);
+        assert!((frequency(&out) - 400.0).abs() < 8.0, "{}", frequency(&out));
+
+        // A freeze goes quiet instead of holding a DC level
+        let frozen = varispeed(&source, 1, RATE, |_| 0.3, 800);
+        assert!(frozen.iter().all(|s| s.abs() < 1e-6));
+    }
+
+    #[test]
+    fn wsola_keeps_pitch_when_slowed_down() {
+        let stereo: Vec<f32> = sine(200.0, 1.0)
+            .iter()
+            .flat_map(|s| [*s, *s * 0.5])
+            .collect();
+        let out = wsola(&stereo, 2, RATE, |t| 0.5 * t, 2 * RATE as usize);
+        assert_eq!(out.len(), 2 * 2 * RATE as usize);
+        let left: Vec<f32> = out.iter().step_by(2).copied().collect();
+        assert!(
+            (frequency(&left) - 200.0).abs() < 6.0,
+            "{}",
+            frequency(&left)
+        );
+        // Grains overlap in phase, so the level holds up
+        let middle = &left[4000..12000];
+        let rms = (middle.iter().map(|s| s * s).sum::<f32>() / middle.len() as f32).sqrt();
+        assert!(rms > 0.6, "{}", rms);
+        assert!(out.chunks(2).all(|f| (f[1] - 0.5 * f[0]).abs() < 1e-4));
+    }
+}
@@ -1,3 +1,6 @@
+use crate::color_management::ColorSpace;
+use crate::effects::pixelate_frame;
+use crate::frame::{from_f32_vec, to_f32_vec, Pixel};
 use pyo3::prelude::*;
 use serde::{Deserialize, Serialize};
 
@@ -48,17 +51,24 @@ impl Transition {
     }
 }
 
-/// Apply transition effect between two scenes
-pub fn apply_transition(
+/// Apply transition effect between two scenes.
+///
+/// Frames of any pixel format are mixed as floats and returned in the same
+/// format. Both frames are encoded in `space`, whose transfer function the
+/// fade uses to mix in linear light.
+pub fn apply_transition<P: Pixel>(
     transition_type: &str,
     progress: f32,
-    from_frame: &[u8],
-    to_frame: &[u8],
+    from_frame: &[P],
+    to_frame: &[P],
     width: u32,
     height: u32,
-) -> Vec<u8> {
-    match transition_type {
-        "fade" => fade_transition(progress, from_frame, to_frame),
+    space: ColorSpace,
+) -> Vec<P> {
+    let from_frame = &to_f32_vec(from_frame)[..];
+    let to_frame = &to_f32_vec(to_frame)[..];
+    let out = match transition_type {
+        "fade" => fade_transition(progress, from_frame, to_frame, space),
         "slide_left" => {
             slide_transition(progress, from_frame, to_frame, width, height, true, false)
         }
@@ -75,27 +85,38 @@ pub fn apply_transition(
         "wipe_right" => {
             wipe_transition(progress, from_frame, to_frame, width, height, false, false)
         }
-        _ => fade_transition(progress, from_frame, to_frame),
-    }
+        "pixelate" => pixelate_transition(progress, from_frame, to_frame, width, height),
+        _ => fade_transition(progress, from_frame, to_frame, space),
+    };
+    from_f32_vec(&out)
 }
 
-fn fade_transition(progress: f32, from: &[u8], to: &[u8]) -> Vec<u8> {
+/// Cross-dissolve in linear light; alpha is mixed directly
+fn fade_transition(progress: f32, from: &[f32], to: &[f32], space: ColorSpace) -> Vec<f32> {
     let alpha = progress.clamp(0.0, 1.0);
+    let transfer = space.transfer();
     from.iter()
         .zip(to.iter())
-        .map(|(f, t)| ((*f as f32) * (1.0 - alpha) + (*t as f32) * alpha) as u8)
+        .enumerate()
+        .map(|(i, (f, t))| {
+            if i % 4 == 3 {
+                return f * (1.0 - alpha) + t * alpha;
+            }
+            let v = transfer.decode(*f) * (1.0 - alpha) + transfer.decode(*t) * alpha;
+            transfer.encode(v)
+        })
         .collect()
 }
 
 fn slide_transition(
     progress: f32,
-    from: &[u8],
-    to: &[u8],
+    from: &[f32],
+    to: &[f32],
     _width: u32,
     _height: u32,
     _left: bool,
     _vertical: bool,
-) -> Vec<u8> {
+) -> Vec<f32> {
     // Simplified implementation - actual would handle pixel positioning
     if progress < 0.5 {
         from.to_vec()
@@ -106,12 +127,12 @@ fn slide_transition(
 
 fn zoom_transition(
     progress: f32,
-    from: &[u8],
-    to: &[u8],
+    from: &[f32],
+    to: &[f32],
     _width: u32,
     _height: u32,
     _zoom_in: bool,
-) -> Vec<u8> {
+) -> Vec<f32> {
     // Simplified implementation
     if progress < 0.5 {
         from.to_vec()
@@ -122,13 +143,13 @@ fn zoom_transition(
 
 fn wipe_transition(
     progress: f32,
-    from: &[u8],
-    to: &[u8],
+    from: &[f32],
+    to: &[f32],
     _width: u32,
     _height: u32,
     _left: bool,
     _vertical: bool,
-) -> Vec<u8> {
+) -> Vec<f32> {
     // Simplified implementation
     if progress < 0.5 {
         from.to_vec()
@@ -136,3 +157,25 @@ fn wipe_transition(
         to.to_vec()
     }
 }
+
+/// Blocks grow until the midpoint, where the scenes swap, then shrink again
+fn pixelate_transition(
+    progress: f32,
+    from: &[f32],
+    to: &[f32],
+    width: u32,
+    height: u32,
+) -> Vec<f32> {
+    let progress = progress.clamp(0.0, 1.0);
+    let max_block = (width.min(height) / 16).max(1) as f32;
+    let peak = 1.0 - (progress * 2.0 - 1.0).abs();
+    let block = (1.0 + (max_block - 1.0) * peak).round() as u32;
+
+    let mut frame = if progress < 0.5 {
+        from.to_vec()
+    } else {
+        to.to_vec()
+    };
+    pixelate_frame(&mut frame, width, height, block);
+    frame
+}
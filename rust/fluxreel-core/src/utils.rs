@@ -203,10 +203,11 @@ pub fn parse_color(color: &str) -> (u8, u8, u8, u8) {
 
 /// Convert RGB to HSL
 pub fn rgb_to_hsl(r: u8, g: u8, b: u8) -> (f32, f32, f32) {
-    let r = r as f32 / 255.0;
-    let g = g as f32 / 255.0;
-    let b = b as f32 / 255.0;
+    rgb_to_hsl_f32(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0)
+}
 
+/// Convert 0-1 RGB to HSL (hue in degrees, saturation and lightness 0-1)
+pub fn rgb_to_hsl_f32(r: f32, g: f32, b: f32) -> (f32, f32, f32) {
     let max = r.max(g.max(b));
     let min = r.min(g.min(b));
     let delta = max - min;
@@ -237,6 +238,16 @@ pub fn rgb_to_hsl(r: u8, g: u8, b: u8) -> (f32, f32, f32) {
 
 /// Convert HSL to RGB
 pub fn hsl_to_rgb(h: f32, s: f32, l: f32) -> (u8, u8, u8) {
+    let (r, g, b) = hsl_to_rgb_f32(h, s, l);
+    (
+        (r * 255.0).clamp(0.0, 255.0) as u8,
+        (g * 255.0).clamp(0.0, 255.0) as u8,
+        (b * 255.0).clamp(0.0, 255.0) as u8,
+    )
+}
+
+/// Convert HSL to 0-1 RGB
+pub fn hsl_to_rgb_f32(h: f32, s: f32, l: f32) -> (f32, f32, f32) {
     let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
     let x = c * (1.0 - ((h / 60.0) % 2.0 - 1.0).abs());
     let m = l - c / 2.0;
@@ -255,11 +266,25 @@ pub fn hsl_to_rgb(h: f32, s: f32, l: f32) -> (u8, u8, u8) {
         (c, 0.0, x)
     };
 
-    (
-        ((r + m) * 255.0).clamp(0.0, 255.0) as u8,
-        ((g + m) * 255.0).clamp(0.0, 255.0) as u8,
-        ((b + m) * 255.0).clamp(0.0, 255.0) as u8,
-    )
+    (r + m, g + m, b + m)
+}
+
+/// Decode an sRGB-encoded channel (0-1) to linear light
+pub fn srgb_to_linear(v: f32) -> f32 {
+    if v <= 0.04045 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Encode a linear-light channel to sRGB (0-1 in, 0-1 out for in-range values)
+pub fn linear_to_srgb(v: f32) -> f32 {
+    if v <= 0.0031308 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1.0 / 2.4) - 0.055
+    }
 }
 
 /// Interpolate between two values
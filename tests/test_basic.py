@@ -53,8 +53,9 @@ def test_scene():
 
 def test_audio_track():
     """Test Audio track creation"""
-    audio = Audio("assets/test.mp3")
+    audio = Audio("assets/test.mp3", lazy=True)
     assert audio.path == "assets/test.mp3"
+    assert not audio.loaded
 
 
 def test_available_helpers():